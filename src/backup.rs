@@ -0,0 +1,227 @@
+/*
+ * tasklog - A command-line task tracking tool.
+ *
+ * Copyright (C) 2024 Lucas M. de Jong Larrarte
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with this program. If not, see <https://www.gnu.org/licenses/>.
+ */
+#[cfg(test)]
+mod test;
+
+use std::collections::BTreeMap;
+use std::fs;
+use std::io::{ErrorKind, Read};
+use std::path::{Path, PathBuf};
+use std::thread;
+use std::time::Duration;
+
+use chrono::{Local, NaiveDate};
+use flate2::read::GzDecoder;
+use flate2::write::GzEncoder;
+use flate2::Compression;
+
+use crate::journal::JournalEntry;
+use crate::storage::{file_io, write_atomically};
+use crate::{schema, Storage, TaskError, TaskManager, TaskResult};
+use crate::{CATALOGUE_DOCUMENT, HISTORY_DOCUMENT};
+
+/// Name of the configuration file inside an archive.
+const CONFIG_ENTRY: &str = "settings.toml";
+
+/// Names of the storage documents kept in a backup.
+pub const DOCUMENTS: [&str; 2] = [CATALOGUE_DOCUMENT, HISTORY_DOCUMENT];
+
+/// Prefix of the names of automatic backups.
+const AUTO_PREFIX: &str = "auto-";
+
+/// A copy of all tasks, journals, documents and the configuration, stored as a gzip-compressed
+/// tar archive.
+///
+/// Days are stored as `days/YYYY-MM-DD.json` in the current schema, journals as
+/// `journal/YYYY-MM-DD.jsonl` and the documents of [DOCUMENTS] as `documents/NAME.json`,
+/// whatever the storage they were taken from.
+#[derive(Debug, Default, PartialEq)]
+pub struct Backup {
+    pub days: BTreeMap<NaiveDate, TaskManager>,
+    pub journals: BTreeMap<NaiveDate, Vec<JournalEntry>>,
+    pub documents: BTreeMap<String, String>,
+    pub config: Option<String>,
+}
+impl Backup {
+    /// Collects all data from the storage, along with the given configuration file contents.
+    pub fn collect(storage: &dyn Storage, config: Option<String>) -> TaskResult<Self> {
+        Self::collect_days(storage, config, false)
+    }
+
+    /// Collects all data from the storage like [Backup::collect], but leaves out the days that
+    /// cannot be loaded, so that damaged data doesn't prevent taking a backup.
+    pub fn collect_readable(storage: &dyn Storage, config: Option<String>) -> TaskResult<Self> {
+        Self::collect_days(storage, config, true)
+    }
+
+    /// Collects all data from the storage, leaving out the days that cannot be loaded if
+    /// `skip_unreadable` is set.
+    fn collect_days(
+        storage: &dyn Storage,
+        config: Option<String>,
+        skip_unreadable: bool,
+    ) -> TaskResult<Self> {
+        let mut backup = Backup {
+            config,
+            ..Backup::default()
+        };
+        for date in storage.dates()? {
+            match storage.load(date) {
+                Ok(tasks) => {
+                    backup.days.insert(date, tasks);
+                }
+                Err(TaskError::InvalidFile(..) | TaskError::InvalidData(..)) if skip_unreadable => {
+                }
+                Err(e) => return Err(e),
+            }
+        }
+        for date in storage.journal_dates()? {
+            backup.journals.insert(date, storage.journal(date)?);
+        }
+        for name in DOCUMENTS {
+            if let Some(data) = storage.load_document(name)? {
+                backup.documents.insert(name.to_string(), data);
+            }
+        }
+        Ok(backup)
+    }
+
+    /// Writes the backup as an archive at the given path.
+    pub fn write(&self, path: &Path) -> TaskResult<()> {
+        let encoder = GzEncoder::new(vec![], Compression::default());
+        let mut archive = tar::Builder::new(encoder);
+        for (date, tasks) in &self.days {
+            let name = date.format("days/%F.json").to_string();
+            append(&mut archive, &name, schema::to_string(tasks).as_bytes())
+                .map_err(file_io(path))?;
+        }
+        for (date, entries) in &self.journals {
+            let name = date.format("journal/%F.jsonl").to_string();
+            let mut data = String::new();
+            for entry in entries {
+                data += &serde_json::to_string(entry).expect("should be able to serialize events");
+                data.push('\n');
+            }
+            append(&mut archive, &name, data.as_bytes()).map_err(file_io(path))?;
+        }
+        for (name, data) in &self.documents {
+            let name = format!("documents/{name}.json");
+            append(&mut archive, &name, data.as_bytes()).map_err(file_io(path))?;
+        }
+        if let Some(config) = &self.config {
+            append(&mut archive, CONFIG_ENTRY, config.as_bytes()).map_err(file_io(path))?;
+        }
+        let data = archive
+            .into_inner()
+            .and_then(|encoder| encoder.finish())
+            .map_err(file_io(path))?;
+        write_atomically(path, &data)
+    }
+
+    /// Reads the archive at the given path.
+    pub fn read(path: &Path) -> TaskResult<Self> {
+        let file = fs::File::open(path).map_err(file_io(path))?;
+        let mut archive = tar::Archive::new(GzDecoder::new(file));
+        let mut backup = Backup::default();
+        for entry in archive.entries().map_err(file_io(path))? {
+            let mut entry = entry.map_err(file_io(path))?;
+            let name = entry
+                .path()
+                .map_err(file_io(path))?
+                .to_string_lossy()
+                .into_owned();
+            let mut data = String::new();
+            entry.read_to_string(&mut data).map_err(file_io(path))?;
+            let invalid = |e| TaskError::InvalidFile(path.join(&name), e);
+            if let Ok(date) = NaiveDate::parse_from_str(&name, "days/%F.json") {
+                let (tasks, _) = schema::from_str(&data).map_err(invalid)?;
+                backup.days.insert(date, tasks);
+            } else if let Ok(date) = NaiveDate::parse_from_str(&name, "journal/%F.jsonl") {
+                let entries = data
+                    .lines()
+                    .filter(|line| !line.is_empty())
+                    .map(serde_json::from_str)
+                    .collect::<Result<_, _>>()
+                    .map_err(invalid)?;
+                backup.journals.insert(date, entries);
+            } else if let Some(document) = name
+                .strip_prefix("documents/")
+                .and_then(|name| name.strip_suffix(".json"))
+                .filter(|name| DOCUMENTS.contains(name))
+            {
+                backup.documents.insert(document.to_string(), data);
+            } else if name == CONFIG_ENTRY {
+                backup.config = Some(data);
+            }
+        }
+        Ok(backup)
+    }
+}
+
+/// Appends a regular file with the given name and contents to the archive.
+fn append<W: std::io::Write>(
+    archive: &mut tar::Builder<W>,
+    name: &str,
+    data: &[u8],
+) -> std::io::Result<()> {
+    let mut header = tar::Header::new_gnu();
+    header.set_size(data.len() as u64);
+    header.set_mode(0o644);
+    header.set_mtime(Local::now().timestamp().max(0) as u64);
+    header.set_cksum();
+    archive.append_data(&mut header, name, data)
+}
+
+/// Returns the default file name of a backup taken now, down to the millisecond so that names
+/// still sort by time.
+pub fn file_name() -> String {
+    Local::now()
+        .format("tasklog-%Y%m%d-%H%M%S-%3f.tar.gz")
+        .to_string()
+}
+
+/// Writes an automatic backup into `dir` before a destructive command, then deletes the oldest
+/// automatic backups so that at most `keep` remain. Returns the path of the new backup.
+pub fn auto_backup(backup: &Backup, dir: &Path, keep: usize) -> TaskResult<PathBuf> {
+    fs::create_dir_all(dir).map_err(file_io(dir))?;
+    let mut path = dir.join(format!("{AUTO_PREFIX}{}", file_name()));
+    // Never overwrite a backup taken within the same millisecond.
+    while path.exists() {
+        thread::sleep(Duration::from_millis(1));
+        path = dir.join(format!("{AUTO_PREFIX}{}", file_name()));
+    }
+    backup.write(&path)?;
+    let mut backups = vec![];
+    for entry in fs::read_dir(dir).map_err(file_io(dir))? {
+        let entry = entry.map_err(file_io(dir))?;
+        let name = entry.file_name().to_string_lossy().into_owned();
+        if name.starts_with(AUTO_PREFIX) && name.ends_with(".tar.gz") {
+            backups.push(entry.path());
+        }
+    }
+    backups.sort();
+    let excess = backups.len().saturating_sub(keep);
+    for old in &backups[..excess] {
+        match fs::remove_file(old) {
+            Err(e) if e.kind() != ErrorKind::NotFound => Err(TaskError::FileIO(old.clone(), e))?,
+            _ => {}
+        }
+    }
+    Ok(path)
+}
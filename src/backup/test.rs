@@ -0,0 +1,133 @@
+/*
+ * tasklog - A command-line task tracking tool.
+ *
+ * Copyright (C) 2024 Lucas M. de Jong Larrarte
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with this program. If not, see <https://www.gnu.org/licenses/>.
+ */
+use super::*;
+use crate::journal::Event;
+use crate::test_util::date;
+use crate::{JsonDirStorage, MemoryStorage};
+use chrono::Duration;
+use std::time::Duration as StdDuration;
+use tempfile::TempDir;
+
+fn storage() -> MemoryStorage {
+    let now = Local::now() - Duration::minutes(30);
+    let storage = MemoryStorage::new();
+    let mut tasks = TaskManager::new();
+    tasks.start_new_task("Test".to_string(), now).unwrap();
+    let events: Vec<_> = tasks
+        .take_events()
+        .into_iter()
+        .map(|event| JournalEntry::new(event, now))
+        .collect();
+    storage.save(&tasks, date(16)).unwrap();
+    storage.save(&TaskManager::new(), date(17)).unwrap();
+    storage.append_journal(date(16), &events).unwrap();
+    storage
+        .save_document(CATALOGUE_DOCUMENT, r#"{"tasks":[]}"#)
+        .unwrap();
+    storage
+        .save_document(HISTORY_DOCUMENT, r#"{"undo":[],"redo":[]}"#)
+        .unwrap();
+    storage.save_document("other", "{}").unwrap();
+    storage
+}
+
+#[test]
+fn test_backup_collect() {
+    let backup = Backup::collect(&storage(), Some("config".to_string())).unwrap();
+    assert_eq!(
+        backup.days.keys().collect::<Vec<_>>(),
+        vec![&date(16), &date(17)]
+    );
+    assert_eq!(backup.days[&date(16)].running_task(), Some("Test"));
+    assert_eq!(backup.journals.keys().collect::<Vec<_>>(), vec![&date(16)]);
+    assert!(matches!(
+        backup.journals[&date(16)][0].event,
+        Event::Started { .. }
+    ));
+    assert_eq!(
+        backup.documents.keys().collect::<Vec<_>>(),
+        vec![CATALOGUE_DOCUMENT, HISTORY_DOCUMENT]
+    );
+    assert_eq!(backup.documents[CATALOGUE_DOCUMENT], r#"{"tasks":[]}"#);
+    assert_eq!(backup.config.as_deref(), Some("config"));
+}
+
+#[test]
+fn test_backup_collect_readable() {
+    let dir = TempDir::new().unwrap();
+    let storage = JsonDirStorage::new(dir.path(), StdDuration::from_millis(100)).unwrap();
+    storage.save(&TaskManager::new(), date(17)).unwrap();
+    fs::write(dir.path().join("2024-07-16.json"), "{}").unwrap();
+    assert!(Backup::collect(&storage, None).is_err());
+    let backup = Backup::collect_readable(&storage, None).unwrap();
+    assert_eq!(backup.days.keys().collect::<Vec<_>>(), vec![&date(17)]);
+}
+
+#[test]
+fn test_backup_write_and_read() {
+    let dir = TempDir::new().unwrap();
+    let path = dir.path().join("backup.tar.gz");
+    let backup = Backup::collect(&storage(), Some("config".to_string())).unwrap();
+    backup.write(&path).unwrap();
+    let read = Backup::read(&path).unwrap();
+    assert_eq!(read.documents.len(), 2);
+    assert_eq!(read, backup);
+}
+
+#[test]
+fn test_backup_read_when_not_an_archive() {
+    let dir = TempDir::new().unwrap();
+    let path = dir.path().join("backup.tar.gz");
+    fs::write(&path, "not an archive").unwrap();
+    assert!(matches!(Backup::read(&path), Err(TaskError::FileIO(..))));
+}
+
+#[test]
+fn test_auto_backup_keeps_most_recent() {
+    let dir = TempDir::new().unwrap();
+    for day in 1..=4 {
+        let name = format!("{AUTO_PREFIX}tasklog-2024070{day}-120000.tar.gz");
+        fs::write(dir.path().join(name), "").unwrap();
+    }
+    fs::write(dir.path().join("tasklog-20240701-120000.tar.gz"), "").unwrap();
+    let backup = Backup::collect(&storage(), None).unwrap();
+    let path = auto_backup(&backup, dir.path(), 3).unwrap();
+    let mut names: Vec<_> = fs::read_dir(dir.path())
+        .unwrap()
+        .map(|entry| entry.unwrap().file_name().into_string().unwrap())
+        .collect();
+    names.sort();
+    assert_eq!(names.len(), 4);
+    assert_eq!(names[0], "auto-tasklog-20240703-120000.tar.gz");
+    assert_eq!(names[1], "auto-tasklog-20240704-120000.tar.gz");
+    assert_eq!(dir.path().join(&names[2]), path);
+    assert_eq!(names[3], "tasklog-20240701-120000.tar.gz");
+    assert_eq!(Backup::read(&path).unwrap(), backup);
+}
+
+#[test]
+fn test_auto_backup_keeps_backups_taken_at_once() {
+    let dir = TempDir::new().unwrap();
+    let backup = Backup::collect(&storage(), None).unwrap();
+    let paths: Vec<_> = (0..3)
+        .map(|_| auto_backup(&backup, dir.path(), 3).unwrap())
+        .collect();
+    assert!(paths[0] < paths[1] && paths[1] < paths[2]);
+    assert_eq!(fs::read_dir(dir.path()).unwrap().count(), 3);
+}
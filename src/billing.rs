@@ -0,0 +1,320 @@
+/*
+ * tasklog - A command-line task tracking tool.
+ *
+ * Copyright (C) 2024 Lucas M. de Jong Larrarte
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with this program. If not, see <https://www.gnu.org/licenses/>.
+ */
+#[cfg(test)]
+mod test;
+
+use std::fmt;
+
+use chrono::{Duration, NaiveDate};
+use serde::{Deserialize, Serialize};
+
+use crate::report::csv_field;
+use crate::task_manager::format_duration;
+use crate::task_path::{self, SEPARATOR};
+
+/// Billing settings, read from the `[billing]` table of the configuration file.
+#[derive(Debug, Serialize, Deserialize, Default, Clone, PartialEq)]
+pub struct BillingConfig {
+    /// The currency of the rates, shown on invoices. E.g. EUR.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub currency: Option<String>,
+    /// The tax added to invoices, in percent of the subtotal.
+    #[serde(default)]
+    pub tax_rate: f64,
+    /// The rules setting the rates of tasks, see [BillingConfig::rate].
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub rates: Vec<RateRule>,
+}
+impl BillingConfig {
+    /// Returns the hourly rate of a task with the given tags, or `None` if it is not billable.
+    ///
+    /// The rate and the billable flag are each taken from the last rule that applies to the task
+    /// and sets them, so general rules should come before more specific ones. A task is billable
+    /// if it has a rate, unless a rule says otherwise.
+    pub fn rate(&self, task: &str, tags: &[String]) -> Option<f64> {
+        let mut rate = None;
+        let mut billable = None;
+        for rule in self.rates.iter().filter(|rule| rule.applies_to(task, tags)) {
+            rate = rule.rate.or(rate);
+            billable = rule.billable.or(billable);
+        }
+        match billable.unwrap_or(rate.is_some()) {
+            true => Some(rate.unwrap_or(0.0)),
+            false => None,
+        }
+    }
+}
+
+/// A rule setting the hourly rate of tasks, or whether they are billable.
+#[derive(Debug, Serialize, Deserialize, Default, Clone, PartialEq)]
+#[serde(deny_unknown_fields)]
+pub struct RateRule {
+    /// The task, or the project or client, whose tasks the rule applies to. E.g. acme/website.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub task: Option<String>,
+    /// The tag of the tasks the rule applies to.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub tag: Option<String>,
+    /// The hourly rate of the tasks.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub rate: Option<f64>,
+    /// Whether the tasks are billable.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub billable: Option<bool>,
+}
+impl RateRule {
+    /// Checks if the rule applies to a task with the given tags. A rule without a task or tag
+    /// applies to every task.
+    fn applies_to(&self, task: &str, tags: &[String]) -> bool {
+        let task_matches = self
+            .task
+            .as_ref()
+            .is_none_or(|path| task_path::is_within(task, path));
+        let tag_matches = self.tag.as_ref().is_none_or(|tag| tags.contains(tag));
+        task_matches && tag_matches
+    }
+}
+
+/// The time spent on a task, with the tags it had at the time.
+#[derive(Debug, Clone, PartialEq)]
+pub struct TaskTime {
+    pub name: String,
+    pub tags: Vec<String>,
+    pub time: Duration,
+}
+
+/// The amounts due for the billable time spent on tasks, grouped by project.
+#[derive(Debug, Serialize, Clone, PartialEq)]
+pub struct Invoice {
+    /// The client billed, if the invoice is limited to one.
+    pub client: Option<String>,
+    pub from: NaiveDate,
+    pub to: NaiveDate,
+    pub currency: Option<String>,
+    pub projects: Vec<InvoiceProject>,
+    pub subtotal: f64,
+    /// The tax rate, in percent.
+    pub tax_rate: f64,
+    pub tax: f64,
+    pub total: f64,
+    /// The time spent on tasks that are not billable, in minutes.
+    pub non_billable_minutes: i64,
+}
+
+/// The lines of an invoice for the tasks of a project.
+#[derive(Debug, Serialize, Clone, PartialEq)]
+pub struct InvoiceProject {
+    /// The path of the project, which is `None` for tasks that are not part of one.
+    pub name: Option<String>,
+    pub lines: Vec<InvoiceLine>,
+    pub subtotal: f64,
+}
+
+/// The amount due for the time spent on a task at a rate.
+#[derive(Debug, Serialize, Clone, PartialEq)]
+pub struct InvoiceLine {
+    pub task: String,
+    pub minutes: i64,
+    pub rate: f64,
+    pub amount: f64,
+}
+
+impl Invoice {
+    /// Builds the invoice for the time spent on tasks from `from` to `to`, leaving out the tasks
+    /// of other clients than `client`. The client of a task is the first level of its path.
+    ///
+    /// The time of a task is billed per rate, so a task whose rate changed gets a line for each
+    /// rate. Amounts are rounded to cents per line.
+    pub fn new(
+        config: &BillingConfig,
+        client: Option<&str>,
+        from: NaiveDate,
+        to: NaiveDate,
+        times: impl IntoIterator<Item = TaskTime>,
+    ) -> Self {
+        let mut billed: Vec<(String, f64, Duration)> = vec![];
+        let mut non_billable = Duration::zero();
+        for task in times {
+            if client.is_some_and(|client| task_path::ancestor(&task.name, 1) != client) {
+                continue;
+            }
+            let Some(rate) = config.rate(&task.name, &task.tags) else {
+                non_billable += task.time;
+                continue;
+            };
+            match billed
+                .iter_mut()
+                .find(|(name, other, _)| *name == task.name && *other == rate)
+            {
+                Some((_, _, time)) => *time += task.time,
+                None => billed.push((task.name, rate, task.time)),
+            }
+        }
+        let mut projects: Vec<InvoiceProject> = vec![];
+        for (task, rate, time) in billed {
+            let minutes = time.num_minutes();
+            let line = InvoiceLine {
+                amount: round_cents(minutes as f64 / 60.0 * rate),
+                task,
+                minutes,
+                rate,
+            };
+            let name = project(&line.task);
+            match projects.iter_mut().find(|project| project.name == name) {
+                Some(project) => project.lines.push(line),
+                None => projects.push(InvoiceProject {
+                    name,
+                    lines: vec![line],
+                    subtotal: 0.0,
+                }),
+            }
+        }
+        for project in &mut projects {
+            project.subtotal = round_cents(project.lines.iter().map(|line| line.amount).sum());
+        }
+        let subtotal = round_cents(projects.iter().map(|project| project.subtotal).sum());
+        let tax = round_cents(subtotal * config.tax_rate / 100.0);
+        Invoice {
+            client: client.map(str::to_string),
+            from,
+            to,
+            currency: config.currency.clone(),
+            projects,
+            subtotal,
+            tax_rate: config.tax_rate,
+            tax,
+            total: round_cents(subtotal + tax),
+            non_billable_minutes: non_billable.num_minutes(),
+        }
+    }
+
+    /// Renders the invoice as CSV, with a row for each line followed by rows for the subtotal of
+    /// each project, the subtotal, the tax and the total. The `kind` column tells them apart.
+    pub fn to_csv(&self) -> String {
+        let mut csv = "kind,project,task,hours,rate,amount\n".to_string();
+        let mut row = |kind: &str, project: &str, task: &str, hours, rate, amount: f64| {
+            let field =
+                |value: Option<f64>| value.map_or(String::new(), |value| format!("{value:.2}"));
+            csv += &format!(
+                "{kind},{},{},{},{},{amount:.2}\n",
+                csv_field(project),
+                csv_field(task),
+                field(hours),
+                field(rate)
+            );
+        };
+        for project in &self.projects {
+            let name = project.name.as_deref().unwrap_or_default();
+            for line in &project.lines {
+                let hours = line.minutes as f64 / 60.0;
+                row(
+                    "line",
+                    name,
+                    &line.task,
+                    Some(hours),
+                    Some(line.rate),
+                    line.amount,
+                );
+            }
+            row("project", name, "", None, None, project.subtotal);
+        }
+        row("subtotal", "", "", None, None, self.subtotal);
+        row("tax", "", "", None, None, self.tax);
+        row("total", "", "", None, None, self.total);
+        csv
+    }
+}
+impl fmt::Display for Invoice {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let title = match &self.client {
+            Some(client) => format!("Invoice for {client}"),
+            None => "Invoice".to_string(),
+        };
+        writeln!(f, "  {title}, {} to {}", self.from, self.to)?;
+        let tax = format!("Tax ({}%)", self.tax_rate);
+        let total = match &self.currency {
+            Some(currency) => format!("Total ({currency})"),
+            None => "Total".to_string(),
+        };
+        let labels = self.projects.iter().flat_map(|project| {
+            let name = project.name.as_deref().map(str::len);
+            let lines = project.lines.iter().map(|line| label(project, line).len());
+            name.into_iter().chain(lines)
+        });
+        let width = labels
+            .chain([tax.len(), total.len(), "Subtotal".len() + 2])
+            .max()
+            .unwrap_or(0);
+        let amounts = self.projects.iter().flat_map(|project| &project.lines);
+        let amounts = amounts.map(|line| line.amount).chain([self.total]);
+        let amount_width = amounts.map(|amount| format!("{amount:.2}").len()).max();
+        let amount_width = amount_width.unwrap_or(0).max(6);
+        let row = |f: &mut fmt::Formatter<'_>, label: &str, hours: &str, rate: &str, amount| {
+            writeln!(
+                f,
+                "    {label:<width$} {hours:>6} {rate:>8} {amount:>amount_width$.2}"
+            )
+        };
+        for project in &self.projects {
+            writeln!(f)?;
+            if let Some(name) = &project.name {
+                writeln!(f, "    {name}")?;
+            }
+            for line in &project.lines {
+                let label = label(project, line);
+                let hours = format_duration(Duration::minutes(line.minutes));
+                row(f, &label, &hours, &format!("{:.2}", line.rate), line.amount)?;
+            }
+            if project.name.is_some() {
+                row(f, "  Subtotal", "", "", project.subtotal)?;
+            }
+        }
+        writeln!(f, "    {:=>1$}", "", width + amount_width + 17)?;
+        row(f, "Subtotal", "", "", self.subtotal)?;
+        row(f, &tax, "", "", self.tax)?;
+        row(f, &total, "", "", self.total)?;
+        if self.non_billable_minutes > 0 {
+            let time = format_duration(Duration::minutes(self.non_billable_minutes));
+            writeln!(f, "\n    Non-billable time: {time}")?;
+        }
+        Ok(())
+    }
+}
+
+/// Returns the path of the project a task is part of, which is its parent.
+fn project(task: &str) -> Option<String> {
+    let levels = task_path::levels(task);
+    let (_, parent) = levels.split_last()?;
+    (!parent.is_empty()).then(|| parent.join(&SEPARATOR.to_string()))
+}
+
+/// Returns the label of a line within its project: the name of the task relative to it,
+/// indented under the project.
+fn label(project: &InvoiceProject, line: &InvoiceLine) -> String {
+    let levels = task_path::levels(&line.task);
+    match (&project.name, levels.last()) {
+        (Some(_), Some(last)) => format!("  {last}"),
+        _ => line.task.clone(),
+    }
+}
+
+/// Rounds an amount to cents. A negative zero, such as the sum of no amounts, becomes zero.
+fn round_cents(amount: f64) -> f64 {
+    (amount * 100.0).round() / 100.0 + 0.0
+}
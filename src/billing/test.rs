@@ -0,0 +1,170 @@
+/*
+ * tasklog - A command-line task tracking tool.
+ *
+ * Copyright (C) 2024 Lucas M. de Jong Larrarte
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with this program. If not, see <https://www.gnu.org/licenses/>.
+ */
+use super::*;
+use crate::test_util::date;
+
+fn rule(task: Option<&str>, tag: Option<&str>, rate: Option<f64>) -> RateRule {
+    RateRule {
+        task: task.map(str::to_string),
+        tag: tag.map(str::to_string),
+        rate,
+        billable: None,
+    }
+}
+
+fn config() -> BillingConfig {
+    BillingConfig {
+        currency: Some("EUR".to_string()),
+        tax_rate: 21.0,
+        rates: vec![
+            rule(Some("acme"), None, Some(90.0)),
+            rule(Some("acme/website"), None, Some(100.0)),
+            RateRule {
+                tag: Some("internal".to_string()),
+                billable: Some(false),
+                ..Default::default()
+            },
+        ],
+    }
+}
+
+fn time(name: &str, tags: &[&str], minutes: i64) -> TaskTime {
+    TaskTime {
+        name: name.to_string(),
+        tags: tags.iter().map(|tag| tag.to_string()).collect(),
+        time: Duration::minutes(minutes),
+    }
+}
+
+#[test]
+fn test_rate() {
+    let config = config();
+    let tags = |tags: &[&str]| tags.iter().map(|tag| tag.to_string()).collect::<Vec<_>>();
+    assert_eq!(config.rate("acme/api", &[]), Some(90.0));
+    assert_eq!(config.rate("acme/website/login", &[]), Some(100.0));
+    assert_eq!(config.rate("acme/website", &tags(&["internal"])), None);
+    assert_eq!(config.rate("other/task", &[]), None);
+    let config = BillingConfig {
+        rates: vec![rule(None, Some("billable"), None)],
+        ..Default::default()
+    };
+    assert_eq!(config.rate("coding", &tags(&["billable"])), None);
+}
+
+#[test]
+fn test_rate_when_billable_without_rate() {
+    let config = BillingConfig {
+        rates: vec![RateRule {
+            tag: Some("billable".to_string()),
+            billable: Some(true),
+            ..Default::default()
+        }],
+        ..Default::default()
+    };
+    assert_eq!(config.rate("coding", &["billable".to_string()]), Some(0.0));
+}
+
+#[test]
+fn test_invoice() {
+    let times = vec![
+        time("acme/website/login", &[], 90),
+        time("acme/api", &[], 20),
+        time("acme/website/login", &[], 30),
+        time("acme/website/meeting", &["internal"], 45),
+        time("other/task", &[], 60),
+    ];
+    let invoice = Invoice::new(&config(), Some("acme"), date(1), date(31), times);
+    assert_eq!(
+        invoice.projects,
+        vec![
+            InvoiceProject {
+                name: Some("acme/website".to_string()),
+                lines: vec![InvoiceLine {
+                    task: "acme/website/login".to_string(),
+                    minutes: 120,
+                    rate: 100.0,
+                    amount: 200.0,
+                }],
+                subtotal: 200.0,
+            },
+            InvoiceProject {
+                name: Some("acme".to_string()),
+                lines: vec![InvoiceLine {
+                    task: "acme/api".to_string(),
+                    minutes: 20,
+                    rate: 90.0,
+                    amount: 30.0,
+                }],
+                subtotal: 30.0,
+            },
+        ]
+    );
+    assert_eq!(invoice.subtotal, 230.0);
+    assert_eq!(invoice.tax, 48.3);
+    assert_eq!(invoice.total, 278.3);
+    assert_eq!(invoice.non_billable_minutes, 45);
+}
+
+#[test]
+fn test_invoice_display() {
+    let times = vec![time("acme/api", &[], 20), time("coding", &[], 10)];
+    let config = BillingConfig {
+        rates: vec![rule(None, None, Some(90.0))],
+        ..config()
+    };
+    let invoice = Invoice::new(&config, None, date(1), date(31), times);
+    let expected = [
+        "  Invoice, 2024-07-01 to 2024-07-31",
+        "",
+        "    acme",
+        "      api        00:20    90.00  30.00",
+        "      Subtotal                   30.00",
+        "",
+        "    coding       00:10    90.00  15.00",
+        "    ==================================",
+        "    Subtotal                     45.00",
+        "    Tax (21%)                     9.45",
+        "    Total (EUR)                  54.45",
+    ];
+    assert_eq!(invoice.to_string(), expected.join("\n") + "\n");
+}
+
+#[test]
+fn test_invoice_when_empty() {
+    let invoice = Invoice::new(&config(), None, date(1), date(31), vec![]);
+    assert!(invoice.projects.is_empty());
+    assert!(invoice.subtotal.is_sign_positive());
+    assert!(invoice.total.is_sign_positive());
+    assert!(!invoice.to_string().contains("-0.00"));
+}
+
+#[test]
+fn test_invoice_to_csv() {
+    let times = vec![time("acme/website, v2/login", &[], 30)];
+    let invoice = Invoice::new(&config(), None, date(1), date(31), times);
+    assert_eq!(
+        invoice.to_csv(),
+        "kind,project,task,hours,rate,amount\n\
+         line,\"acme/website, v2\",\"acme/website, v2/login\",0.50,90.00,45.00\n\
+         project,\"acme/website, v2\",,,,45.00\n\
+         subtotal,,,,,45.00\n\
+         tax,,,,,9.45\n\
+         total,,,,,54.45\n"
+    );
+}
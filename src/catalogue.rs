@@ -0,0 +1,176 @@
+/*
+ * tasklog - A command-line task tracking tool.
+ *
+ * Copyright (C) 2024 Lucas M. de Jong Larrarte
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with this program. If not, see <https://www.gnu.org/licenses/>.
+ */
+#[cfg(test)]
+mod test;
+
+use chrono::{Duration, NaiveDate};
+use serde::{Deserialize, Serialize};
+
+use crate::{TaskError, TaskResult};
+
+/// A task known from any day, identified by an ID that stays the same across days and renames.
+#[derive(Debug, Serialize, Deserialize, PartialEq, Clone)]
+pub struct CatalogueTask {
+    pub id: u64,
+    pub name: String,
+    /// Earlier names of the task, which the days recorded before it was renamed still use.
+    #[serde(default)]
+    pub aliases: Vec<String>,
+    /// The first day the task was worked on.
+    pub first_used: NaiveDate,
+    /// The last day the task was worked on.
+    pub last_used: NaiveDate,
+    /// How long the task is expected to take across all days, in minutes.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub estimate_minutes: Option<i64>,
+}
+impl CatalogueTask {
+    /// Returns how long the task is expected to take across all days, if it has an estimate.
+    pub fn estimate(&self) -> Option<Duration> {
+        self.estimate_minutes.map(Duration::minutes)
+    }
+
+    /// Checks if the task is or was called `name`.
+    fn is_called(&self, name: &str) -> bool {
+        self.name == name || self.aliases.iter().any(|alias| alias == name)
+    }
+}
+
+/// The tasks worked on across all days.
+///
+/// Tasks are kept in the order they were last recorded in, so that the last one is the one
+/// worked on most recently.
+#[derive(Debug, Serialize, Deserialize, Default, PartialEq, Clone)]
+pub struct Catalogue {
+    tasks: Vec<CatalogueTask>,
+    /// The ID of the next task added. IDs are never reused.
+    next_id: u64,
+}
+impl Catalogue {
+    /// Creates an empty catalogue.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Returns the tasks, ordered by ID.
+    pub fn tasks(&self) -> Vec<&CatalogueTask> {
+        let mut tasks: Vec<_> = self.tasks.iter().collect();
+        tasks.sort_by_key(|task| task.id);
+        tasks
+    }
+
+    /// Returns the task that is or was called `name`, preferring a task's current name over
+    /// another task's earlier one.
+    pub fn get(&self, name: &str) -> Option<&CatalogueTask> {
+        let current = self.tasks.iter().find(|task| task.name == name);
+        current.or_else(|| self.tasks.iter().find(|task| task.is_called(name)))
+    }
+
+    /// Returns the only task whose name contains `name`, or the task with exactly that name.
+    pub fn find(&self, name: &str) -> TaskResult<&CatalogueTask> {
+        if let Some(task) = self.tasks.iter().find(|task| task.name == name) {
+            return Ok(task);
+        }
+        let mut tasks = self.tasks.iter().filter(|task| task.name.contains(name));
+        match (tasks.next(), tasks.next()) {
+            (None, _) => Err(TaskError::TaskNotFound(name.to_string())),
+            (Some(task), None) => Ok(task),
+            _ => Err(TaskError::MultipleTasksFound),
+        }
+    }
+
+    /// Returns the task worked on most recently, if any.
+    pub fn most_recent(&self) -> Option<&CatalogueTask> {
+        self.tasks.iter().max_by_key(|task| task.last_used)
+    }
+
+    /// Records that the tasks with the given names were worked on on `date`, adding the ones
+    /// not known yet.
+    pub fn record<'a>(&mut self, date: NaiveDate, names: impl IntoIterator<Item = &'a str>) {
+        for name in names {
+            let task = match self.tasks.iter().position(|task| task.name == name) {
+                Some(index) => Some(index),
+                None => self.tasks.iter().position(|task| task.is_called(name)),
+            };
+            let task = match task {
+                Some(index) => {
+                    let mut task = self.tasks.remove(index);
+                    task.first_used = task.first_used.min(date);
+                    task.last_used = task.last_used.max(date);
+                    task
+                }
+                None => {
+                    self.next_id += 1;
+                    CatalogueTask {
+                        id: self.next_id,
+                        name: name.to_string(),
+                        aliases: vec![],
+                        first_used: date,
+                        last_used: date,
+                        estimate_minutes: None,
+                    }
+                }
+            };
+            self.tasks.push(task);
+        }
+    }
+
+    /// Sets or clears the estimate of the only task whose name contains `name`, see [Self::find].
+    /// Returns the name of the task.
+    pub fn set_estimate(&mut self, name: &str, estimate: Option<Duration>) -> TaskResult<String> {
+        let id = self.find(name)?.id;
+        let task = self
+            .tasks
+            .iter_mut()
+            .find(|task| task.id == id)
+            .expect("found task should be in the catalogue");
+        task.estimate_minutes = estimate.map(|estimate| estimate.num_minutes());
+        Ok(task.name.clone())
+    }
+
+    /// Renames the task called `from` to `to`, keeping `from` as an alias, and returns whether it
+    /// did. If a task called `to` is known already, the renamed task is taken to be that one
+    /// instead.
+    pub fn rename(&mut self, from: &str, to: &str) -> bool {
+        if self.get(to).is_some() {
+            return false;
+        }
+        match self.tasks.iter_mut().find(|task| task.name == from) {
+            Some(task) => {
+                let from = std::mem::replace(&mut task.name, to.to_string());
+                task.aliases.push(from);
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Undoes renaming the task called `from` to `to`, if `from` is the alias it was given last.
+    pub fn undo_rename(&mut self, from: &str, to: &str) {
+        let renamed = |task: &&mut CatalogueTask| {
+            task.name == to && task.aliases.last().is_some_and(|alias| alias == from)
+        };
+        if let Some(task) = self.tasks.iter_mut().find(renamed) {
+            task.name = task
+                .aliases
+                .pop()
+                .expect("renamed task should have an alias");
+        }
+    }
+}
@@ -0,0 +1,114 @@
+/*
+ * tasklog - A command-line task tracking tool.
+ *
+ * Copyright (C) 2024 Lucas M. de Jong Larrarte
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with this program. If not, see <https://www.gnu.org/licenses/>.
+ */
+use super::*;
+use crate::test_util::date;
+
+fn names(catalogue: &Catalogue) -> Vec<(u64, &str)> {
+    catalogue
+        .tasks()
+        .into_iter()
+        .map(|task| (task.id, task.name.as_str()))
+        .collect()
+}
+
+#[test]
+fn test_record() {
+    let mut catalogue = Catalogue::new();
+    catalogue.record(date(16), ["Test1", "Test2"]);
+    catalogue.record(date(15), ["Test2", "Test3"]);
+    assert_eq!(
+        names(&catalogue),
+        vec![(1, "Test1"), (2, "Test2"), (3, "Test3")]
+    );
+    let task = catalogue.get("Test2").unwrap();
+    assert_eq!((task.first_used, task.last_used), (date(15), date(16)));
+}
+
+#[test]
+fn test_most_recent() {
+    let mut catalogue = Catalogue::new();
+    assert_eq!(catalogue.most_recent(), None);
+    catalogue.record(date(16), ["Test1", "Test2"]);
+    catalogue.record(date(15), ["Test3"]);
+    assert_eq!(catalogue.most_recent().unwrap().name, "Test2");
+    catalogue.record(date(16), ["Test1"]);
+    assert_eq!(catalogue.most_recent().unwrap().name, "Test1");
+}
+
+#[test]
+fn test_find() {
+    let mut catalogue = Catalogue::new();
+    catalogue.record(date(16), ["Test", "Test review", "Other"]);
+    assert_eq!(catalogue.find("Test").unwrap().id, 1);
+    assert_eq!(catalogue.find("rev").unwrap().id, 2);
+    let error = catalogue.find("e").unwrap_err();
+    assert!(matches!(error, TaskError::MultipleTasksFound));
+    let error = catalogue.find("None").unwrap_err();
+    assert!(matches!(error, TaskError::TaskNotFound(name) if name == "None"));
+}
+
+#[test]
+fn test_rename_keeps_id() {
+    let mut catalogue = Catalogue::new();
+    catalogue.record(date(15), ["Test1"]);
+    catalogue.rename("Test1", "Renamed");
+    catalogue.record(date(16), ["Renamed"]);
+    catalogue.record(date(14), ["Test1"]);
+    assert_eq!(names(&catalogue), vec![(1, "Renamed")]);
+    let task = catalogue.get("Test1").unwrap();
+    assert_eq!(task.aliases, vec!["Test1"]);
+    assert_eq!((task.first_used, task.last_used), (date(14), date(16)));
+}
+
+#[test]
+fn test_undo_rename() {
+    let mut catalogue = Catalogue::new();
+    catalogue.record(date(16), ["Test1"]);
+    assert!(catalogue.rename("Test1", "Renamed"));
+    catalogue.undo_rename("Other", "Renamed");
+    assert_eq!(names(&catalogue), vec![(1, "Renamed")]);
+    catalogue.undo_rename("Test1", "Renamed");
+    assert_eq!(names(&catalogue), vec![(1, "Test1")]);
+    assert!(catalogue.get("Test1").unwrap().aliases.is_empty());
+}
+
+#[test]
+fn test_rename_to_known_task() {
+    let mut catalogue = Catalogue::new();
+    catalogue.record(date(16), ["Test1", "Test2"]);
+    assert!(!catalogue.rename("Test1", "Test2"));
+    assert_eq!(names(&catalogue), vec![(1, "Test1"), (2, "Test2")]);
+}
+
+#[test]
+fn test_set_estimate() {
+    let mut catalogue = Catalogue::new();
+    catalogue.record(date(16), ["Test1", "Other"]);
+    let name = catalogue
+        .set_estimate("Test", Some(Duration::hours(3)))
+        .unwrap();
+    assert_eq!(name, "Test1");
+    catalogue.rename("Test1", "Renamed");
+    let task = catalogue.get("Test1").unwrap();
+    assert_eq!(task.estimate(), Some(Duration::hours(3)));
+    catalogue.set_estimate("Renamed", None).unwrap();
+    assert_eq!(catalogue.get("Renamed").unwrap().estimate(), None);
+    let error = catalogue.set_estimate("None", None).unwrap_err();
+    assert!(matches!(error, TaskError::TaskNotFound(_)));
+}
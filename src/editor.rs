@@ -0,0 +1,273 @@
+/*
+ * tasklog - A command-line task tracking tool.
+ *
+ * Copyright (C) 2024 Lucas M. de Jong Larrarte
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with this program. If not, see <https://www.gnu.org/licenses/>.
+ */
+#[cfg(test)]
+mod test;
+
+use std::collections::HashMap;
+use std::fmt;
+
+use chrono::{DateTime, Local, NaiveDate, NaiveTime, Timelike};
+use serde::Deserialize;
+use toml::Spanned;
+
+use crate::time_expr::{self, logical_date};
+use crate::{fsck, TaskManager, TaskRecord};
+
+/// Prefix of the lines that [annotate] adds to a document to point out its errors.
+const ANNOTATION: &str = "# error: ";
+
+/// A document holding the tasks of a day, as written by [render].
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct Document {
+    #[serde(default)]
+    task: Vec<TaskDocument>,
+}
+
+/// A task in a [Document].
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct TaskDocument {
+    name: Spanned<String>,
+    #[serde(default)]
+    tags: Vec<String>,
+    #[serde(default)]
+    notes: Vec<String>,
+    #[serde(default)]
+    entries: Vec<Spanned<Vec<String>>>,
+    running_since: Option<Spanned<String>>,
+}
+
+/// An error in an edited document, at the given byte offset if it can be located.
+#[derive(Debug, Clone, PartialEq)]
+pub struct EditError {
+    offset: Option<usize>,
+    message: String,
+}
+impl fmt::Display for EditError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.message)
+    }
+}
+
+/// Renders the tasks of a day as a TOML document meant to be edited by hand.
+///
+/// Times on the day are written as times of day, with seconds only if they have any.
+pub fn render(date: NaiveDate, records: &[TaskRecord], day_start: NaiveTime) -> String {
+    let mut document = format!(
+        "# Tasks of {date}. Save and close the editor to apply your changes.\n\
+         # Each entry is [start, end] or [start, end, note]. A running task has `running_since`\n\
+         # instead of a last entry.\n\
+         # Times of day before {} fall on the next date.\n",
+        day_start.format("%H:%M")
+    );
+    let time = |time| toml::Value::String(render_time(time, date, day_start));
+    let string = |string: &String| toml::Value::String(string.clone()).to_string();
+    for record in records {
+        document += "\n[[task]]\n";
+        document += &format!("name = {}\n", toml::Value::String(record.name.clone()));
+        if !record.tags.is_empty() {
+            let tags: Vec<_> = record.tags.iter().map(string).collect();
+            document += &format!("tags = [{}]\n", tags.join(", "));
+        }
+        if !record.notes.is_empty() {
+            document += "notes = [\n";
+            for note in &record.notes {
+                document += &format!("    {},\n", string(note));
+            }
+            document += "]\n";
+        }
+        if record.entries.is_empty() {
+            document += "entries = []\n";
+        } else {
+            document += "entries = [\n";
+            let notes = record.entry_notes.iter().chain(std::iter::repeat(&None));
+            for (&(start, end), note) in record.entries.iter().zip(notes) {
+                let note = note.as_ref().map(|note| format!(", {}", string(note)));
+                let note = note.unwrap_or_default();
+                document += &format!("    [{}, {}{note}],\n", time(start), time(end));
+            }
+            document += "]\n";
+        }
+        if let Some(start) = record.running_since {
+            document += &format!("running_since = {}\n", time(start));
+        }
+    }
+    document
+}
+
+/// Renders a time as a time of day if it falls on the given day, or with its date otherwise.
+fn render_time(time: DateTime<Local>, date: NaiveDate, day_start: NaiveTime) -> String {
+    let format = match (logical_date(time, day_start) == date, time.second()) {
+        (true, 0) => "%H:%M",
+        (true, _) => "%H:%M:%S",
+        (false, 0) => "%F %H:%M",
+        (false, _) => "%F %H:%M:%S",
+    };
+    time.format(format).to_string()
+}
+
+/// Parses an edited document back into the tasks of a day, checking that they are valid.
+///
+/// Times are read like the time options of the commands, on the given day. Times left as
+/// rendered from `original` keep their full precision.
+pub fn parse(
+    document: &str,
+    date: NaiveDate,
+    now: DateTime<Local>,
+    day_start: NaiveTime,
+    original: &[TaskRecord],
+) -> Result<TaskManager, Vec<EditError>> {
+    let parsed: Document = toml::from_str(document).map_err(|e| {
+        vec![EditError {
+            offset: e.span().map(|span| span.start),
+            message: e.message().trim().to_string(),
+        }]
+    })?;
+    let originals: HashMap<_, _> = original
+        .iter()
+        .flat_map(|record| {
+            let entries = record.entries.iter().flat_map(|&(start, end)| [start, end]);
+            entries.chain(record.running_since)
+        })
+        .map(|time| (render_time(time, date, day_start), time))
+        .collect();
+    let mut errors = vec![];
+    let mut resolve = |time: &str, offset: usize| {
+        if let Some(&time) = originals.get(time) {
+            return Some(time);
+        }
+        let resolved = time_expr::parse_time(time)
+            .map_err(|e| e.to_string())
+            .and_then(|expr| {
+                expr.resolve_on_day(date, now, day_start)
+                    .map_err(|e| e.to_string())
+            })
+            .and_then(|resolved| match resolved > now {
+                true => Err(format!("'{time}' is in the future")),
+                false => Ok(resolved),
+            });
+        resolved
+            .map_err(|message| {
+                errors.push(EditError {
+                    offset: Some(offset),
+                    message,
+                })
+            })
+            .ok()
+    };
+    let mut entry_errors = vec![];
+    let mut records = vec![];
+    for task in &parsed.task {
+        let mut entries = vec![];
+        for entry in &task.entries {
+            let offset = entry.span().start;
+            let (start, end, note) = match entry.get_ref().as_slice() {
+                [start, end] => (start, end, None),
+                [start, end, note] => (start, end, Some(note.clone())),
+                _ => {
+                    entry_errors.push(EditError {
+                        offset: Some(offset),
+                        message: "an entry must be [start, end] or [start, end, note]".to_string(),
+                    });
+                    continue;
+                }
+            };
+            if let (Some(start), Some(end)) = (resolve(start, offset), resolve(end, offset)) {
+                entries.push((start, end, note));
+            }
+        }
+        entries.sort();
+        let (entries, entry_notes) = entries
+            .into_iter()
+            .map(|(start, end, note)| ((start, end), note))
+            .unzip();
+        let running_since = task
+            .running_since
+            .as_ref()
+            .and_then(|start| resolve(start.get_ref(), start.span().start));
+        records.push(TaskRecord {
+            name: task.name.get_ref().clone(),
+            tags: task.tags.clone(),
+            notes: task.notes.clone(),
+            entries,
+            entry_notes,
+            running_since,
+        });
+    }
+    errors.extend(entry_errors);
+    if !errors.is_empty() {
+        return Err(errors);
+    }
+    let checks = fsck::check(vec![(date, records.clone())], day_start);
+    for problem in checks.into_iter().flat_map(|check| check.problems) {
+        let task = parsed
+            .task
+            .iter()
+            .find(|task| task.name.get_ref() == problem.task());
+        errors.push(EditError {
+            offset: task.map(|task| task.name.span().start),
+            message: problem.to_string(),
+        });
+    }
+    if !errors.is_empty() {
+        return Err(errors);
+    }
+    records.sort_by_key(|record| {
+        let stop_time = record.entries.last().map(|&(_, end)| end);
+        (record.running_since.is_some(), stop_time)
+    });
+    TaskManager::from_records(records).map_err(|message| {
+        vec![EditError {
+            offset: None,
+            message,
+        }]
+    })
+}
+
+/// Adds the errors to the document as comments above the lines they were found on, replacing
+/// those added by earlier calls. Errors that cannot be located go at the top.
+pub fn annotate(document: &str, errors: &[EditError]) -> String {
+    let mut annotated = String::new();
+    let annotate_at = |range: Option<std::ops::Range<usize>>, annotated: &mut String| {
+        for error in errors {
+            let located = match (&range, error.offset) {
+                (None, None) => true,
+                (None, Some(offset)) => offset >= document.len(),
+                (Some(range), Some(offset)) => range.contains(&offset),
+                _ => false,
+            };
+            if located {
+                *annotated += &format!("{ANNOTATION}{}\n", error.message);
+            }
+        }
+    };
+    annotate_at(None, &mut annotated);
+    let mut start = 0;
+    for line in document.split_inclusive('\n') {
+        let range = start..start + line.len();
+        start += line.len();
+        if line.starts_with(ANNOTATION) {
+            continue;
+        }
+        annotate_at(Some(range), &mut annotated);
+        annotated += line;
+    }
+    annotated
+}
@@ -0,0 +1,200 @@
+/*
+ * tasklog - A command-line task tracking tool.
+ *
+ * Copyright (C) 2024 Lucas M. de Jong Larrarte
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with this program. If not, see <https://www.gnu.org/licenses/>.
+ */
+use super::*;
+use crate::test_util::{day_start, time};
+
+fn date() -> NaiveDate {
+    NaiveDate::from_ymd_opt(2024, 7, 16).unwrap()
+}
+
+fn now() -> DateTime<Local> {
+    time(17, 12, 0)
+}
+
+fn records() -> Vec<TaskRecord> {
+    vec![
+        TaskRecord {
+            name: "Test1".to_string(),
+            tags: vec!["billable".to_string(), "review".to_string()],
+            notes: vec!["Ticket #42".to_string()],
+            entries: vec![
+                (time(16, 9, 0), time(16, 10, 0)),
+                (
+                    time(16, 23, 0) + chrono::Duration::milliseconds(1500),
+                    time(17, 1, 0),
+                ),
+            ],
+            entry_notes: vec![Some("Fixed the bug".to_string()), None],
+            running_since: None,
+        },
+        TaskRecord {
+            name: "Test \"2\"".to_string(),
+            tags: vec![],
+            notes: vec![],
+            entries: vec![],
+            entry_notes: vec![],
+            running_since: Some(time(17, 2, 0)),
+        },
+    ]
+}
+
+fn parse_one(document: &str) -> Result<TaskManager, Vec<EditError>> {
+    parse(document, date(), now(), day_start(), &records())
+}
+
+#[test]
+fn test_render() {
+    let document = render(date(), &records(), day_start());
+    let tasks = document.split_once("\n\n").unwrap().1;
+    assert_eq!(
+        tasks,
+        "[[task]]\n\
+         name = \"Test1\"\n\
+         tags = [\"billable\", \"review\"]\n\
+         notes = [\n    \
+             \"Ticket #42\",\n\
+         ]\n\
+         entries = [\n    \
+             [\"09:00\", \"10:00\", \"Fixed the bug\"],\n    \
+             [\"23:00:01\", \"01:00\"],\n\
+         ]\n\
+         \n\
+         [[task]]\n\
+         name = 'Test \"2\"'\n\
+         entries = []\n\
+         running_since = \"02:00\"\n"
+    );
+}
+
+#[test]
+fn test_parse_when_unchanged() {
+    let document = render(date(), &records(), day_start());
+    let tasks = parse_one(&document).unwrap();
+    assert_eq!(tasks.to_records(), records());
+}
+
+#[test]
+fn test_parse_with_changes() {
+    let document = "\
+        [[task]]\n\
+        name = \"Test3\"\n\
+        entries = [[\"11:00\", \"12:00\"], [\"9:00\", \"10:30\"]]\n\
+        \n\
+        [[task]]\n\
+        name = \"Test1\"\n\
+        entries = [[\"2024-07-16 13:00\", \"14:00\"]]\n";
+    let tasks = parse_one(document).unwrap();
+    assert_eq!(
+        tasks.to_records(),
+        vec![
+            TaskRecord {
+                name: "Test3".to_string(),
+                tags: vec![],
+                notes: vec![],
+                entries: vec![
+                    (time(16, 9, 0), time(16, 10, 30)),
+                    (time(16, 11, 0), time(16, 12, 0))
+                ],
+                entry_notes: vec![None; 2],
+                running_since: None,
+            },
+            TaskRecord {
+                name: "Test1".to_string(),
+                tags: vec![],
+                notes: vec![],
+                entries: vec![(time(16, 13, 0), time(16, 14, 0))],
+                entry_notes: vec![None],
+                running_since: None,
+            },
+        ]
+    );
+}
+
+#[test]
+fn test_parse_when_invalid_toml() {
+    let document = "[[task]]\nname = \n";
+    let errors = parse_one(document).unwrap_err();
+    assert_eq!(errors.len(), 1);
+    assert_eq!(errors[0].offset, Some(document.len() - 1));
+}
+
+#[test]
+fn test_parse_when_invalid_times() {
+    let document = "\
+        [[task]]\n\
+        name = \"Test1\"\n\
+        entries = [[\"9:00\", \"soon\"]]\n\
+        running_since = \"2024-07-17 13:00\"\n";
+    let errors = parse_one(document).unwrap_err();
+    assert_eq!(errors.len(), 2);
+    assert_eq!(errors[0].offset, Some(document.find("[\"9").unwrap()));
+    assert!(errors[0].message.contains("'soon' is not a time"));
+    assert_eq!(errors[1].offset, Some(document.find("\"2024").unwrap()));
+    assert_eq!(errors[1].message, "'2024-07-17 13:00' is in the future");
+}
+
+#[test]
+fn test_parse_when_entry_is_malformed() {
+    let document = "\
+        [[task]]\n\
+        name = \"Test1\"\n\
+        entries = [[\"9:00\"]]\n";
+    let errors = parse_one(document).unwrap_err();
+    assert_eq!(errors.len(), 1);
+    assert_eq!(errors[0].offset, Some(document.find("[\"9").unwrap()));
+    assert_eq!(
+        errors[0].message,
+        "an entry must be [start, end] or [start, end, note]"
+    );
+}
+
+#[test]
+fn test_parse_when_overlapping() {
+    let document = "\
+        [[task]]\n\
+        name = \"Test1\"\n\
+        entries = [[\"9:00\", \"11:00\"]]\n\
+        \n\
+        [[task]]\n\
+        name = \"Test2\"\n\
+        entries = [[\"10:00\", \"12:00\"]]\n";
+    let errors = parse_one(document).unwrap_err();
+    assert_eq!(errors.len(), 1);
+    assert_eq!(errors[0].offset, Some(document.find("\"Test2").unwrap()));
+    assert!(errors[0].message.contains("overlap"));
+}
+
+#[test]
+fn test_annotate() {
+    let document = "# error: old\n[[task]]\nname = \"Test1\"\n";
+    let errors = vec![
+        EditError {
+            offset: Some(document.find("name").unwrap() + 2),
+            message: "bad name".to_string(),
+        },
+        EditError {
+            offset: None,
+            message: "bad document".to_string(),
+        },
+    ];
+    assert_eq!(
+        annotate(document, &errors),
+        "# error: bad document\n[[task]]\n# error: bad name\nname = \"Test1\"\n"
+    );
+}
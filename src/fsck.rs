@@ -0,0 +1,406 @@
+/*
+ * tasklog - A command-line task tracking tool.
+ *
+ * Copyright (C) 2024 Lucas M. de Jong Larrarte
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with this program. If not, see <https://www.gnu.org/licenses/>.
+ */
+#[cfg(test)]
+mod test;
+
+use std::collections::HashSet;
+use std::fmt;
+
+use chrono::{DateTime, Duration, Local, NaiveDate, NaiveDateTime, NaiveTime, TimeZone};
+
+use crate::{TaskManager, TaskRecord};
+
+/// A violation of the invariants of the tasks of a day.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Problem {
+    /// A completed entry ends before it starts.
+    BackwardsEntry {
+        task: String,
+        start: DateTime<Local>,
+        end: DateTime<Local>,
+    },
+    /// The entries of a task are not in chronological order, or overlap each other.
+    UnorderedEntries { task: String },
+    /// Entries of two different tasks overlap.
+    Overlap {
+        first: String,
+        second: String,
+        at: DateTime<Local>,
+    },
+    /// An entry starts outside the day it is stored in.
+    OutsideDay {
+        task: String,
+        start: DateTime<Local>,
+    },
+    /// More than one task is running.
+    MultipleRunning { tasks: Vec<String> },
+    /// A task is running, and so is a task on a later day.
+    RunningOnLaterDay { task: String, date: NaiveDate },
+    /// Several tasks have the same name.
+    DuplicateName { task: String },
+    /// A stopped task has no entries.
+    NoEntries { task: String },
+}
+impl Problem {
+    /// Returns the name of the task the problem is found at. For problems involving several
+    /// tasks, this is the one the problem shows up at last.
+    pub fn task(&self) -> &str {
+        match self {
+            Problem::BackwardsEntry { task, .. }
+            | Problem::UnorderedEntries { task }
+            | Problem::OutsideDay { task, .. }
+            | Problem::RunningOnLaterDay { task, .. }
+            | Problem::DuplicateName { task }
+            | Problem::NoEntries { task } => task,
+            Problem::Overlap { second, .. } => second,
+            Problem::MultipleRunning { tasks } => tasks.last().map_or("", String::as_str),
+        }
+    }
+
+    /// Checks if [DayCheck::repair] fixes the problem. Entries outside the day need to be moved
+    /// by hand, as only the user knows which day they belong to.
+    pub fn is_repairable(&self) -> bool {
+        !matches!(self, Problem::OutsideDay { .. })
+    }
+}
+impl fmt::Display for Problem {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Problem::BackwardsEntry { task, start, end } => write!(
+                f,
+                "an entry of '{task}' ends at {} before it starts at {}",
+                end.format("%F %T"),
+                start.format("%F %T")
+            ),
+            Problem::UnorderedEntries { task } => {
+                write!(f, "the entries of '{task}' are not in chronological order")
+            }
+            Problem::Overlap { first, second, at } => {
+                write!(
+                    f,
+                    "'{first}' and '{second}' overlap at {}",
+                    at.format("%F %T")
+                )
+            }
+            Problem::OutsideDay { task, start } => write!(
+                f,
+                "an entry of '{task}' starts at {}, outside the day",
+                start.format("%F %T")
+            ),
+            Problem::MultipleRunning { tasks } => {
+                write!(f, "several tasks are running: '{}'", tasks.join("', '"))
+            }
+            Problem::RunningOnLaterDay { task, date } => {
+                write!(f, "'{task}' is running, but so is a task on {date}")
+            }
+            Problem::DuplicateName { task } => write!(f, "there are several tasks named '{task}'"),
+            Problem::NoEntries { task } => write!(f, "the stopped task '{task}' has no entries"),
+        }
+    }
+}
+
+/// The problems found in the tasks of a day.
+#[derive(Debug, Clone, PartialEq)]
+pub struct DayCheck {
+    pub date: NaiveDate,
+    pub problems: Vec<Problem>,
+    records: Vec<TaskRecord>,
+    /// When to stop the running task, if a task on a later day is running too.
+    stop_running_at: Option<DateTime<Local>>,
+}
+impl DayCheck {
+    /// Checks if any of the problems can be repaired.
+    pub fn is_repairable(&self) -> bool {
+        self.problems.iter().any(Problem::is_repairable)
+    }
+
+    /// Returns the tasks of the day with the repairable problems fixed:
+    /// - tasks with the same name are merged, keeping the tags and notes of all of them,
+    /// - entries that end before they start are dropped,
+    /// - overlapping entries are trimmed, the one that started first keeping the shared time,
+    /// - a running task is stopped when a later entry starts, or at the end of the day if a
+    ///   task on a later day is running too,
+    /// - stopped tasks left without entries are dropped.
+    pub fn repair(&self) -> TaskManager {
+        let mut names: Vec<&str> = vec![];
+        let mut tags: Vec<Vec<String>> = vec![];
+        let mut notes: Vec<Vec<String>> = vec![];
+        let mut intervals = vec![];
+        for record in &self.records {
+            let task = match names.iter().position(|name| *name == record.name) {
+                Some(task) => task,
+                None => {
+                    names.push(&record.name);
+                    tags.push(vec![]);
+                    notes.push(vec![]);
+                    names.len() - 1
+                }
+            };
+            tags[task].extend(record.tags.iter().cloned());
+            notes[task].extend(record.notes.iter().cloned());
+            intervals.extend(intervals_of(record, task));
+        }
+        for tags in &mut tags {
+            tags.sort();
+            tags.dedup();
+        }
+        intervals.sort_by_key(|interval| interval.start);
+        let mut entries = vec![vec![]; names.len()];
+        let mut entry_notes = vec![vec![]; names.len()];
+        let mut cursor = None;
+        let mut running: Option<(usize, DateTime<Local>)> = None;
+        for interval in intervals {
+            if let Some((task, since)) = running.take() {
+                let end = interval.start.max(since);
+                entries[task].push((since, end));
+                entry_notes[task].push(None);
+                cursor = Some(end);
+            }
+            let start = cursor.map_or(interval.start, |cursor| interval.start.max(cursor));
+            match interval.end {
+                Some(end) if end > start || (end == start && start == interval.start) => {
+                    entries[interval.task].push((start, end));
+                    entry_notes[interval.task].push(interval.note.cloned());
+                    cursor = Some(end);
+                }
+                Some(_) => {}
+                None => running = Some((interval.task, start)),
+            }
+        }
+        if let (Some((task, since)), Some(end)) = (running, self.stop_running_at) {
+            entries[task].push((since, end.max(since)));
+            entry_notes[task].push(None);
+            running = None;
+        }
+        for notes in &mut notes {
+            let mut seen = HashSet::new();
+            notes.retain(|note| seen.insert(note.clone()));
+        }
+        let tasks = tags
+            .into_iter()
+            .zip(notes)
+            .zip(entries.into_iter().zip(entry_notes));
+        let records = names
+            .into_iter()
+            .zip(tasks)
+            .enumerate()
+            .map(
+                |(task, (name, ((tags, notes), (entries, entry_notes))))| TaskRecord {
+                    name: name.to_string(),
+                    tags,
+                    notes,
+                    entries,
+                    entry_notes,
+                    running_since: running
+                        .filter(|(running, _)| *running == task)
+                        .map(|(_, since)| since),
+                },
+            )
+            .filter(|record| !record.entries.is_empty() || record.running_since.is_some())
+            .collect();
+        TaskManager::from_records(records).expect("repaired tasks should be valid")
+    }
+}
+
+/// An entry of a task, which is open-ended if the task is running.
+#[derive(Debug)]
+struct Interval<'a, T> {
+    task: T,
+    start: DateTime<Local>,
+    end: Option<DateTime<Local>>,
+    note: Option<&'a String>,
+}
+
+/// Returns the entries of a task as intervals labelled with `task`, leaving out the entries that
+/// end before they start.
+fn intervals_of<'a, T: Copy + 'a>(
+    record: &'a TaskRecord,
+    task: T,
+) -> impl Iterator<Item = Interval<'a, T>> + 'a {
+    let notes = record
+        .entry_notes
+        .iter()
+        .map(Option::as_ref)
+        .chain(std::iter::repeat(None));
+    let completed = record
+        .entries
+        .iter()
+        .zip(notes)
+        .filter(|((start, end), _)| start <= end)
+        .map(move |(&(start, end), note)| Interval {
+            task,
+            start,
+            end: Some(end),
+            note,
+        });
+    let running = record.running_since.map(|start| Interval {
+        task,
+        start,
+        end: None,
+        note: None,
+    });
+    completed.chain(running)
+}
+
+/// Checks the tasks of the given days, which must be in chronological order. A day is taken to
+/// run from `day_start` on its date to `day_start` on the next date.
+pub fn check(days: Vec<(NaiveDate, Vec<TaskRecord>)>, day_start: NaiveTime) -> Vec<DayCheck> {
+    let mut checks: Vec<_> = days
+        .into_iter()
+        .map(|(date, records)| check_day(date, records, day_start))
+        .collect();
+    let running_days: Vec<_> = checks
+        .iter()
+        .enumerate()
+        .filter(|(_, check)| check.records.iter().any(is_running))
+        .map(|(i, _)| i)
+        .collect();
+    if let Some((&last, earlier)) = running_days.split_last() {
+        let later = checks[last].date;
+        for &i in earlier {
+            let check = &mut checks[i];
+            check.stop_running_at = Some(day_window(check.date, day_start).1);
+            for record in check.records.iter().filter(|record| is_running(record)) {
+                check.problems.push(Problem::RunningOnLaterDay {
+                    task: record.name.clone(),
+                    date: later,
+                });
+            }
+        }
+    }
+    checks
+}
+
+/// Checks the tasks of a single day.
+fn check_day(date: NaiveDate, records: Vec<TaskRecord>, day_start: NaiveTime) -> DayCheck {
+    let (day_begin, day_end) = day_window(date, day_start);
+    let mut problems = vec![];
+    let mut names = HashSet::new();
+    let mut duplicates = HashSet::new();
+    for record in &records {
+        let task = || record.name.clone();
+        if !names.insert(&record.name) && duplicates.insert(&record.name) {
+            problems.push(Problem::DuplicateName { task: task() });
+        }
+        if record.entries.is_empty() && record.running_since.is_none() {
+            problems.push(Problem::NoEntries { task: task() });
+        }
+        for &(start, end) in &record.entries {
+            if end < start {
+                problems.push(Problem::BackwardsEntry {
+                    task: task(),
+                    start,
+                    end,
+                });
+            }
+        }
+        let ends = record.entries.iter().map(|&(_, end)| end);
+        let starts = record.entries.iter().map(|&(start, _)| start);
+        let starts = starts.chain(record.running_since).skip(1);
+        if ends.zip(starts).any(|(end, next_start)| next_start < end) {
+            problems.push(Problem::UnorderedEntries { task: task() });
+        }
+        let starts = record.entries.iter().map(|&(start, _)| start);
+        for start in starts.chain(record.running_since) {
+            if start < day_begin || start >= day_end {
+                problems.push(Problem::OutsideDay {
+                    task: task(),
+                    start,
+                });
+            }
+        }
+    }
+    let running: Vec<_> = records
+        .iter()
+        .filter(|record| is_running(record))
+        .map(|record| record.name.clone())
+        .collect();
+    if running.len() > 1 {
+        problems.push(Problem::MultipleRunning { tasks: running });
+    }
+    problems.extend(overlaps(&records));
+    DayCheck {
+        date,
+        problems,
+        records,
+        stop_running_at: None,
+    }
+}
+
+/// Finds the entries of different tasks that overlap. Running tasks overlapping each other are
+/// left out, as they are reported as [Problem::MultipleRunning].
+fn overlaps(records: &[TaskRecord]) -> Vec<Problem> {
+    let mut intervals: Vec<_> = records
+        .iter()
+        .flat_map(|record| intervals_of(record, record.name.as_str()))
+        .collect();
+    intervals.sort_by_key(|interval| interval.start);
+    let mut problems = vec![];
+    let mut latest: Option<&Interval<&str>> = None;
+    for interval in &intervals {
+        if let Some(latest) = latest {
+            let overlaps = latest.end.is_none_or(|end| interval.start < end);
+            let both_running = latest.end.is_none() && interval.end.is_none();
+            if overlaps && !both_running && latest.task != interval.task {
+                problems.push(Problem::Overlap {
+                    first: latest.task.to_string(),
+                    second: interval.task.to_string(),
+                    at: interval.start,
+                });
+            }
+        }
+        let ends_later = match (latest.map(|latest| latest.end), interval.end) {
+            (None, _) | (_, None) => true,
+            (Some(None), Some(_)) => false,
+            (Some(Some(latest)), Some(end)) => end > latest,
+        };
+        if ends_later {
+            latest = Some(interval);
+        }
+    }
+    problems
+}
+
+/// Checks if the task is running.
+fn is_running(record: &TaskRecord) -> bool {
+    record.running_since.is_some()
+}
+
+/// Returns the time window of a day, from `day_start` on its date to `day_start` on the next.
+pub(crate) fn day_window(
+    date: NaiveDate,
+    day_start: NaiveTime,
+) -> (DateTime<Local>, DateTime<Local>) {
+    let next = date
+        .succ_opt()
+        .expect("date should not be the last representable date");
+    (
+        local(date.and_time(day_start)),
+        local(next.and_time(day_start)),
+    )
+}
+
+/// Converts a local date and time to [Local], moving times skipped by a change to daylight
+/// saving time forward by an hour.
+fn local(time: NaiveDateTime) -> DateTime<Local> {
+    match Local.from_local_datetime(&time).earliest() {
+        Some(time) => time,
+        None => local(time + Duration::hours(1)),
+    }
+}
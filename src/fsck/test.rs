@@ -0,0 +1,196 @@
+/*
+ * tasklog - A command-line task tracking tool.
+ *
+ * Copyright (C) 2024 Lucas M. de Jong Larrarte
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with this program. If not, see <https://www.gnu.org/licenses/>.
+ */
+use super::*;
+use crate::test_util::{date, day_start, time};
+
+fn stopped(name: &str, entries: &[(u32, u32)]) -> TaskRecord {
+    TaskRecord {
+        name: name.to_string(),
+        tags: vec![],
+        notes: vec![],
+        entries: entries
+            .iter()
+            .map(|&(start, end)| (time(16, start, 0), time(16, end, 0)))
+            .collect(),
+        entry_notes: vec![None; entries.len()],
+        running_since: None,
+    }
+}
+
+fn running(name: &str, since: u32) -> TaskRecord {
+    TaskRecord {
+        name: name.to_string(),
+        tags: vec![],
+        notes: vec![],
+        entries: vec![],
+        entry_notes: vec![],
+        running_since: Some(time(16, since, 0)),
+    }
+}
+
+fn check_one(records: Vec<TaskRecord>) -> DayCheck {
+    check(vec![(date(16), records)], day_start()).remove(0)
+}
+
+#[test]
+fn test_check_when_valid() {
+    let records = vec![stopped("Test1", &[(9, 10), (11, 12)]), running("Test2", 12)];
+    let check = check_one(records.clone());
+    assert!(check.problems.is_empty());
+    assert_eq!(check.repair().to_records(), records);
+}
+
+#[test]
+fn test_check_when_entries_overlap() {
+    let check = check_one(vec![
+        stopped("Test1", &[(9, 11)]),
+        stopped("Test2", &[(10, 12)]),
+    ]);
+    assert_eq!(
+        check.problems,
+        vec![Problem::Overlap {
+            first: "Test1".to_string(),
+            second: "Test2".to_string(),
+            at: time(16, 10, 0),
+        }]
+    );
+    assert!(check.is_repairable());
+    assert_eq!(
+        check.repair().to_records(),
+        vec![
+            stopped("Test1", &[(9, 11)]),
+            TaskRecord {
+                name: "Test2".to_string(),
+                tags: vec![],
+                notes: vec![],
+                entries: vec![(time(16, 11, 0), time(16, 12, 0))],
+                entry_notes: vec![None],
+                running_since: None,
+            }
+        ]
+    );
+}
+
+#[test]
+fn test_check_when_entries_are_backwards_or_unordered() {
+    let check = check_one(vec![
+        stopped("Test1", &[(11, 12), (9, 10)]),
+        stopped("Test2", &[(14, 13)]),
+    ]);
+    assert_eq!(
+        check.problems,
+        vec![
+            Problem::UnorderedEntries {
+                task: "Test1".to_string()
+            },
+            Problem::BackwardsEntry {
+                task: "Test2".to_string(),
+                start: time(16, 14, 0),
+                end: time(16, 13, 0),
+            },
+        ]
+    );
+    assert_eq!(
+        check.repair().to_records(),
+        vec![stopped("Test1", &[(9, 10), (11, 12)])]
+    );
+}
+
+#[test]
+fn test_check_when_names_are_duplicated() {
+    let check = check_one(vec![
+        stopped("Test", &[(9, 10)]),
+        stopped("Test", &[(11, 12)]),
+    ]);
+    assert_eq!(
+        check.problems,
+        vec![Problem::DuplicateName {
+            task: "Test".to_string()
+        }]
+    );
+    assert_eq!(
+        check.repair().to_records(),
+        vec![stopped("Test", &[(9, 10), (11, 12)])]
+    );
+}
+
+#[test]
+fn test_check_when_several_tasks_are_running() {
+    let check = check_one(vec![running("Test1", 9), running("Test2", 10)]);
+    assert_eq!(
+        check.problems,
+        vec![Problem::MultipleRunning {
+            tasks: vec!["Test1".to_string(), "Test2".to_string()]
+        }]
+    );
+    let repaired = check.repair();
+    assert_eq!(repaired.running_task(), Some("Test2"));
+    assert_eq!(repaired.to_records()[0], stopped("Test1", &[(9, 10)]));
+}
+
+#[test]
+fn test_check_when_tasks_run_on_several_days() {
+    let later = TaskRecord {
+        name: "Test2".to_string(),
+        tags: vec![],
+        notes: vec![],
+        entries: vec![],
+        entry_notes: vec![],
+        running_since: Some(time(17, 9, 0)),
+    };
+    let checks = check(
+        vec![
+            (date(16), vec![running("Test1", 9)]),
+            (date(17), vec![later]),
+        ],
+        day_start(),
+    );
+    assert_eq!(
+        checks[0].problems,
+        vec![Problem::RunningOnLaterDay {
+            task: "Test1".to_string(),
+            date: date(17),
+        }]
+    );
+    assert!(checks[1].problems.is_empty());
+    assert_eq!(
+        checks[0].repair().to_records(),
+        vec![TaskRecord {
+            name: "Test1".to_string(),
+            tags: vec![],
+            notes: vec![],
+            entries: vec![(time(16, 9, 0), time(17, 4, 30))],
+            entry_notes: vec![None],
+            running_since: None,
+        }]
+    );
+}
+
+#[test]
+fn test_check_when_entry_is_outside_the_day() {
+    let check = check_one(vec![stopped("Test", &[(3, 4)])]);
+    assert_eq!(
+        check.problems,
+        vec![Problem::OutsideDay {
+            task: "Test".to_string(),
+            start: time(16, 3, 0),
+        }]
+    );
+    assert!(!check.is_repairable());
+}
@@ -0,0 +1,109 @@
+/*
+ * tasklog - A command-line task tracking tool.
+ *
+ * Copyright (C) 2024 Lucas M. de Jong Larrarte
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with this program. If not, see <https://www.gnu.org/licenses/>.
+ */
+#[cfg(test)]
+mod test;
+
+use chrono::{DateTime, Local, NaiveDate};
+use serde::{Deserialize, Serialize};
+
+use crate::TaskManager;
+
+/// The maximum number of operations kept in each direction.
+const MAX_OPERATIONS: usize = 100;
+
+/// A change made to the tasks of a day by a single command.
+#[derive(Debug, Serialize, Deserialize, PartialEq, Clone)]
+pub struct Operation {
+    /// The command that performed the change.
+    pub command: String,
+    /// The day whose tasks were changed.
+    pub date: NaiveDate,
+    /// The time the change was made.
+    pub performed: DateTime<Local>,
+    /// The tasks of the day before the change.
+    #[serde(with = "crate::schema")]
+    pub before: TaskManager,
+    /// The tasks of the day after the change.
+    #[serde(with = "crate::schema")]
+    pub after: TaskManager,
+    /// The tasks renamed in the catalogue by the change, from and to, in order.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub renamed: Vec<(String, String)>,
+}
+
+/// The operations that can be undone and redone, most recent last.
+#[derive(Debug, Serialize, Deserialize, Default, PartialEq)]
+pub struct History {
+    undo: Vec<Operation>,
+    redo: Vec<Operation>,
+}
+impl History {
+    /// Creates an empty history.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Records a newly performed operation, which discards the operations that could be redone.
+    pub fn record(&mut self, operation: Operation) {
+        self.redo.clear();
+        push_bounded(&mut self.undo, operation);
+    }
+
+    /// Returns the operation that would be undone next, if any.
+    pub fn next_undo(&self) -> Option<&Operation> {
+        self.undo.last()
+    }
+
+    /// Returns the operation that would be redone next, if any.
+    pub fn next_redo(&self) -> Option<&Operation> {
+        self.redo.last()
+    }
+
+    /// Marks the most recent operation as undone, returning it.
+    pub fn undo(&mut self) -> Option<Operation> {
+        let operation = self.undo.pop()?;
+        push_bounded(&mut self.redo, operation.clone());
+        Some(operation)
+    }
+
+    /// Marks the most recently undone operation as performed again, returning it.
+    pub fn redo(&mut self) -> Option<Operation> {
+        let operation = self.redo.pop()?;
+        push_bounded(&mut self.undo, operation.clone());
+        Some(operation)
+    }
+
+    /// Returns the operations that can be undone, most recent first.
+    pub fn undoable(&self) -> impl Iterator<Item = &Operation> {
+        self.undo.iter().rev()
+    }
+
+    /// Returns the operations that can be redone, most recent first.
+    pub fn redoable(&self) -> impl Iterator<Item = &Operation> {
+        self.redo.iter().rev()
+    }
+}
+
+/// Pushes an operation, dropping the oldest ones beyond [MAX_OPERATIONS].
+fn push_bounded(operations: &mut Vec<Operation>, operation: Operation) {
+    operations.push(operation);
+    if operations.len() > MAX_OPERATIONS {
+        operations.drain(..operations.len() - MAX_OPERATIONS);
+    }
+}
@@ -0,0 +1,103 @@
+/*
+ * tasklog - A command-line task tracking tool.
+ *
+ * Copyright (C) 2024 Lucas M. de Jong Larrarte
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with this program. If not, see <https://www.gnu.org/licenses/>.
+ */
+use super::*;
+
+fn operation(command: &str) -> Operation {
+    Operation {
+        command: command.to_string(),
+        date: NaiveDate::from_ymd_opt(2024, 7, 16).unwrap(),
+        performed: Local::now(),
+        before: TaskManager::new(),
+        after: TaskManager::new(),
+        renamed: vec![],
+    }
+}
+
+fn commands<'a>(operations: impl Iterator<Item = &'a Operation>) -> Vec<&'a str> {
+    operations
+        .map(|operation| operation.command.as_str())
+        .collect()
+}
+
+#[test]
+fn test_history_new() {
+    let history = History::new();
+    assert_eq!(history.next_undo(), None);
+    assert_eq!(history.next_redo(), None);
+}
+
+#[test]
+fn test_history_undo_and_redo() {
+    let mut history = History::new();
+    history.record(operation("start"));
+    history.record(operation("stop"));
+    assert_eq!(history.undo().unwrap().command, "stop");
+    assert_eq!(commands(history.undoable()), vec!["start"]);
+    assert_eq!(commands(history.redoable()), vec!["stop"]);
+    assert_eq!(history.undo().unwrap().command, "start");
+    assert_eq!(history.undo(), None);
+    assert_eq!(history.redo().unwrap().command, "start");
+    assert_eq!(commands(history.undoable()), vec!["start"]);
+    assert_eq!(commands(history.redoable()), vec!["stop"]);
+}
+
+#[test]
+fn test_history_record_clears_redo() {
+    let mut history = History::new();
+    history.record(operation("start"));
+    history.undo();
+    history.record(operation("switch"));
+    assert_eq!(history.redo(), None);
+    assert_eq!(commands(history.undoable()), vec!["switch"]);
+}
+
+#[test]
+fn test_history_is_bounded() {
+    let mut history = History::new();
+    for i in 0..MAX_OPERATIONS + 5 {
+        history.record(operation(&i.to_string()));
+    }
+    assert_eq!(history.undoable().count(), MAX_OPERATIONS);
+    assert_eq!(history.undoable().last().unwrap().command, "5");
+}
+
+#[test]
+fn test_history_serde_round_trip() {
+    let mut history = History::new();
+    history.record(operation("start"));
+    history.record(operation("stop"));
+    history.undo();
+    let data = serde_json::to_string(&history).unwrap();
+    assert_eq!(serde_json::from_str::<History>(&data).unwrap(), history);
+}
+
+#[test]
+fn test_operation_serializes_tasks_with_schema_version() {
+    let data = serde_json::to_value(operation("start")).unwrap();
+    assert_eq!(
+        data["before"]["schema_version"],
+        crate::schema::CURRENT_VERSION
+    );
+    assert_eq!(
+        data["after"]["schema_version"],
+        crate::schema::CURRENT_VERSION
+    );
+    let read: Operation = serde_json::from_value(data).unwrap();
+    assert_eq!(read.command, "start");
+}
@@ -0,0 +1,159 @@
+/*
+ * tasklog - A command-line task tracking tool.
+ *
+ * Copyright (C) 2024 Lucas M. de Jong Larrarte
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with this program. If not, see <https://www.gnu.org/licenses/>.
+ */
+use std::fmt::{Display, Formatter};
+
+use chrono::{DateTime, Local};
+use serde::{Deserialize, Serialize};
+
+use crate::TaskManager;
+
+/// A mutation of the tasks of a day. Replaying the events of a day in order rebuilds its tasks.
+///
+/// Events always name tasks by their exact name, so that replaying them is unambiguous.
+#[derive(Debug, Serialize, Deserialize, PartialEq, Clone)]
+#[serde(tag = "event", rename_all = "snake_case")]
+pub enum Event {
+    /// A new task was started.
+    Started { task: String, at: DateTime<Local> },
+    /// The running task was stopped.
+    Stopped { at: DateTime<Local> },
+    /// A stopped task was resumed.
+    Resumed { task: String, at: DateTime<Local> },
+    /// A completed time entry was added to a task, which was created if needed.
+    Added {
+        task: String,
+        start: DateTime<Local>,
+        end: DateTime<Local>,
+    },
+    /// The completed entry at `index` of a task was changed.
+    EntryEdited {
+        task: String,
+        index: usize,
+        start: DateTime<Local>,
+        end: DateTime<Local>,
+    },
+    /// The completed entry at `index` of a task was deleted.
+    EntryDeleted { task: String, index: usize },
+    /// The completed entry at `index` of a task was split in two.
+    EntrySplit {
+        task: String,
+        index: usize,
+        at: DateTime<Local>,
+    },
+    /// The completed entry at `index` of a task was moved to another task.
+    EntryMoved {
+        task: String,
+        index: usize,
+        to: String,
+    },
+    /// A task was renamed.
+    Renamed { from: String, to: String },
+    /// Tags were added to and removed from a task.
+    Tagged {
+        task: String,
+        added: Vec<String>,
+        removed: Vec<String>,
+    },
+    /// A note was added to a task, or set on its completed entry at `index`.
+    Noted {
+        task: String,
+        index: Option<usize>,
+        note: String,
+    },
+    /// A task was deleted.
+    Deleted { task: String },
+    /// All tasks of the day were replaced, e.g. to record a day that predates the journal.
+    Snapshot {
+        #[serde(with = "crate::schema")]
+        tasks: TaskManager,
+    },
+}
+impl Display for Event {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let time = |at: &DateTime<Local>| at.format("%F %T").to_string();
+        match self {
+            Event::Started { task, at } => write!(f, "started '{task}' at {}", time(at)),
+            Event::Stopped { at } => write!(f, "stopped the running task at {}", time(at)),
+            Event::Resumed { task, at } => write!(f, "resumed '{task}' at {}", time(at)),
+            Event::Added { task, start, end } => {
+                write!(f, "added {} to {} to '{task}'", time(start), time(end))
+            }
+            Event::EntryEdited {
+                task,
+                index,
+                start,
+                end,
+            } => write!(
+                f,
+                "changed entry {} of '{task}' to {} to {}",
+                index + 1,
+                time(start),
+                time(end)
+            ),
+            Event::EntryDeleted { task, index } => {
+                write!(f, "deleted entry {} of '{task}'", index + 1)
+            }
+            Event::EntrySplit { task, index, at } => {
+                write!(f, "split entry {} of '{task}' at {}", index + 1, time(at))
+            }
+            Event::EntryMoved { task, index, to } => {
+                write!(f, "moved entry {} of '{task}' to '{to}'", index + 1)
+            }
+            Event::Renamed { from, to } => write!(f, "renamed '{from}' to '{to}'"),
+            Event::Tagged {
+                task,
+                added,
+                removed,
+            } => {
+                let changes: Vec<_> = added
+                    .iter()
+                    .map(|tag| format!("+{tag}"))
+                    .chain(removed.iter().map(|tag| format!("-{tag}")))
+                    .collect();
+                write!(f, "tagged '{task}' {}", changes.join(" "))
+            }
+            Event::Noted { task, index, note } => match index {
+                None => write!(f, "noted on '{task}': {note}"),
+                Some(index) => write!(f, "noted on entry {} of '{task}': {note}", index + 1),
+            },
+            Event::Deleted { task } => write!(f, "deleted '{task}'"),
+            Event::Snapshot { tasks } => {
+                write!(
+                    f,
+                    "recorded a snapshot of {} task(s)",
+                    tasks.list_tasks().len()
+                )
+            }
+        }
+    }
+}
+
+/// An event in the journal, with the time it was recorded.
+#[derive(Debug, Serialize, Deserialize, PartialEq, Clone)]
+pub struct JournalEntry {
+    pub recorded: DateTime<Local>,
+    #[serde(flatten)]
+    pub event: Event,
+}
+impl JournalEntry {
+    /// Creates a journal entry for the given event, recorded at the given time.
+    pub fn new(event: Event, recorded: DateTime<Local>) -> Self {
+        Self { recorded, event }
+    }
+}
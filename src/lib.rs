@@ -16,18 +16,62 @@
  * You should have received a copy of the GNU General Public License
  * along with this program. If not, see <https://www.gnu.org/licenses/>.
  */
+#[cfg(test)]
+mod test;
+#[cfg(test)]
+mod test_util;
+
+use std::collections::HashMap;
+use std::io::{self, ErrorKind, Write};
+use std::iter;
+use std::num::NonZeroUsize;
 use std::ops::RangeInclusive;
 use std::path::PathBuf;
 use std::str::FromStr;
-use std::{env, fs};
+use std::time::Duration as StdDuration;
+use std::{env, fs, process};
 
-use chrono::{Days, Duration, Local, NaiveDate, NaiveTime};
-use clap::{builder::ArgPredicate, ArgAction, Parser, Subcommand};
+use chrono::{DateTime, Days, Duration, Local, NaiveDate, NaiveTime};
+use clap::{builder::ArgPredicate, ArgAction, Args, Parser, Subcommand, ValueEnum};
+use colored::Colorize;
 use serde::{Deserialize, Serialize};
 
-pub use crate::task_manager::{TaskError, TaskManager, TaskResult};
+use crate::backup::Backup;
+use crate::billing::{BillingConfig, Invoice, TaskTime};
+use crate::catalogue::Catalogue;
+use crate::history::{History, Operation};
+use crate::journal::{Event, JournalEntry};
+use crate::report::{
+    Aggregation, DelimitedFormatter, HtmlFormatter, JsonFormatter, MarkdownFormatter,
+    ReportFormatter, TextFormatter,
+};
+pub use crate::report::{Budget, Report, ReportOptions};
+use crate::storage::file_io;
+#[cfg(feature = "sqlite")]
+pub use crate::storage::SqliteStorage;
+pub use crate::storage::{JsonDirStorage, MemoryStorage, Storage};
+use crate::task_manager::format_duration;
+pub use crate::task_manager::{TaskError, TaskManager, TaskRecord, TaskResult};
+use crate::time_expr::TimeExpr;
 
+pub mod backup;
+pub mod billing;
+pub mod catalogue;
+pub mod editor;
+pub mod fsck;
+pub mod history;
+pub mod journal;
+pub mod report;
+mod schema;
+pub mod storage;
 pub mod task_manager;
+pub mod task_path;
+pub mod time_expr;
+
+/// Name of the storage document holding the history of undoable operations.
+const HISTORY_DOCUMENT: &str = "history";
+/// Name of the storage document holding the catalogue of tasks known from any day.
+const CATALOGUE_DOCUMENT: &str = "catalogue";
 
 /// Command-line interface structure.
 #[derive(Debug, Parser)]
@@ -51,6 +95,19 @@ enum Command {
         /// Creates the task before starting it. Requires a task name.
         #[arg(short, long, action = ArgAction::SetTrue, requires = "task")]
         create: bool,
+        /// Tags to add to the task, like +review.
+        #[arg(value_name = "+TAG", value_parser = parse_added_tag, requires = "task")]
+        tags: Vec<String>,
+        /// How long the task is expected to take across all days. E.g. 2h or 1h30m.
+        #[arg(
+            long,
+            value_name = "DURATION",
+            value_parser = time_expr::parse_duration,
+            requires = "task"
+        )]
+        estimate: Option<Duration>,
+        #[command(flatten)]
+        time: TimeArgs,
     },
     /// Stops work on the current task.
     Stop {
@@ -61,10 +118,21 @@ enum Command {
             value_name = "DATE",
             requires = "duration"
         )]
-        date: Option<NaiveDate>,
+        date: Option<TimeExpr>,
         /// The task duration in minutes.
-        #[arg(short, long, require_equals = true, value_name = "MINUTES")]
+        #[arg(
+            short,
+            long,
+            require_equals = true,
+            value_name = "MINUTES",
+            conflicts_with_all = ["at", "ago"]
+        )]
         duration: Option<u16>,
+        /// A note on the work done in the entry being stopped.
+        #[arg(long, value_name = "TEXT")]
+        note: Option<String>,
+        #[command(flatten)]
+        time: TimeArgs,
     },
     /// Switches to a different task.
     Switch {
@@ -74,29 +142,89 @@ enum Command {
         /// Creates the task before switching to it. Requires a task name.
         #[arg(short, long, action = ArgAction::SetTrue, requires = "task")]
         create: bool,
+        /// Tags to add to the task, like +review.
+        #[arg(value_name = "+TAG", value_parser = parse_added_tag, requires = "task")]
+        tags: Vec<String>,
+        #[command(flatten)]
+        time: TimeArgs,
+    },
+    /// Adds a completed time entry to a task, creating the task if needed.
+    Add {
+        /// The name of the task.
+        #[arg(value_name = "TASK")]
+        task: String,
+        /// When the entry starts. E.g. 9:00 or "yesterday 17:30".
+        #[arg(
+            long,
+            value_name = "TIME",
+            value_parser = time_expr::parse_time,
+            allow_hyphen_values = true
+        )]
+        from: TimeExpr,
+        /// When the entry ends, on the same day as it starts. E.g. 10:30. Times of day are on the
+        /// day the entry starts.
+        #[arg(
+            long,
+            value_name = "TIME",
+            value_parser = time_expr::parse_time,
+            allow_hyphen_values = true
+        )]
+        to: TimeExpr,
+        /// The day to add the entry to, on which times of day are taken. E.g. 2024-07-16 or
+        /// yesterday. Defaults to the day the entry starts on.
+        #[arg(long, require_equals = true, value_name = "DATE")]
+        date: Option<TimeExpr>,
+    },
+    /// Lists the time entries of a task, numbered for the entry commands.
+    Entries {
+        /// The name of the task.
+        #[arg(value_name = "TASK")]
+        task: String,
+        /// The date whose entries to list. E.g. 2024-07-16 or yesterday. Defaults to today.
+        #[arg(long, require_equals = true, value_name = "DATE")]
+        date: Option<TimeExpr>,
+    },
+    /// Changes, deletes, splits or moves a single time entry of a task.
+    Entry {
+        #[command(subcommand)]
+        command: EntryCommand,
+    },
+    /// Opens the tasks of a day in an editor, and saves the changes once they are valid.
+    ///
+    /// The editor is taken from $VISUAL or $EDITOR.
+    Edit {
+        /// The date to edit. E.g. 2024-07-16 or yesterday. Defaults to today.
+        #[arg(long, require_equals = true, value_name = "DATE")]
+        date: Option<TimeExpr>,
     },
     /// Prints a report of the tasks worked on in a day.
     Report {
-        /// Whether to report on today.
-        #[arg(short, short_alias = '0', long, action = ArgAction::SetTrue, default_value = "true", default_value_ifs = [
-            ("yesterday", ArgPredicate::IsPresent, Some("false")), 
-            ("dates", ArgPredicate::IsPresent, Some("false")),
-            ("from", ArgPredicate::IsPresent, Some("false")),
-            ("to", ArgPredicate::IsPresent, Some("false")),
-        ], conflicts_with_all = ["from", "to"])]
-        today: bool,
-        /// Whether to report on yesterday.
-        #[arg(short, short_alias = '1', long, action = ArgAction::SetTrue, conflicts_with_all = ["from", "to"])]
-        yesterday: bool,
-        /// The dates to report on. In format YYYY-MM-DD.
-        #[arg(long, action = ArgAction::Append, value_name = "DATE", num_args = 0.., conflicts_with_all = ["from", "to"])]
-        dates: Vec<NaiveDate>,
-        /// The date to start the report from (inclusive). In format YYYY-MM-DD.
-        #[arg(long, value_name = "DATE", require_equals = true, conflicts_with_all = ["today", "yesterday", "dates"])]
-        from: Option<NaiveDate>,
-        /// The date to end the report on (inclusive). In format YYYY-MM-DD.
-        #[arg(long, value_name = "DATE", requires = "from", require_equals = true, conflicts_with_all = ["today", "yesterday", "dates"])]
-        to: Option<NaiveDate>,
+        #[command(flatten)]
+        dates: ReportDates,
+        /// Rolls the time of tasks up into their ancestors this many levels deep, for task names
+        /// like client/project/task.
+        #[arg(long, value_name = "DEPTH")]
+        depth: Option<NonZeroUsize>,
+        /// Only reports the tasks with this tag. Can be given several times to require all of
+        /// the tags.
+        #[arg(long = "tag", value_name = "TAG", value_parser = parse_tag)]
+        tags: Vec<String>,
+        /// Reports the time per group of tasks instead of per task, or per day, week or month.
+        #[arg(long, value_name = "GROUP", conflicts_with = "depth")]
+        group_by: Option<GroupBy>,
+        /// Merges the days reported on into a single report, with the total and the average per
+        /// day worked of each task.
+        #[arg(short, long, action = ArgAction::SetTrue)]
+        aggregate: bool,
+        /// Lists the notes of the tasks and their entries.
+        #[arg(short, long, action = ArgAction::SetTrue)]
+        verbose: bool,
+        /// The format to print the report in.
+        #[arg(long, value_enum, default_value_t = ReportFormat::Text)]
+        format: ReportFormat,
+        /// Writes the report to this file instead of printing it.
+        #[arg(short, long, value_name = "FILE")]
+        output: Option<PathBuf>,
     },
     /// Prints the current task.
     Current,
@@ -114,13 +242,409 @@ enum Command {
         /// The number of days before today to list tasks.
         #[arg(short, default_value_t = 0, require_equals = true, value_name = "DAYS")]
         n: u16,
+        /// Shows the tasks as a tree of their levels, for task names like client/project/task.
+        #[arg(long, action = ArgAction::SetTrue)]
+        tree: bool,
+        /// Only lists the tasks with this tag. Can be given several times to require all of
+        /// the tags.
+        #[arg(long = "tag", value_name = "TAG", value_parser = parse_tag)]
+        tags: Vec<String>,
+    },
+    /// Adds tags to or removes tags from a task.
+    Tag {
+        /// The name of the task.
+        #[arg(value_name = "TASK")]
+        task: String,
+        /// The tags to add, like +review, or to remove, like -review.
+        #[arg(
+            value_name = "+TAG|-TAG",
+            value_parser = parse_tag_change,
+            allow_hyphen_values = true,
+            required = true
+        )]
+        changes: Vec<TagChange>,
+        /// The date of the task. E.g. 2024-07-16 or yesterday. Defaults to today.
+        #[arg(long, require_equals = true, value_name = "DATE")]
+        date: Option<TimeExpr>,
     },
+    /// Adds a note to a task, or sets the note of one of its time entries.
+    Note {
+        /// The name of the task.
+        #[arg(value_name = "TASK")]
+        task: String,
+        /// The text of the note.
+        #[arg(value_name = "TEXT")]
+        note: String,
+        /// The number of the entry to set the note of, as listed by the entries command.
+        #[arg(long, value_name = "N")]
+        entry: Option<NonZeroUsize>,
+        /// The date of the task. E.g. 2024-07-16 or yesterday. Defaults to today.
+        #[arg(long, require_equals = true, value_name = "DATE")]
+        date: Option<TimeExpr>,
+    },
+    /// Sets how long a task is expected to take across all days, or prints the time spent on it
+    /// against its estimate.
+    Estimate {
+        /// The name of the task.
+        #[arg(value_name = "TASK")]
+        task: String,
+        /// The time the task is expected to take. E.g. 3h or 1h30m.
+        #[arg(
+            value_name = "DURATION",
+            value_parser = time_expr::parse_duration,
+            conflicts_with = "clear"
+        )]
+        estimate: Option<Duration>,
+        /// Removes the estimate of the task.
+        #[arg(long, action = ArgAction::SetTrue)]
+        clear: bool,
+    },
+    /// Prints the amounts due for the billable time spent on tasks, at the rates configured in
+    /// the billing section of the configuration file.
+    Invoice {
+        /// The first date to bill (inclusive). E.g. 2024-07-01 or "last monday".
+        #[arg(long, require_equals = true, value_name = "DATE")]
+        from: TimeExpr,
+        /// The last date to bill (inclusive). Defaults to today.
+        #[arg(long, require_equals = true, value_name = "DATE")]
+        to: Option<TimeExpr>,
+        /// Only bills the tasks of this client, the first level of task names like
+        /// client/project/task.
+        #[arg(long, value_name = "CLIENT")]
+        client: Option<String>,
+        /// The format to print the invoice in.
+        #[arg(long, value_enum, default_value_t = InvoiceFormat::Text)]
+        format: InvoiceFormat,
+    },
+    /// Lists the tasks known from any day, with the total time spent on them.
+    Tasks,
     /// Deletes a task.
     Delete {
         /// The name of the task to delete.
         #[arg(value_name = "TASK")]
         task: String,
     },
+    /// Upgrades all day files to the current schema.
+    Migrate {
+        /// Only prints which files would be upgraded, without rewriting them.
+        #[arg(long, action = ArgAction::SetTrue)]
+        dry_run: bool,
+    },
+    /// Imports the days stored as JSON files in a directory into the configured storage.
+    Import {
+        /// The directory holding the JSON day files. Defaults to the data directory.
+        #[arg(value_name = "DIR")]
+        dir: Option<PathBuf>,
+        /// Replaces days that already hold tasks in the configured storage.
+        #[arg(long, action = ArgAction::SetTrue)]
+        overwrite: bool,
+    },
+    /// Prints the journal of changes made to the tasks of a day.
+    Log {
+        /// The date whose journal to print. E.g. 2024-07-16 or yesterday. Defaults to today.
+        #[arg(long, require_equals = true, value_name = "DATE")]
+        date: Option<TimeExpr>,
+    },
+    /// Rebuilds the tasks of a day by replaying its journal, e.g. after its data got corrupted.
+    Rebuild {
+        /// The date to rebuild. E.g. 2024-07-16 or yesterday. Defaults to today.
+        #[arg(
+            long,
+            require_equals = true,
+            value_name = "DATE",
+            conflicts_with = "all"
+        )]
+        date: Option<TimeExpr>,
+        /// Rebuilds every day that has a journal.
+        #[arg(long, action = ArgAction::SetTrue)]
+        all: bool,
+    },
+    /// Undoes the most recent changes made by other commands.
+    Undo {
+        /// The number of changes to undo.
+        #[arg(value_name = "N", default_value_t = 1)]
+        count: usize,
+        /// Lists the changes that can be undone and redone instead.
+        #[arg(short, long, action = ArgAction::SetTrue, conflicts_with = "count")]
+        list: bool,
+    },
+    /// Redoes the most recently undone changes.
+    Redo {
+        /// The number of changes to redo.
+        #[arg(value_name = "N", default_value_t = 1)]
+        count: usize,
+    },
+    /// Writes a compressed archive of all tasks, journals, the task catalogue, the undo history and
+    /// the configuration.
+    Backup {
+        /// The archive to write. Defaults to a timestamped file in the backups directory.
+        #[arg(short, long, value_name = "FILE")]
+        output: Option<PathBuf>,
+    },
+    /// Checks all days for damaged or inconsistent tasks, and offers to repair them.
+    Fsck {
+        /// Repairs every day that can be repaired, without asking.
+        #[arg(long, action = ArgAction::SetTrue)]
+        auto: bool,
+    },
+    /// Restores the tasks and journals of the days, the task catalogue and the undo history in a backup archive,
+    /// after previewing the changes.
+    Restore {
+        /// The archive to restore.
+        #[arg(value_name = "ARCHIVE")]
+        archive: PathBuf,
+        /// Restores without asking for confirmation.
+        #[arg(short, long, action = ArgAction::SetTrue)]
+        yes: bool,
+    },
+}
+
+/// Commands acting on a single time entry, as numbered by the entries command.
+#[derive(Debug, Subcommand)]
+enum EntryCommand {
+    /// Changes the start or end of a time entry.
+    Edit {
+        #[command(flatten)]
+        entry: EntryArgs,
+        /// The new start of the entry. E.g. 9:00. Times of day are on the entry's day.
+        #[arg(
+            long,
+            value_name = "TIME",
+            value_parser = time_expr::parse_time,
+            allow_hyphen_values = true,
+            required_unless_present = "to"
+        )]
+        from: Option<TimeExpr>,
+        /// The new end of the entry. E.g. 10:30. Times of day are on the entry's day.
+        #[arg(
+            long,
+            value_name = "TIME",
+            value_parser = time_expr::parse_time,
+            allow_hyphen_values = true
+        )]
+        to: Option<TimeExpr>,
+    },
+    /// Deletes a time entry, and its task if it was the task's only entry.
+    Delete {
+        #[command(flatten)]
+        entry: EntryArgs,
+    },
+    /// Splits a time entry in two.
+    Split {
+        #[command(flatten)]
+        entry: EntryArgs,
+        /// The time at which to split the entry. E.g. 9:45.
+        #[arg(
+            long,
+            value_name = "TIME",
+            value_parser = time_expr::parse_time,
+            allow_hyphen_values = true
+        )]
+        at: TimeExpr,
+    },
+    /// Moves a time entry to another task, creating it if needed.
+    Move {
+        #[command(flatten)]
+        entry: EntryArgs,
+        /// The name of the task to move the entry to.
+        #[arg(value_name = "NEW_TASK")]
+        to: String,
+    },
+}
+
+/// Options identifying a time entry.
+#[derive(Debug, Args)]
+struct EntryArgs {
+    /// The name of the task.
+    #[arg(value_name = "TASK")]
+    task: String,
+    /// The number of the entry, as listed by the entries command.
+    #[arg(value_name = "N")]
+    number: NonZeroUsize,
+    /// The date of the entry. E.g. 2024-07-16 or yesterday. Defaults to today.
+    #[arg(long, require_equals = true, value_name = "DATE")]
+    date: Option<TimeExpr>,
+}
+impl EntryArgs {
+    /// Returns the index of the entry within its task.
+    fn index(&self) -> usize {
+        self.number.get() - 1
+    }
+
+    /// Resolves the date of the entry.
+    fn date(&self, config: &Config) -> TaskResult<NaiveDate> {
+        resolve_date(&self.date.unwrap_or(TimeExpr::Now), config)
+    }
+}
+
+/// A change to the tags of a task.
+#[derive(Debug, Clone)]
+enum TagChange {
+    Add(String),
+    Remove(String),
+}
+
+/// Parses a change to the tags of a task, written +tag to add it or -tag to remove it.
+fn parse_tag_change(change: &str) -> Result<TagChange, String> {
+    if change.starts_with("--") {
+        return Err("options must be given before the tags".to_string());
+    }
+    match (change.strip_prefix('+'), change.strip_prefix('-')) {
+        (Some(tag), _) => parse_tag(tag).map(TagChange::Add),
+        (_, Some(tag)) => parse_tag(tag).map(TagChange::Remove),
+        _ => Err("tags must be written as +tag or -tag".to_string()),
+    }
+}
+
+/// Parses a tag to add to a task, written +tag.
+fn parse_added_tag(tag: &str) -> Result<String, String> {
+    match tag.strip_prefix('+') {
+        Some(tag) => parse_tag(tag),
+        None => Err("tags must be written as +tag".to_string()),
+    }
+}
+
+/// Parses a tag, which may be written with a leading +.
+fn parse_tag(tag: &str) -> Result<String, String> {
+    let tag = tag.strip_prefix('+').unwrap_or(tag);
+    if tag.is_empty() || tag.starts_with('-') || tag.contains(char::is_whitespace) {
+        return Err(format!("'{tag}' is not a valid tag"));
+    }
+    Ok(tag.to_string())
+}
+
+/// Formats an invoice can be printed in.
+#[derive(Debug, Clone, Copy, ValueEnum)]
+enum InvoiceFormat {
+    Text,
+    Json,
+    Csv,
+}
+
+/// Formats a report can be printed in.
+#[derive(Debug, Clone, Copy, ValueEnum)]
+enum ReportFormat {
+    /// Tables for the terminal.
+    Text,
+    Json,
+    Csv,
+    Markdown,
+    /// Tab-separated values.
+    Tsv,
+    /// A self-contained page with a timeline and charts, best written to a file with --output.
+    Html,
+}
+impl ReportFormat {
+    /// Returns the formatter rendering reports in the format.
+    fn formatter(self) -> Box<dyn ReportFormatter> {
+        match self {
+            ReportFormat::Text => Box::new(TextFormatter),
+            ReportFormat::Json => Box::new(JsonFormatter),
+            ReportFormat::Csv => Box::new(DelimitedFormatter::csv()),
+            ReportFormat::Markdown => Box::new(MarkdownFormatter),
+            ReportFormat::Tsv => Box::new(DelimitedFormatter::tsv()),
+            ReportFormat::Html => Box::new(HtmlFormatter),
+        }
+    }
+}
+
+/// Groups of tasks that a report can be made of.
+#[derive(Debug, Clone, Copy, ValueEnum)]
+enum GroupBy {
+    /// The tags of the tasks. A task counts towards each of its tags.
+    Tag,
+    /// The tasks, across all days reported on. Same as --aggregate.
+    Task,
+    /// The days reported on, in a single report.
+    Day,
+    /// The weeks of the days reported on, in a single report.
+    Week,
+    /// The months of the days reported on, in a single report.
+    Month,
+}
+
+/// Options selecting the days to report on.
+#[derive(Debug, Args)]
+struct ReportDates {
+    /// Whether to report on today.
+    #[arg(short, short_alias = '0', long, action = ArgAction::SetTrue, default_value = "true", default_value_ifs = [
+        ("yesterday", ArgPredicate::IsPresent, Some("false")), 
+        ("dates", ArgPredicate::IsPresent, Some("false")),
+        ("from", ArgPredicate::IsPresent, Some("false")),
+        ("to", ArgPredicate::IsPresent, Some("false")),
+    ], conflicts_with_all = ["from", "to"])]
+    today: bool,
+    /// Whether to report on yesterday.
+    #[arg(short, short_alias = '1', long, action = ArgAction::SetTrue, conflicts_with_all = ["from", "to"])]
+    yesterday: bool,
+    /// The dates to report on. E.g. 2024-07-16, yesterday or "last friday".
+    #[arg(long, action = ArgAction::Append, value_name = "DATE", num_args = 0.., conflicts_with_all = ["from", "to"])]
+    dates: Vec<TimeExpr>,
+    /// The date to start the report from (inclusive). E.g. 2024-07-16, yesterday or "last friday".
+    #[arg(long, value_name = "DATE", require_equals = true, conflicts_with_all = ["today", "yesterday", "dates"])]
+    from: Option<TimeExpr>,
+    /// The date to end the report on (inclusive). E.g. 2024-07-16, yesterday or "last friday".
+    #[arg(long, value_name = "DATE", requires = "from", require_equals = true, conflicts_with_all = ["today", "yesterday", "dates"])]
+    to: Option<TimeExpr>,
+}
+impl ReportDates {
+    /// Resolves the dates to report on, in order.
+    fn resolve(&self, config: &Config) -> TaskResult<Vec<NaiveDate>> {
+        let mut dates = self
+            .dates
+            .iter()
+            .map(|date| resolve_date(date, config))
+            .collect::<TaskResult<Vec<_>>>()?;
+        if let Some(from) = &self.from {
+            let from = resolve_date(from, config)?;
+            let to = resolve_date(&self.to.unwrap_or(TimeExpr::Now), config)?;
+            dates = NaiveDateIter::new(from, to).collect();
+        } else {
+            if self.yesterday {
+                dates.push(date(1, config)?);
+            }
+            if self.today {
+                dates.push(date(0, config)?);
+            }
+            dates.sort();
+            dates.dedup();
+        }
+        Ok(dates)
+    }
+}
+
+/// Options of the commands that can take effect at a time other than now.
+#[derive(Debug, Args)]
+struct TimeArgs {
+    /// When the command takes effect, instead of now. E.g. 9:15, yesterday 17:30, -20m, noon or
+    /// "last friday 14:00". Times before the start of the day fall after midnight.
+    #[arg(
+        long,
+        value_name = "TIME",
+        value_parser = time_expr::parse_time,
+        allow_hyphen_values = true,
+        conflicts_with = "ago"
+    )]
+    at: Option<TimeExpr>,
+    /// How long ago the command takes effect, instead of now. E.g. 15m, 2h or 1h30m.
+    #[arg(long, value_name = "DURATION", value_parser = time_expr::parse_duration)]
+    ago: Option<Duration>,
+}
+impl TimeArgs {
+    /// Resolves the time the command takes effect at, along with the day it belongs to.
+    fn resolve(&self, config: &Config) -> TaskResult<(NaiveDate, DateTime<Local>)> {
+        let now = Local::now();
+        let day_start = day_start(config)?;
+        let time = match (self.at, self.ago) {
+            (Some(at), _) => at.resolve(now, day_start)?,
+            (None, Some(ago)) => time_expr::before(now, ago)?,
+            (None, None) => now,
+        };
+        if time > now {
+            return Err(TaskError::FutureTime(time.format("%F %H:%M").to_string()));
+        }
+        Ok((time_expr::logical_date(time, day_start), time))
+    }
 }
 
 /// Configuration structure representing configuration options.
@@ -128,6 +652,39 @@ enum Command {
 struct Config {
     data_dir: String,
     day_start: String,
+    /// How long to wait for another command to release the data directory, in milliseconds.
+    #[serde(default = "default_lock_timeout")]
+    lock_timeout: u64,
+    /// The storage backend keeping the tasks.
+    #[serde(default)]
+    storage: StorageKind,
+    /// How many automatic backups to keep. Setting it to 0 disables automatic backups.
+    #[serde(default = "default_backup_keep")]
+    backup_keep: usize,
+    /// The rates and tax used to bill tasks.
+    #[serde(default)]
+    billing: BillingConfig,
+    /// The file the configuration was loaded from.
+    #[serde(skip)]
+    file: PathBuf,
+}
+/// Returns the default number of automatic backups to keep.
+fn default_backup_keep() -> usize {
+    10
+}
+/// Available storage backends.
+#[derive(Debug, Serialize, Deserialize, Default, Clone, Copy)]
+#[serde(rename_all = "lowercase")]
+enum StorageKind {
+    /// One JSON file per day in the data directory.
+    #[default]
+    Json,
+    /// A single SQLite database in the data directory. Requires the `sqlite` feature.
+    Sqlite,
+}
+/// Returns the default lock timeout, used when the config file doesn't set one.
+fn default_lock_timeout() -> u64 {
+    5000
 }
 impl Default for Config {
     fn default() -> Self {
@@ -139,6 +696,11 @@ impl Default for Config {
                 .expect("data_dir should be a valid string")
                 .to_string(),
             day_start: "04:30".to_string(),
+            lock_timeout: default_lock_timeout(),
+            storage: StorageKind::default(),
+            backup_keep: default_backup_keep(),
+            billing: BillingConfig::default(),
+            file: PathBuf::new(),
         }
     }
 }
@@ -147,23 +709,26 @@ impl Config {
     fn load(config_file: PathBuf) -> TaskResult<Self> {
         Self::create_config_file_if_needed(&config_file)?;
         let settings = config::Config::builder()
-            .add_source(config::File::from(config_file))
+            .add_source(config::File::from(config_file.clone()))
             .add_source(config::Environment::with_prefix("TASKLOG"))
             .build()?;
-        Ok(settings.try_deserialize()?)
+        let mut config: Self = settings.try_deserialize()?;
+        config.file = config_file;
+        Ok(config)
     }
 
     /// Creates the config file if it doesn't exist.
     fn create_config_file_if_needed(config_file: &PathBuf) -> TaskResult<()> {
         if !config_file.exists() {
             if let Some(parent) = config_file.parent() {
-                fs::create_dir_all(parent)?;
+                fs::create_dir_all(parent).map_err(file_io(parent))?;
             }
             let default_config = Config::default();
             fs::write(
                 config_file,
                 toml::to_string(&default_config).expect("config should be serializable"),
-            )?;
+            )
+            .map_err(file_io(config_file))?;
         }
         Ok(())
     }
@@ -182,201 +747,1620 @@ pub fn handle(cli: Cli) -> TaskResult<()> {
             })
     });
     let config = Config::load(config)?;
-    fs::create_dir_all(PathBuf::from(&config.data_dir))?;
-    match cli.command {
-        Command::Start { task, create } => {
+    let storage = open_storage(&config)?;
+    execute(cli.command, storage.as_ref(), &config)
+}
+
+/// Opens the storage backend selected in the configuration.
+fn open_storage(config: &Config) -> TaskResult<Box<dyn Storage>> {
+    let lock_timeout = StdDuration::from_millis(config.lock_timeout);
+    match config.storage {
+        StorageKind::Json => Ok(Box::new(JsonDirStorage::new(
+            &config.data_dir,
+            lock_timeout,
+        )?)),
+        #[cfg(feature = "sqlite")]
+        StorageKind::Sqlite => {
+            let data_dir = PathBuf::from(&config.data_dir);
+            fs::create_dir_all(&data_dir).map_err(file_io(&data_dir))?;
+            Ok(Box::new(SqliteStorage::open(
+                data_dir.join("tasklog.db"),
+                lock_timeout,
+            )?))
+        }
+        #[cfg(not(feature = "sqlite"))]
+        StorageKind::Sqlite => Err(config::ConfigError::Message(
+            "the sqlite storage requires tasklog to be built with the `sqlite` feature".to_string(),
+        ))?,
+    }
+}
+
+/// Executes the given command against the given storage.
+fn execute(command: Command, storage: &dyn Storage, config: &Config) -> TaskResult<()> {
+    match command {
+        Command::Start {
+            task,
+            create,
+            tags,
+            estimate,
+            time,
+        } => {
             if create {
                 start_new(
                     task.expect("task should exist when create flag is set"),
-                    &config,
+                    tags,
+                    estimate,
+                    time,
+                    storage,
+                    config,
                 )
             } else {
                 match task {
-                    Some(task) => resume(task, &config),
-                    None => resume_last(&config),
+                    Some(task) => resume(task, tags, estimate, time, storage, config),
+                    None => resume_last(time, storage, config),
                 }
             }
         }
-        Command::Stop { date, duration } => stop(date, duration, &config),
-        Command::Switch { task, create } => {
+        Command::Stop {
+            date,
+            duration,
+            note,
+            time,
+        } => stop(date, duration, note, time, storage, config),
+        Command::Switch {
+            task,
+            create,
+            tags,
+            time,
+        } => {
             if create {
                 switch_new(
                     task.expect("task should exist when create flag is set"),
-                    &config,
+                    tags,
+                    time,
+                    storage,
+                    config,
                 )
             } else {
                 match task {
-                    Some(task) => switch(task, &config),
-                    None => switch_previous(&config),
+                    Some(task) => switch(task, tags, time, storage, config),
+                    None => switch_previous(time, storage, config),
                 }
             }
         }
+        Command::Add {
+            task,
+            from,
+            to,
+            date,
+        } => add(task, from, to, date, storage, config),
+        Command::Entries { task, date } => entries(task, date, storage, config),
+        Command::Entry { command } => entry(command, storage, config),
+        Command::Edit { date } => edit(date, storage, config),
         Command::Report {
-            today,
-            yesterday,
             dates,
+            depth,
+            tags,
+            group_by,
+            aggregate,
+            verbose,
+            format,
+            output,
+        } => {
+            let options = ReportOptions {
+                depth: depth.map(NonZeroUsize::get),
+                tags,
+                by_tag: matches!(group_by, Some(GroupBy::Tag)),
+                verbose,
+                budgets: HashMap::new(),
+            };
+            let aggregation = match group_by {
+                Some(GroupBy::Task) => Some(Aggregation::Rows),
+                Some(GroupBy::Day) => Some(Aggregation::Day),
+                Some(GroupBy::Week) => Some(Aggregation::Week),
+                Some(GroupBy::Month) => Some(Aggregation::Month),
+                Some(GroupBy::Tag) | None => aggregate.then_some(Aggregation::Rows),
+            };
+            report(dates, options, aggregation, format, output, storage, config)
+        }
+        Command::Current => current(storage, config),
+        Command::Rename { task, new_name } => rename(task, new_name, storage, config),
+        Command::List { n, tree, tags } => list(n, tree, tags, storage, config),
+        Command::Tag {
+            task,
+            changes,
+            date,
+        } => tag(task, changes, date, storage, config),
+        Command::Note {
+            task,
+            note: text,
+            entry,
+            date,
+        } => note(task, text, entry, date, storage, config),
+        Command::Estimate {
+            task,
+            estimate: duration,
+            clear,
+        } => estimate(task, duration, clear, storage),
+        Command::Invoice {
             from,
             to,
-        } => report(today, yesterday, dates, from, to, &config),
-        Command::Current => current(&config),
-        Command::Rename { task, new_name } => rename(task, new_name, &config),
-        Command::List { n } => list(n, &config),
-        Command::Delete { task } => delete(task, &config),
+            client,
+            format,
+        } => invoice(from, to, client, format, storage, config),
+        Command::Tasks => tasks(storage),
+        Command::Delete { task } => delete(task, storage, config),
+        Command::Migrate { dry_run } => migrate(dry_run, storage, config),
+        Command::Import { dir, overwrite } => import(dir, overwrite, storage, config),
+        Command::Log { date } => log(date, storage, config),
+        Command::Rebuild { date, all } => rebuild(date, all, storage, config),
+        Command::Undo { count, list } => undo(count, list, storage),
+        Command::Redo { count } => redo(count, storage),
+        Command::Backup { output } => backup(output, storage, config),
+        Command::Restore { archive, yes } => restore(archive, yes, storage, config),
+        Command::Fsck { auto } => fsck(auto, storage, config),
     }
 }
 
 /// Processes a mutating action on the tasks.
+///
+/// The storage stays locked for the whole read/action/write cycle, so concurrent
+/// commands cannot lose each other's updates.
+///
+/// The change is recorded in the history under the name `command`, so that it can be undone.
+///
+/// A task left running on an earlier day is carried over to `date` first, see
+/// [carry_over_running_task].
 fn process_mutating_action<T>(
     date: NaiveDate,
+    command: &str,
+    storage: &dyn Storage,
     config: &Config,
     action: impl FnOnce(&mut TaskManager) -> TaskResult<T>,
 ) -> TaskResult<T> {
-    let mut tasks = read_tasks(date, config)?;
+    let _lock = storage.lock()?;
+    carry_over_running_task(date, storage, config)?;
+    mutate(date, command, storage, action)
+}
+
+/// Carries a task left running on an earlier day over to `date`, so that it can be stopped or
+/// switched from there: it stops at the end of its day and runs through each day in between.
+/// On the first of those days that already has entries, it stops when they start instead of
+/// being carried any further. Does nothing for a date in the future.
+///
+/// Each day changed is recorded in the history, so that it can be undone.
+fn carry_over_running_task(
+    date: NaiveDate,
+    storage: &dyn Storage,
+    config: &Config,
+) -> TaskResult<()> {
+    let day_start = day_start(config)?;
+    let (begin, _) = fsck::day_window(date, day_start);
+    if begin > Local::now() || storage.load(date)?.running_task().is_some() {
+        return Ok(());
+    }
+    let Some((from, tasks)) = running_before(date, storage)? else {
+        return Ok(());
+    };
+    let task_name = tasks
+        .running_task()
+        .expect("should have a running task")
+        .to_string();
+    let command = format!("carry over {task_name}");
+    let (_, end) = fsck::day_window(from, day_start);
+    mutate(from, &command, storage, |task_manager| {
+        task_manager.stop_running_task_with_time(end)
+    })?;
+    let mut day = from;
+    while day < date {
+        day = day.succ_opt().expect("should not be the last date");
+        let (begin, end) = fsck::day_window(day, day_start);
+        let tasks = storage.load(day)?;
+        let first_start = tasks
+            .to_records()
+            .iter()
+            .flat_map(|record| {
+                let starts = record.entries.iter().map(|&(start, _)| start);
+                starts.chain(record.running_since).collect::<Vec<_>>()
+            })
+            .min();
+        mutate(day, &command, storage, |task_manager| match first_start {
+            Some(start) => task_manager.add_entry(task_name.clone(), begin, start.max(begin)),
+            None if day == date => task_manager.start_new_task(task_name.clone(), begin),
+            None => task_manager.add_entry(task_name.clone(), begin, end),
+        })?;
+        if first_start.is_some() {
+            break;
+        }
+    }
+    Ok(())
+}
+
+/// Loads the tasks of the given date for a command that can do without them. A day whose data
+/// cannot be parsed is skipped with a warning, and `None` returned. Any other error, such as a
+/// truncated file having been moved aside, is returned.
+fn load_readable(date: NaiveDate, storage: &dyn Storage) -> TaskResult<Option<TaskManager>> {
+    match storage.load(date) {
+        Ok(tasks) => Ok(Some(tasks)),
+        Err(e @ (TaskError::InvalidFile(..) | TaskError::InvalidData(..))) => {
+            eprintln!("warning: skipped {date}, which cannot be read: {e}");
+            Ok(None)
+        }
+        Err(e) => Err(e),
+    }
+}
+
+/// Returns the latest day before `date` that has tasks, with its tasks, if one of them is still
+/// running. A day that cannot be read is skipped with a warning, and taken to have no running
+/// task.
+fn running_before(
+    date: NaiveDate,
+    storage: &dyn Storage,
+) -> TaskResult<Option<(NaiveDate, TaskManager)>> {
+    for day in storage.dates()?.into_iter().rev().filter(|day| *day < date) {
+        let Some(tasks) = load_readable(day, storage)? else {
+            return Ok(None);
+        };
+        if !tasks.list_tasks().is_empty() {
+            return Ok(tasks.running_task().is_some().then_some((day, tasks)));
+        }
+    }
+    Ok(None)
+}
+
+/// Processes a mutating action on the tasks, like [process_mutating_action], in a storage
+/// that the caller has already locked.
+fn mutate<T>(
+    date: NaiveDate,
+    command: &str,
+    storage: &dyn Storage,
+    action: impl FnOnce(&mut TaskManager) -> TaskResult<T>,
+) -> TaskResult<T> {
+    let mut tasks = storage.load(date)?;
+    let before = tasks.clone();
     let task_name = action(&mut tasks)?;
-    write_tasks(&tasks, date, config)?;
+    let renamed = record_and_save(&mut tasks, &before, date, storage)?;
+    let mut history = load_history(storage)?;
+    history.record(Operation {
+        command: command.to_string(),
+        date,
+        performed: Local::now(),
+        before,
+        after: tasks,
+        renamed,
+    });
+    save_history(&history, storage)?;
     Ok(task_name)
 }
 
-/// Resumes the task with the given name.
-fn resume(task_name: String, config: &Config) -> TaskResult<()> {
-    let today = today(config)?;
-    let task_name = process_mutating_action(today, config, |task_manager| {
-        task_manager.resume_task(task_name, Local::now())
-    })?;
+/// Loads the history of undoable operations.
+fn load_history(storage: &dyn Storage) -> TaskResult<History> {
+    match storage.load_document(HISTORY_DOCUMENT)? {
+        None => Ok(History::new()),
+        Some(data) => Ok(serde_json::from_str(&data)?),
+    }
+}
+
+/// Saves the history of undoable operations.
+fn save_history(history: &History, storage: &dyn Storage) -> TaskResult<()> {
+    let data = serde_json::to_string(history).expect("should be able to serialize history");
+    storage.save_document(HISTORY_DOCUMENT, &data)
+}
+
+/// Undoes the last `count` operations, or lists the operations that can be undone.
+fn undo(count: usize, list: bool, storage: &dyn Storage) -> TaskResult<()> {
+    let _lock = storage.lock()?;
+    let mut history = load_history(storage)?;
+    if list {
+        print_operations("Operations that can be undone", history.undoable());
+        print_operations("Operations that can be redone", history.redoable());
+        return Ok(());
+    }
+    for _ in 0..count {
+        let Some(operation) = history.next_undo() else {
+            println!("Nothing left to undo");
+            break;
+        };
+        let (command, date) = (operation.command.clone(), operation.date);
+        replace_day(
+            date,
+            &operation.after,
+            operation.before.clone(),
+            storage,
+            |catalogue| {
+                for (from, to) in operation.renamed.iter().rev() {
+                    catalogue.undo_rename(from, to);
+                }
+            },
+        )?;
+        history.undo();
+        save_history(&history, storage)?;
+        println!("Undid '{command}' on {date}");
+    }
+    Ok(())
+}
+
+/// Redoes the last `count` undone operations.
+fn redo(count: usize, storage: &dyn Storage) -> TaskResult<()> {
+    let _lock = storage.lock()?;
+    let mut history = load_history(storage)?;
+    for _ in 0..count {
+        let Some(operation) = history.next_redo() else {
+            println!("Nothing left to redo");
+            break;
+        };
+        let (command, date) = (operation.command.clone(), operation.date);
+        replace_day(
+            date,
+            &operation.before,
+            operation.after.clone(),
+            storage,
+            |catalogue| {
+                for (from, to) in &operation.renamed {
+                    catalogue.rename(from, to);
+                }
+            },
+        )?;
+        history.redo();
+        save_history(&history, storage)?;
+        println!("Redid '{command}' on {date}");
+    }
+    Ok(())
+}
+
+/// Replaces the tasks of the given date, which must currently be `expected`, with `tasks`.
+///
+/// The catalogue is updated first, so that it knows the tasks of the day by their names in
+/// `tasks` when they are recorded.
+fn replace_day(
+    date: NaiveDate,
+    expected: &TaskManager,
+    tasks: TaskManager,
+    storage: &dyn Storage,
+    update_catalogue: impl FnOnce(&mut Catalogue),
+) -> TaskResult<()> {
+    let before = storage.load(date)?;
+    if before != *expected {
+        return Err(TaskError::HistoryConflict(date));
+    }
+    let mut catalogue = load_catalogue(storage)?;
+    update_catalogue(&mut catalogue);
+    save_catalogue(&catalogue, storage)?;
+    let mut after = before.clone();
+    after.apply(Event::Snapshot { tasks })?;
+    record_and_save(&mut after, &before, date, storage)?;
+    Ok(())
+}
+
+/// Prints a numbered list of operations under the given title.
+fn print_operations<'a>(title: &str, operations: impl Iterator<Item = &'a Operation>) {
+    println!("{title}:");
+    let mut empty = true;
+    for (i, operation) in operations.enumerate() {
+        empty = false;
+        println!(
+            "  {:>3}. {}  {}  (at {})",
+            i + 1,
+            operation.date,
+            operation.command,
+            operation.performed.format("%F %T")
+        );
+    }
+    if empty {
+        println!("  none");
+    }
+}
+
+/// Appends the events recorded by `tasks` to the journal of the given date, then saves them.
+/// Returns the tasks renamed in the catalogue, from and to.
+///
+/// If the day held tasks before the journal existed, a snapshot of its previous state `before`
+/// is recorded first, so that replaying the journal reproduces the saved tasks.
+fn record_and_save(
+    tasks: &mut TaskManager,
+    before: &TaskManager,
+    date: NaiveDate,
+    storage: &dyn Storage,
+) -> TaskResult<Vec<(String, String)>> {
+    let now = Local::now();
+    let events = tasks.take_events();
+    let mut entries = vec![];
+    if !events.is_empty() && !before.list_tasks().is_empty() && storage.journal(date)?.is_empty() {
+        let snapshot = Event::Snapshot {
+            tasks: before.clone(),
+        };
+        entries.push(JournalEntry::new(snapshot, now));
+    }
+    entries.extend(
+        events
+            .into_iter()
+            .map(|event| JournalEntry::new(event, now)),
+    );
+    storage.append_journal(date, &entries)?;
+    storage.save(tasks, date)?;
+    let mut catalogue = load_catalogue(storage)?;
+    let mut renamed = vec![];
+    for entry in &entries {
+        if let Event::Renamed { from, to } = &entry.event {
+            if catalogue.rename(from, to) {
+                renamed.push((from.clone(), to.clone()));
+            }
+        }
+    }
+    catalogue.record(date, tasks.list_tasks());
+    save_catalogue(&catalogue, storage)?;
+    Ok(renamed)
+}
+
+/// Loads the catalogue of tasks known from any day. If there is none yet, it is built from the
+/// days that can be read, see [load_readable].
+fn load_catalogue(storage: &dyn Storage) -> TaskResult<Catalogue> {
+    if let Some(data) = storage.load_document(CATALOGUE_DOCUMENT)? {
+        return Ok(serde_json::from_str(&data)?);
+    }
+    let mut catalogue = Catalogue::new();
+    for date in storage.dates()? {
+        if let Some(tasks) = load_readable(date, storage)? {
+            catalogue.record(date, tasks.list_tasks());
+        }
+    }
+    Ok(catalogue)
+}
+
+/// Saves the catalogue of tasks known from any day.
+fn save_catalogue(catalogue: &Catalogue, storage: &dyn Storage) -> TaskResult<()> {
+    let data = serde_json::to_string(catalogue).expect("should be able to serialize catalogue");
+    storage.save_document(CATALOGUE_DOCUMENT, &data)
+}
+
+/// Resumes the task with the given name, adding the given tags to it and setting its estimate
+/// if one is given.
+fn resume(
+    task_name: String,
+    tags: Vec<String>,
+    estimate: Option<Duration>,
+    time: TimeArgs,
+    storage: &dyn Storage,
+    config: &Config,
+) -> TaskResult<()> {
+    let (date, now) = time.resolve(config)?;
+    let task_name = start_with_estimate(
+        date,
+        &format!("start {task_name}"),
+        estimate,
+        storage,
+        config,
+        |task_manager| {
+            let result = match task_manager.resume_task(task_name.clone(), now) {
+                Err(TaskError::TaskNotFound(_)) => {
+                    let name = load_catalogue(storage)?.find(&task_name)?.name.clone();
+                    task_manager.start_new_task(name, now)
+                }
+                result => result,
+            };
+            add_tags(task_manager, result?, tags)
+        },
+    )?;
     println!("Resumed task: {task_name}");
     Ok(())
 }
 
-/// Starts a new task with the given name.
-fn start_new(task_name: String, config: &Config) -> TaskResult<()> {
-    let today = today(config)?;
-    let task_name = process_mutating_action(today, config, |task_manager| {
-        task_manager.start_new_task(task_name, Local::now())
-    })?;
+/// Starts a task like [process_mutating_action], then sets the estimate of the task named by
+/// `action` if one is given, without releasing the lock in between.
+fn start_with_estimate(
+    date: NaiveDate,
+    command: &str,
+    estimate: Option<Duration>,
+    storage: &dyn Storage,
+    config: &Config,
+    action: impl FnOnce(&mut TaskManager) -> TaskResult<String>,
+) -> TaskResult<String> {
+    let _lock = storage.lock()?;
+    carry_over_running_task(date, storage, config)?;
+    let task_name = mutate(date, command, storage, action)?;
+    if estimate.is_some() {
+        set_estimate(&task_name, estimate, storage)?;
+    }
+    Ok(task_name)
+}
+
+/// Starts a new task with the given name, tags and estimate.
+fn start_new(
+    task_name: String,
+    tags: Vec<String>,
+    estimate: Option<Duration>,
+    time: TimeArgs,
+    storage: &dyn Storage,
+    config: &Config,
+) -> TaskResult<()> {
+    let (date, now) = time.resolve(config)?;
+    let task_name = start_with_estimate(
+        date,
+        &format!("start --create {task_name}"),
+        estimate,
+        storage,
+        config,
+        |task_manager| {
+            let task_name = task_manager.start_new_task(task_name, now)?;
+            add_tags(task_manager, task_name, tags)
+        },
+    )?;
     println!("Started new task: {task_name}");
     Ok(())
 }
 
-/// Stops the currently running task.
-fn stop(date: Option<NaiveDate>, duration: Option<u16>, config: &Config) -> TaskResult<()> {
-    let date = date.unwrap_or(today(config)?);
-    let task_name = process_mutating_action(date, config, |task_manager| match duration {
-        None => task_manager.stop_running_task_with_time(Local::now()),
-        Some(minutes) => task_manager
-            .stop_running_task_with_duration(Duration::minutes(minutes as i64), Local::now()),
+/// Stops the currently running task, setting the note of the entry it stops if one is given.
+fn stop(
+    date: Option<TimeExpr>,
+    duration: Option<u16>,
+    note: Option<String>,
+    time: TimeArgs,
+    storage: &dyn Storage,
+    config: &Config,
+) -> TaskResult<()> {
+    let (today, now) = time.resolve(config)?;
+    let date = match date {
+        Some(date) => resolve_date(&date, config)?,
+        None => today,
+    };
+    let task_name = process_mutating_action(date, "stop", storage, config, |task_manager| {
+        let task_name = match duration {
+            None => task_manager.stop_running_task_with_time(now)?,
+            Some(minutes) => task_manager
+                .stop_running_task_with_duration(Duration::minutes(minutes as i64), now)?,
+        };
+        let Some(note) = note else {
+            return Ok(task_name);
+        };
+        let index = task_manager.task_record(&task_name)?.entries.len() - 1;
+        task_manager.note_entry(task_name, index, note)
     })?;
     println!("Stopped task: {task_name}");
     Ok(())
 }
 
 /// Resumes the last running task.
-fn resume_last(config: &Config) -> TaskResult<()> {
-    let today = today(config)?;
-    let task_name = process_mutating_action(today, config, |task_manager| {
-        task_manager.resume_last_task(Local::now())
-    })?;
+fn resume_last(time: TimeArgs, storage: &dyn Storage, config: &Config) -> TaskResult<()> {
+    let (date, now) = time.resolve(config)?;
+    let task_name =
+        process_mutating_action(
+            date,
+            "start",
+            storage,
+            config,
+            |task_manager| match task_manager.resume_last_task(now) {
+                Err(TaskError::NoTasksFound) => match load_catalogue(storage)?.most_recent() {
+                    Some(task) => task_manager.start_new_task(task.name.clone(), now),
+                    None => Err(TaskError::NoTasksFound),
+                },
+                result => result,
+            },
+        )?;
     println!("Resumed task: {task_name}");
     Ok(())
 }
 
-/// Switches to the given task.
-fn switch(task_name: String, config: &Config) -> TaskResult<()> {
-    let today = today(config)?;
-    let task_name = process_mutating_action(today, config, |task_manager| {
-        task_manager.switch_task(task_name, Local::now())
-    })?;
+/// Switches to the given task, adding the given tags to it.
+fn switch(
+    task_name: String,
+    tags: Vec<String>,
+    time: TimeArgs,
+    storage: &dyn Storage,
+    config: &Config,
+) -> TaskResult<()> {
+    let (date, now) = time.resolve(config)?;
+    let task_name = process_mutating_action(
+        date,
+        &format!("switch {task_name}"),
+        storage,
+        config,
+        |task_manager| {
+            let result = match task_manager.switch_task(task_name.clone(), now) {
+                Err(TaskError::TaskNotFound(_)) => {
+                    let name = load_catalogue(storage)?.find(&task_name)?.name.clone();
+                    task_manager.switch_new_task(name, now)
+                }
+                result => result,
+            };
+            add_tags(task_manager, result?, tags)
+        },
+    )?;
     println!("Switched to task: {task_name}");
     Ok(())
 }
 
-/// Switches to a new task.
-fn switch_new(task_name: String, config: &Config) -> TaskResult<()> {
-    let today = today(config)?;
-    let task_name = process_mutating_action(today, config, |task_manager| {
-        task_manager.switch_new_task(task_name, Local::now())
-    })?;
+/// Switches to a new task with the given tags.
+fn switch_new(
+    task_name: String,
+    tags: Vec<String>,
+    time: TimeArgs,
+    storage: &dyn Storage,
+    config: &Config,
+) -> TaskResult<()> {
+    let (date, now) = time.resolve(config)?;
+    let task_name = process_mutating_action(
+        date,
+        &format!("switch --create {task_name}"),
+        storage,
+        config,
+        |task_manager| {
+            let task_name = task_manager.switch_new_task(task_name, now)?;
+            add_tags(task_manager, task_name, tags)
+        },
+    )?;
     println!("Switched to new task: {task_name}");
     Ok(())
 }
 
+/// Adds the tags to the task with the given name, if there are any, and returns its name.
+fn add_tags(
+    task_manager: &mut TaskManager,
+    task_name: String,
+    tags: Vec<String>,
+) -> TaskResult<String> {
+    if tags.is_empty() {
+        return Ok(task_name);
+    }
+    task_manager.tag_task(task_name, tags, vec![])
+}
+
 /// Switches to the previous task.
-fn switch_previous(config: &Config) -> TaskResult<()> {
-    let today = today(config)?;
-    let task_name = process_mutating_action(today, config, |task_manager| {
-        task_manager.switch_last_task(Local::now())
+fn switch_previous(time: TimeArgs, storage: &dyn Storage, config: &Config) -> TaskResult<()> {
+    let (date, now) = time.resolve(config)?;
+    let task_name = process_mutating_action(date, "switch", storage, config, |task_manager| {
+        task_manager.switch_last_task(now)
     })?;
     println!("Switched to task: {task_name}");
     Ok(())
 }
 
+/// Adds a completed time entry from `from` to `to` to the given task.
+fn add(
+    task_name: String,
+    from: TimeExpr,
+    to: TimeExpr,
+    date: Option<TimeExpr>,
+    storage: &dyn Storage,
+    config: &Config,
+) -> TaskResult<()> {
+    let now = Local::now();
+    let day_start = day_start(config)?;
+    let (date, start) = match date {
+        Some(date) => {
+            let date = date.resolve_date(now, day_start)?;
+            let start = from.resolve_on_day(date, now, day_start)?;
+            if time_expr::logical_date(start, day_start) != date {
+                return Err(TaskError::TimeOutsideDate(
+                    start.format("%F %H:%M").to_string(),
+                    date,
+                ));
+            }
+            (date, start)
+        }
+        None => {
+            let start = from.resolve(now, day_start)?;
+            (time_expr::logical_date(start, day_start), start)
+        }
+    };
+    let end = to.resolve_on_day(date, now, day_start)?;
+    if time_expr::logical_date(end, day_start) != date {
+        return Err(TaskError::TimeOutsideDate(
+            end.format("%F %H:%M").to_string(),
+            date,
+        ));
+    }
+    if end > now {
+        return Err(TaskError::FutureTime(end.format("%F %H:%M").to_string()));
+    }
+    let task_name = process_mutating_action(
+        date,
+        &format!("add {task_name}"),
+        storage,
+        config,
+        |task_manager| task_manager.add_entry(task_name, start, end),
+    )?;
+    println!(
+        "Added {} to {} to task: {task_name}",
+        start.format("%F %H:%M"),
+        end.format("%F %H:%M")
+    );
+    Ok(())
+}
+
+/// Lists the time entries of the given task, numbered from 1. The running entry comes last.
+fn entries(
+    task_name: String,
+    date: Option<TimeExpr>,
+    storage: &dyn Storage,
+    config: &Config,
+) -> TaskResult<()> {
+    let date = resolve_date(&date.unwrap_or(TimeExpr::Now), config)?;
+    let task_manager = storage.load(date)?;
+    let record = task_manager.task_record(&task_manager.find_task(&task_name)?)?;
+    println!("  {} {}", date.format("%F"), record.name);
+    for note in &record.notes {
+        println!("    {note}");
+    }
+    let entries = record.entries.iter().zip(&record.entry_notes);
+    for (index, ((start, end), note)) in entries.enumerate() {
+        let note = note.as_ref().map(|note| format!(" | {note}"));
+        println!(
+            "    {:>2} | {} - {} | {}{}",
+            index + 1,
+            start.format("%H:%M"),
+            end.format("%H:%M"),
+            format_duration(*end - *start),
+            note.unwrap_or_default()
+        );
+    }
+    if let Some(start) = record.running_since {
+        let line = format!(
+            "    {:>2} | {} -       | {}",
+            record.entries.len() + 1,
+            start.format("%H:%M"),
+            format_duration(Local::now() - start)
+        );
+        println!("{}", line.green().bold());
+    }
+    Ok(())
+}
+
+/// Changes, deletes, splits or moves a single time entry.
+fn entry(command: EntryCommand, storage: &dyn Storage, config: &Config) -> TaskResult<()> {
+    let now = Local::now();
+    let day_start = day_start(config)?;
+    match command {
+        EntryCommand::Edit { entry, from, to } => {
+            let date = entry.date(config)?;
+            let from = from
+                .map(|from| from.resolve_on_day(date, now, day_start))
+                .transpose()?;
+            let to = to
+                .map(|to| to.resolve_on_day(date, now, day_start))
+                .transpose()?;
+            if let Some(time) = from.into_iter().chain(to).find(|time| *time > now) {
+                return Err(TaskError::FutureTime(time.format("%F %H:%M").to_string()));
+            }
+            let (task_name, start, end) = process_mutating_action(
+                date,
+                &format!("entry edit {} {}", entry.task, entry.number),
+                storage,
+                config,
+                |task_manager| {
+                    let task_name = task_manager.find_task(&entry.task)?;
+                    let (start, end) = task_manager.entry(&task_name, entry.index())?;
+                    let (start, end) = (from.unwrap_or(start), to.unwrap_or(end));
+                    let task_name =
+                        task_manager.edit_entry(task_name, entry.index(), start, end)?;
+                    Ok((task_name, start, end))
+                },
+            )?;
+            println!(
+                "Changed entry {} of task {task_name} to {} to {}",
+                entry.number,
+                start.format("%F %H:%M"),
+                end.format("%F %H:%M")
+            );
+        }
+        EntryCommand::Delete { entry } => {
+            let date = entry.date(config)?;
+            let _lock = storage.lock()?;
+            backup_before_destructive_command(storage, config)?;
+            carry_over_running_task(date, storage, config)?;
+            let task_name = mutate(
+                date,
+                &format!("entry delete {} {}", entry.task, entry.number),
+                storage,
+                |task_manager| {
+                    let task_name = task_manager.find_task(&entry.task)?;
+                    task_manager.delete_entry(task_name, entry.index())
+                },
+            )?;
+            println!("Deleted entry {} of task: {task_name}", entry.number);
+        }
+        EntryCommand::Split { entry, at } => {
+            let date = entry.date(config)?;
+            let at = at.resolve_on_day(date, now, day_start)?;
+            let task_name = process_mutating_action(
+                date,
+                &format!("entry split {} {}", entry.task, entry.number),
+                storage,
+                config,
+                |task_manager| {
+                    let task_name = task_manager.find_task(&entry.task)?;
+                    task_manager.split_entry(task_name, entry.index(), at)
+                },
+            )?;
+            println!(
+                "Split entry {} of task {task_name} at {}",
+                entry.number,
+                at.format("%F %H:%M")
+            );
+        }
+        EntryCommand::Move { entry, to } => {
+            let date = entry.date(config)?;
+            let (task_name, to) = process_mutating_action(
+                date,
+                &format!("entry move {} {} {to}", entry.task, entry.number),
+                storage,
+                config,
+                |task_manager| {
+                    let task_name = task_manager.find_task(&entry.task)?;
+                    task_manager.move_entry(task_name, entry.index(), to)
+                },
+            )?;
+            println!(
+                "Moved entry {} of task {task_name} to task: {to}",
+                entry.number
+            );
+        }
+    }
+    Ok(())
+}
+
+/// Opens the tasks of the given date in an editor, reopening it with the errors pointed out
+/// until the edited tasks are valid or the user gives up.
+fn edit(date: Option<TimeExpr>, storage: &dyn Storage, config: &Config) -> TaskResult<()> {
+    let date = resolve_date(&date.unwrap_or(TimeExpr::Now), config)?;
+    let day_start = day_start(config)?;
+    // Carries a task running on an earlier day over first, so that it shows in the editor and
+    // saving doesn't find the day changed by the carry-over.
+    let before = {
+        let _lock = storage.lock()?;
+        carry_over_running_task(date, storage, config)?;
+        storage.load(date)?
+    };
+    let records = before.to_records();
+    let original = editor::render(date, &records, day_start);
+    let mut document = original.clone();
+    let tasks = loop {
+        document = run_editor(&document, date)?;
+        if document == original {
+            println!("No changes made");
+            return Ok(());
+        }
+        match editor::parse(&document, date, Local::now(), day_start, &records) {
+            Ok(tasks) => break tasks,
+            Err(errors) => {
+                for error in &errors {
+                    println!("error: {error}");
+                }
+                if !confirm("Reopen the editor to fix them?")? {
+                    println!("No changes saved");
+                    return Ok(());
+                }
+                document = editor::annotate(&document, &errors);
+            }
+        }
+    };
+    process_mutating_action(date, "edit", storage, config, |task_manager| {
+        if *task_manager != before {
+            return Err(TaskError::ChangedWhileEditing(date));
+        }
+        task_manager.apply(Event::Snapshot { tasks })
+    })?;
+    println!("Saved the tasks of {date}");
+    Ok(())
+}
+
+/// Lets the user edit a document in their editor, returning the edited document.
+///
+/// The document is written to a new temporary file that only the user can read, which is
+/// deleted afterwards.
+fn run_editor(document: &str, date: NaiveDate) -> TaskResult<String> {
+    let editor = env::var("VISUAL")
+        .or_else(|_| env::var("EDITOR"))
+        .unwrap_or_else(|_| "vi".to_string());
+    let mut file = tempfile::Builder::new()
+        .prefix(&format!("tasklog-{date}-"))
+        .suffix(".toml")
+        .tempfile()
+        .map_err(file_io(&env::temp_dir()))?;
+    file.write_all(document.as_bytes())
+        .map_err(file_io(file.path()))?;
+    let path = file.into_temp_path();
+    let mut words = editor.split_whitespace();
+    let status = process::Command::new(words.next().unwrap_or("vi"))
+        .args(words)
+        .arg(&path)
+        .status();
+    let edited = fs::read_to_string(&path).map_err(file_io(&path));
+    drop(path);
+    let status = status.map_err(|e| TaskError::EditorIO(editor.clone(), e))?;
+    if !status.success() {
+        return Err(TaskError::EditorFailed(editor, status));
+    }
+    edited
+}
+
 /// Prints the name of the currently running task.
-fn current(config: &Config) -> TaskResult<()> {
+///
+/// A task left running on an earlier day is still current.
+fn current(storage: &dyn Storage, config: &Config) -> TaskResult<()> {
     let today = date(0, config)?;
-    let task_manager = read_tasks(today, config)?;
+    let task_manager = storage.load(today)?;
+    let budgets = budgets(&load_catalogue(storage)?, storage, Local::now())?;
+    let budget = |task: &str| {
+        let budget = budgets.get(task);
+        let budget = budget.map(|budget| format!(" ({})", budget.highlighted()));
+        budget.unwrap_or_default()
+    };
     match task_manager.running_task() {
-        None => println!("No task currently running"),
-        Some(task) => println!("Current task: {}", task),
+        None => match running_before(today, storage)? {
+            None => println!("No task currently running"),
+            Some((_, task_manager)) => {
+                let task = task_manager
+                    .running_task()
+                    .expect("should have a running task");
+                let since = task_manager.task_record(task)?.running_since;
+                let since = since.expect("running task should have a start");
+                println!(
+                    "Current task: {task} (since {}){}",
+                    since.format("%F %H:%M"),
+                    budget(task)
+                );
+            }
+        },
+        Some(task) => println!("Current task: {}{}", task, budget(task)),
     }
     Ok(())
 }
 
-/// Lists all tasks.
-fn list(days_ago: u16, config: &Config) -> TaskResult<()> {
+/// Lists all tasks, or those with all of the given tags.
+fn list(
+    days_ago: u16,
+    tree: bool,
+    tags: Vec<String>,
+    storage: &dyn Storage,
+    config: &Config,
+) -> TaskResult<()> {
     let today = date(days_ago, config)?;
-    let task_manager = read_tasks(today, config)?;
-    let tasks = task_manager.list_tasks();
-    println!("{}", tasks.join("\n"));
+    let task_manager = storage.load(today)?;
+    let mut tasks = task_manager.list_tasks();
+    tasks.retain(|task| {
+        let task_tags = task_manager.tags(task);
+        tags.iter().all(|tag| task_tags.contains(tag))
+    });
+    if tree {
+        print!("{}", task_path::tree(tasks));
+    } else {
+        println!("{}", tasks.join("\n"));
+    }
+    Ok(())
+}
+
+/// Adds tags to and removes tags from the given task.
+fn tag(
+    task_name: String,
+    changes: Vec<TagChange>,
+    date: Option<TimeExpr>,
+    storage: &dyn Storage,
+    config: &Config,
+) -> TaskResult<()> {
+    let date = resolve_date(&date.unwrap_or(TimeExpr::Now), config)?;
+    let (mut add, mut remove) = (vec![], vec![]);
+    let mut command = format!("tag {task_name}");
+    for change in changes {
+        match change {
+            TagChange::Add(tag) => {
+                command += &format!(" +{tag}");
+                add.push(tag);
+            }
+            TagChange::Remove(tag) => {
+                command += &format!(" -{tag}");
+                remove.push(tag);
+            }
+        }
+    }
+    let (task_name, tags) =
+        process_mutating_action(date, &command, storage, config, |task_manager| {
+            let task_name = task_manager.find_task(&task_name)?;
+            let task_name = task_manager.tag_task(task_name, add, remove)?;
+            let tags = task_manager.tags(&task_name).to_vec();
+            Ok((task_name, tags))
+        })?;
+    let tags: Vec<_> = tags.iter().map(|tag| format!("+{tag}")).collect();
+    match tags.is_empty() {
+        true => println!("Task {task_name} has no tags"),
+        false => println!("Tagged task: {task_name} {}", tags.join(" ")),
+    }
+    Ok(())
+}
+
+/// Adds a note to the given task, or sets the note of its entry with the given number.
+fn note(
+    task_name: String,
+    note: String,
+    entry: Option<NonZeroUsize>,
+    date: Option<TimeExpr>,
+    storage: &dyn Storage,
+    config: &Config,
+) -> TaskResult<()> {
+    let date = resolve_date(&date.unwrap_or(TimeExpr::Now), config)?;
+    let command = match entry {
+        None => format!("note {task_name}"),
+        Some(entry) => format!("note {task_name} --entry {entry}"),
+    };
+    let task_name = process_mutating_action(date, &command, storage, config, |task_manager| {
+        let task_name = task_manager.find_task(&task_name)?;
+        match entry {
+            None => task_manager.note_task(task_name, note),
+            Some(entry) => task_manager.note_entry(task_name, entry.get() - 1, note),
+        }
+    })?;
+    match entry {
+        None => println!("Added note to task: {task_name}"),
+        Some(entry) => println!("Set note of entry {entry} of task: {task_name}"),
+    }
+    Ok(())
+}
+
+/// Sets or clears the estimate of a task, or prints the time spent on it against its estimate
+/// when neither an estimate nor `clear` is given.
+fn estimate(
+    task_name: String,
+    estimate: Option<Duration>,
+    clear: bool,
+    storage: &dyn Storage,
+) -> TaskResult<()> {
+    if estimate.is_none() && !clear {
+        let catalogue = load_catalogue(storage)?;
+        let task = catalogue.find(&task_name)?;
+        match budgets(&catalogue, storage, Local::now())?.get(&task.name) {
+            Some(budget) => println!("Task {}: {budget}", task.name),
+            None => println!("Task {} has no estimate", task.name),
+        }
+        return Ok(());
+    }
+    let task_name = {
+        let _lock = storage.lock()?;
+        set_estimate(&task_name, estimate, storage)?
+    };
+    match estimate {
+        Some(estimate) => println!(
+            "Estimated task: {task_name} at {}",
+            format_duration(estimate)
+        ),
+        None => println!("Cleared estimate of task: {task_name}"),
+    }
+    Ok(())
+}
+
+/// Sets or clears the estimate of the task called `task_name` in the catalogue, returning the
+/// full name of the task. The caller must have locked the storage.
+fn set_estimate(
+    task_name: &str,
+    estimate: Option<Duration>,
+    storage: &dyn Storage,
+) -> TaskResult<String> {
+    let mut catalogue = load_catalogue(storage)?;
+    let task_name = catalogue.set_estimate(task_name, estimate)?;
+    save_catalogue(&catalogue, storage)?;
+    Ok(task_name)
+}
+
+/// Returns the budgets of the tasks that have an estimate, with the time spent on them across
+/// all days up to `now`. They are keyed by both the current and the earlier names of the tasks.
+///
+/// Only the days since the first use of any of those tasks are read, and days that cannot be
+/// read are skipped, see [load_readable].
+fn budgets(
+    catalogue: &Catalogue,
+    storage: &dyn Storage,
+    now: DateTime<Local>,
+) -> TaskResult<HashMap<String, Budget>> {
+    let estimated: Vec<_> = catalogue
+        .tasks()
+        .into_iter()
+        .filter(|task| task.estimate().is_some())
+        .collect();
+    let Some(first_used) = estimated.iter().map(|task| task.first_used).min() else {
+        return Ok(HashMap::new());
+    };
+    let mut spent: HashMap<u64, Duration> = estimated
+        .iter()
+        .map(|task| (task.id, Duration::zero()))
+        .collect();
+    for date in storage
+        .dates()?
+        .into_iter()
+        .filter(|date| *date >= first_used)
+    {
+        let Some(tasks) = load_readable(date, storage)? else {
+            continue;
+        };
+        for (name, time) in tasks.times(now) {
+            if let Some(total) = catalogue.get(name).and_then(|task| spent.get_mut(&task.id)) {
+                *total += time;
+            }
+        }
+    }
+    let names = catalogue
+        .tasks()
+        .into_iter()
+        .flat_map(|task| iter::once(&task.name).chain(&task.aliases));
+    let budgets = names.filter_map(|name| {
+        let task = catalogue.get(name)?;
+        let budget = Budget {
+            spent: *spent.get(&task.id)?,
+            estimate: task.estimate()?,
+        };
+        Some((name.clone(), budget))
+    });
+    Ok(budgets.collect())
+}
+
+/// Prints the invoice for the time spent on tasks from `from` to `to`. Tasks renamed since are
+/// billed under their current name.
+fn invoice(
+    from: TimeExpr,
+    to: Option<TimeExpr>,
+    client: Option<String>,
+    format: InvoiceFormat,
+    storage: &dyn Storage,
+    config: &Config,
+) -> TaskResult<()> {
+    let from = resolve_date(&from, config)?;
+    let to = resolve_date(&to.unwrap_or(TimeExpr::Now), config)?;
+    let catalogue = load_catalogue(storage)?;
+    let now = Local::now();
+    let mut times = vec![];
+    for date in NaiveDateIter::new(from, to) {
+        let task_manager = storage.load(date)?;
+        for (name, time) in task_manager.times(now) {
+            let tags = task_manager.tags(name).to_vec();
+            let name = catalogue.get(name).map_or(name, |task| &task.name);
+            times.push(TaskTime {
+                name: name.to_string(),
+                tags,
+                time,
+            });
+        }
+    }
+    let invoice = Invoice::new(&config.billing, client.as_deref(), from, to, times);
+    match format {
+        InvoiceFormat::Text => print!("{invoice}"),
+        InvoiceFormat::Json => println!(
+            "{}",
+            serde_json::to_string_pretty(&invoice).expect("should be able to serialize invoice")
+        ),
+        InvoiceFormat::Csv => print!("{}", invoice.to_csv()),
+    }
+    Ok(())
+}
+
+/// Lists the tasks known from any day, with the total time spent on them across all days. Days
+/// that cannot be read are left out of the totals, see [load_readable].
+fn tasks(storage: &dyn Storage) -> TaskResult<()> {
+    let catalogue = load_catalogue(storage)?;
+    let tasks = catalogue.tasks();
+    if tasks.is_empty() {
+        println!("No tasks found");
+        return Ok(());
+    }
+    let now = Local::now();
+    let mut totals = vec![Duration::zero(); tasks.len()];
+    for date in storage.dates()? {
+        let Some(day) = load_readable(date, storage)? else {
+            continue;
+        };
+        for (name, time) in day.times(now) {
+            if let Some(task) = catalogue.get(name) {
+                let index = tasks.iter().position(|other| other.id == task.id);
+                totals[index.expect("catalogued task should be listed")] += time;
+            }
+        }
+    }
+    let width = tasks
+        .iter()
+        .map(|task| task.name.len())
+        .max()
+        .unwrap_or(0)
+        .max(4);
+    for (task, total) in tasks.into_iter().zip(totals) {
+        let used = if task.first_used == task.last_used {
+            task.first_used.to_string()
+        } else {
+            format!("{} to {}", task.first_used, task.last_used)
+        };
+        println!(
+            "  {:>3}  {:<width$}  {}  {used}",
+            task.id,
+            task.name,
+            format_duration(total)
+        );
+    }
     Ok(())
 }
 
 /// Deletes the given task.
-fn delete(task_name: String, config: &Config) -> TaskResult<()> {
+fn delete(task_name: String, storage: &dyn Storage, config: &Config) -> TaskResult<()> {
     let today = today(config)?;
-    let task_name = process_mutating_action(today, config, |task_manager| {
-        task_manager.delete_task(task_name)
-    })?;
+    let _lock = storage.lock()?;
+    backup_before_destructive_command(storage, config)?;
+    carry_over_running_task(today, storage, config)?;
+    let task_name = mutate(
+        today,
+        &format!("delete {task_name}"),
+        storage,
+        |task_manager| task_manager.delete_task(task_name),
+    )?;
     println!("Deleted task: {task_name}");
     Ok(())
 }
 
 /// Renames the given task.
-fn rename(task_name: String, new_name: String, config: &Config) -> TaskResult<()> {
+fn rename(
+    task_name: String,
+    new_name: String,
+    storage: &dyn Storage,
+    config: &Config,
+) -> TaskResult<()> {
     let today = today(config)?;
-    let (task_name, new_name) = process_mutating_action(today, config, |task_manager| {
-        task_manager.rename_task(task_name, new_name)
-    })?;
+    let (task_name, new_name) = process_mutating_action(
+        today,
+        &format!("rename {task_name} {new_name}"),
+        storage,
+        config,
+        |task_manager| task_manager.rename_task(task_name, new_name),
+    )?;
     println!("Renamed task: {task_name} to {new_name}");
     Ok(())
 }
 
-/// Prints a report of the tasks worked on. The report is generated for the given number of days ago.
-fn report(
-    today: bool,
-    yesterday: bool,
-    mut dates: Vec<NaiveDate>,
-    from: Option<NaiveDate>,
-    to: Option<NaiveDate>,
+/// Upgrades the stored data to the current schema.
+fn migrate(dry_run: bool, storage: &dyn Storage, config: &Config) -> TaskResult<()> {
+    let _lock = storage.lock()?;
+    if !dry_run {
+        backup_before_destructive_command(storage, config)?;
+    }
+    let migrated = storage.migrate(dry_run)?;
+    for (date, version) in &migrated {
+        if dry_run {
+            println!("Would migrate {date} from schema v{version}");
+        } else {
+            println!("Migrated {date} from schema v{version}");
+        }
+    }
+    let current = schema::CURRENT_VERSION;
+    match (migrated.len(), dry_run) {
+        (0, _) => println!("All day files are at schema v{current}"),
+        (n, true) => println!("{n} day file(s) would be migrated to schema v{current}"),
+        (n, false) => println!("{n} day file(s) migrated to schema v{current}"),
+    }
+    Ok(())
+}
+
+/// Copies the days stored as JSON files in `dir` into the storage, along with the catalogue and
+/// the history stored there.
+fn import(
+    dir: Option<PathBuf>,
+    overwrite: bool,
+    storage: &dyn Storage,
     config: &Config,
 ) -> TaskResult<()> {
-    if let Some(from) = from {
-        let to = to.unwrap_or(date(0, config)?);
-        dates = NaiveDateIter::new(from, to).collect();
+    let dir = dir.unwrap_or_else(|| PathBuf::from(&config.data_dir));
+    if !dir.is_dir() {
+        let error = io::Error::new(ErrorKind::NotFound, "not a directory");
+        return Err(TaskError::FileIO(dir, error));
+    }
+    let source = JsonDirStorage::new(dir, StdDuration::from_millis(config.lock_timeout))?;
+    let _lock = storage.lock()?;
+    backup_before_destructive_command(storage, config)?;
+    for name in backup::DOCUMENTS {
+        let Some(data) = source.load_document(name)? else {
+            continue;
+        };
+        if !overwrite && storage.load_document(name)?.is_some() {
+            println!("Skipped the {name}: it already exists");
+            continue;
+        }
+        storage.save_document(name, &data)?;
+        println!("Imported the {name}");
+    }
+    let (mut imported, mut skipped) = (0, 0);
+    for date in source.dates()? {
+        let before = storage.load(date)?;
+        if !overwrite && !before.list_tasks().is_empty() {
+            println!("Skipped {date}: it already has tasks");
+            skipped += 1;
+            continue;
+        }
+        let mut tasks = before.clone();
+        tasks.apply(Event::Snapshot {
+            tasks: source.load(date)?,
+        })?;
+        record_and_save(&mut tasks, &before, date, storage)?;
+        imported += 1;
+    }
+    println!("Imported {imported} day(s), skipped {skipped}");
+    Ok(())
+}
+
+/// Prints the journal of the given date.
+fn log(date: Option<TimeExpr>, storage: &dyn Storage, config: &Config) -> TaskResult<()> {
+    let date = resolve_date(&date.unwrap_or(TimeExpr::Now), config)?;
+    let entries = storage.journal(date)?;
+    if entries.is_empty() {
+        println!("No changes recorded on {date}");
+    }
+    for entry in entries {
+        println!("{}  {}", entry.recorded.format("%F %T"), entry.event);
+    }
+    Ok(())
+}
+
+/// Replaces the saved tasks of the given dates with the result of replaying their journals.
+///
+/// The saved tasks are derived from the journal, so they are replaced even if they can no
+/// longer be read, in which case they are set aside first. Each rewrite is recorded in the
+/// history so that it can be undone.
+fn rebuild(
+    date: Option<TimeExpr>,
+    all: bool,
+    storage: &dyn Storage,
+    config: &Config,
+) -> TaskResult<()> {
+    let _lock = storage.lock()?;
+    backup_before_destructive_command(storage, config)?;
+    let dates = if all {
+        storage.journal_dates()?
     } else {
-        if yesterday {
-            dates.push(date(1, config)?);
+        vec![resolve_date(&date.unwrap_or(TimeExpr::Now), config)?]
+    };
+    for date in dates {
+        let entries = storage.journal(date)?;
+        if entries.is_empty() {
+            println!("No changes recorded on {date}");
+            continue;
+        }
+        let count = entries.len();
+        let tasks = TaskManager::replay(entries.into_iter().map(|entry| entry.event))?;
+        match storage.load(date) {
+            Ok(saved) if saved == tasks => {
+                println!("{date} is up to date");
+                continue;
+            }
+            Ok(_) => {}
+            Err(_) => {
+                if let Some(path) = storage.set_aside(date)? {
+                    println!("Moved the damaged data of {date} to {}", path.display());
+                }
+            }
+        }
+        mutate(date, "rebuild", storage, |task_manager| {
+            task_manager.apply(Event::Snapshot { tasks })
+        })?;
+        println!("Rebuilt {date} from {count} event(s)");
+    }
+    Ok(())
+}
+
+/// Writes a backup of all data to `output`, or to the backups directory by default.
+fn backup(output: Option<PathBuf>, storage: &dyn Storage, config: &Config) -> TaskResult<()> {
+    let _lock = storage.lock()?;
+    let path = output.unwrap_or_else(|| backups_dir(config).join(backup::file_name()));
+    if let Some(parent) = path
+        .parent()
+        .filter(|parent| !parent.as_os_str().is_empty())
+    {
+        fs::create_dir_all(parent).map_err(file_io(parent))?;
+    }
+    let backup = Backup::collect(storage, read_config_file(config))?;
+    backup.write(&path)?;
+    println!(
+        "Backed up {} day(s) to {}",
+        backup.days.len(),
+        path.display()
+    );
+    Ok(())
+}
+
+/// Restores the days, journals and documents of a backup that differ from the stored ones, after
+/// previewing them.
+///
+/// Journals are only ever appended to: the events of a backed up journal are restored when the
+/// stored journal holds the start of them, and a diverged journal is kept as it is.
+fn restore(archive: PathBuf, yes: bool, storage: &dyn Storage, config: &Config) -> TaskResult<()> {
+    let backup = Backup::read(&archive)?;
+    let _lock = storage.lock()?;
+    let mut changes = vec![];
+    for (date, tasks) in backup.days {
+        let current = storage.load(date)?;
+        if current == tasks {
+            continue;
+        }
+        if current.list_tasks().is_empty() {
+            println!("  {date}  added");
+        } else {
+            println!("  {date}  changed");
+        }
+        changes.push((date, tasks));
+    }
+    let mut journals = vec![];
+    for (date, entries) in backup.journals {
+        let current = storage.journal(date)?;
+        if current.len() < entries.len() && entries.starts_with(&current) {
+            println!("  {date}  journal added");
+            journals.push((date, entries[current.len()..].to_vec()));
+        }
+    }
+    let mut documents = vec![];
+    for (name, data) in backup.documents {
+        if storage.load_document(&name)?.as_ref() != Some(&data) {
+            println!("  {name}  changed");
+            documents.push((name, data));
+        }
+    }
+    if changes.is_empty() && journals.is_empty() && documents.is_empty() {
+        println!("Nothing to restore: all days, journals and documents match the backup");
+        return Ok(());
+    }
+    let summary = format!(
+        "{} day(s), {} journal(s) and {} document(s)",
+        changes.len(),
+        journals.len(),
+        documents.len()
+    );
+    if !yes && !confirm(&format!("Restore {summary}?"))? {
+        println!("Restore cancelled");
+        return Ok(());
+    }
+    backup_before_destructive_command(storage, config)?;
+    // The documents go first, so that restoring the days is recorded in the restored history.
+    for (name, data) in documents {
+        storage.save_document(&name, &data)?;
+    }
+    // The journals go before the days too, so that the snapshots of the restored days follow them.
+    for (date, entries) in journals {
+        storage.append_journal(date, &entries)?;
+    }
+    let command = format!("restore {}", archive.display());
+    for (date, tasks) in changes {
+        mutate(date, &command, storage, |task_manager| {
+            task_manager.apply(Event::Snapshot { tasks })
+        })?;
+    }
+    println!("Restored {summary}");
+    Ok(())
+}
+
+/// Checks all days for problems, and repairs the days the user agrees to, or all of them if
+/// `auto` is set.
+///
+/// Days that cannot be loaded are set aside before being replaced with their repaired tasks.
+fn fsck(auto: bool, storage: &dyn Storage, config: &Config) -> TaskResult<()> {
+    let _lock = storage.lock()?;
+    let mut days = vec![];
+    let mut unreadable = 0;
+    for date in storage.dates()? {
+        match storage.load_records(date) {
+            Ok(records) => days.push((date, records)),
+            Err(e) => {
+                println!("{date}:\n  - cannot be read (needs a manual fix): {e}");
+                unreadable += 1;
+            }
+        }
+    }
+    let checks: Vec<_> = fsck::check(days, day_start(config)?)
+        .into_iter()
+        .filter(|check| !check.problems.is_empty())
+        .collect();
+    if checks.is_empty() && unreadable == 0 {
+        println!("No problems found");
+        return Ok(());
+    }
+    let mut backed_up = false;
+    let mut repaired = 0;
+    for check in &checks {
+        println!("{}:", check.date);
+        for problem in &check.problems {
+            if problem.is_repairable() {
+                println!("  - {problem}");
+            } else {
+                println!("  - {problem} (needs a manual fix)");
+            }
         }
-        if today {
-            dates.push(date(0, config)?);
+        if !check.is_repairable() || !(auto || confirm(&format!("Repair {}?", check.date))?) {
+            continue;
         }
-        dates.sort();
-        dates.dedup();
+        if !backed_up {
+            backup_before_destructive_command(storage, config)?;
+            backed_up = true;
+        }
+        match storage.load(check.date) {
+            Err(TaskError::InvalidFile(..) | TaskError::InvalidData(..)) => {
+                if let Some(path) = storage.set_aside(check.date)? {
+                    println!("  Moved the damaged data to {}", path.display());
+                }
+            }
+            result => {
+                result?;
+            }
+        }
+        let tasks = check.repair();
+        mutate(check.date, "fsck", storage, |task_manager| {
+            task_manager.apply(Event::Snapshot { tasks })
+        })?;
+        println!("  Repaired {}", check.date);
+        repaired += 1;
+    }
+    println!(
+        "Found problems on {} day(s), repaired {repaired}",
+        checks.len() + unreadable
+    );
+    Ok(())
+}
+
+/// Takes an automatic backup before a destructive command, unless they are disabled.
+fn backup_before_destructive_command(storage: &dyn Storage, config: &Config) -> TaskResult<()> {
+    if config.backup_keep == 0 {
+        return Ok(());
     }
+    let backup = Backup::collect_readable(storage, read_config_file(config))?;
+    backup::auto_backup(&backup, &backups_dir(config), config.backup_keep)?;
+    Ok(())
+}
+
+/// Returns the directory holding the backups.
+fn backups_dir(config: &Config) -> PathBuf {
+    PathBuf::from(&config.data_dir).join("backups")
+}
+
+/// Reads the configuration file, to include it in backups.
+fn read_config_file(config: &Config) -> Option<String> {
+    fs::read_to_string(&config.file).ok()
+}
+
+/// Asks the user a yes/no question. Anything but an explicit yes is taken as no.
+fn confirm(question: &str) -> TaskResult<bool> {
+    print!("{question} [y/N] ");
+    io::stdout().flush().map_err(TaskError::TerminalIO)?;
+    let mut answer = String::new();
+    let read = io::stdin()
+        .read_line(&mut answer)
+        .map_err(TaskError::TerminalIO)?;
+    if read == 0 {
+        println!();
+    }
+    Ok(matches!(answer.trim().to_lowercase().as_str(), "y" | "yes"))
+}
+
+/// Prints a report of the tasks worked on in the given format, or writes it to `output`. There
+/// is a report for each day, unless they are merged into one as set out by `aggregation`.
+fn report(
+    dates: ReportDates,
+    options: ReportOptions,
+    aggregation: Option<Aggregation>,
+    format: ReportFormat,
+    output: Option<PathBuf>,
+    storage: &dyn Storage,
+    config: &Config,
+) -> TaskResult<()> {
+    let dates = dates.resolve(config)?;
     let now = Local::now();
-    println!();
-    for date in dates {
-        let task_manager = read_tasks(date, config)?;
-        let report = task_manager.generate_report(date, now);
-        println!("{report}");
+    let options = ReportOptions {
+        budgets: budgets(&load_catalogue(storage)?, storage, now)?,
+        ..options
+    };
+    let mut reports = vec![];
+    for &date in &dates {
+        reports.push(storage.load(date)?.report(date, now, &options));
+    }
+    if let (Some(aggregation), Some(&from), Some(&to)) = (aggregation, dates.first(), dates.last())
+    {
+        reports = vec![Report::aggregate(from, to, &reports, aggregation)];
+    }
+    if output.is_some() {
+        colored::control::set_override(false);
+    }
+    let report = format.formatter().format(&reports);
+    match output {
+        Some(path) => {
+            fs::write(&path, report).map_err(file_io(&path))?;
+            println!("Wrote report to {}", path.display());
+        }
+        None => print!("{report}"),
     }
     Ok(())
 }
@@ -413,49 +2397,25 @@ impl Iterator for NaiveDateIter {
     }
 }
 
-/// Reads the tasks from the file for the given date.
-fn read_tasks(today: NaiveDate, config: &Config) -> TaskResult<TaskManager> {
-    let file = get_file(today, config)?;
-    let task_manager = match fs::read_to_string(file) {
-        Ok(data) => serde_json::from_str(&data)?,
-        Err(_) => TaskManager::default(),
-    };
-    Ok(task_manager)
+/// Returns today's date.
+fn today(config: &Config) -> TaskResult<NaiveDate> {
+    date(0, config)
 }
 
-/// Writes the tasks to the file for the given date.
-fn write_tasks(tasks: &TaskManager, today: NaiveDate, config: &Config) -> TaskResult<()> {
-    let file = get_file(today, config)?;
-    let data = serde_json::to_string(&tasks).expect("should be able to serialize tasks");
-    fs::write(file, data)?;
-    Ok(())
+/// Returns the configured time at which days start.
+fn day_start(config: &Config) -> TaskResult<NaiveTime> {
+    Ok(NaiveTime::from_str(&config.day_start)
+        .map_err(|e| config::ConfigError::Foreign(Box::new(e)))?)
 }
 
-/// Gets the file path for the given date.
-fn get_file(today: NaiveDate, config: &Config) -> TaskResult<PathBuf> {
-    let today = today.format("%F.json").to_string();
-    let file = PathBuf::from(&config.data_dir).join(today);
-    Ok(file)
-}
-
-/// Returns today's date.
-fn today(config: &Config) -> TaskResult<NaiveDate> {
-    date(0, config)
+/// Resolves a time expression to the day it falls on.
+fn resolve_date(expr: &TimeExpr, config: &Config) -> TaskResult<NaiveDate> {
+    expr.resolve_date(Local::now(), day_start(config)?)
 }
 
 /// Returns the date `days_ago` days ago.
 fn date(days_ago: u16, config: &Config) -> TaskResult<NaiveDate> {
-    let now = Local::now();
-    let time = now.time();
-    let day_start = NaiveTime::from_str(&config.day_start)
-        .map_err(|e| config::ConfigError::Foreign(Box::new(e)))?;
-    let today = if time >= day_start {
-        now.date_naive()
-    } else {
-        now.date_naive()
-            .checked_sub_days(Days::new(1))
-            .expect("should be able to subtract 1 from today")
-    };
+    let today = time_expr::logical_date(Local::now(), day_start(config)?);
     Ok(today
         .checked_sub_days(Days::new(days_ago as u64))
         .expect("should be able to subtract any u16 days from today"))
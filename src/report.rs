@@ -0,0 +1,685 @@
+/*
+ * tasklog - A command-line task tracking tool.
+ *
+ * Copyright (C) 2024 Lucas M. de Jong Larrarte
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with this program. If not, see <https://www.gnu.org/licenses/>.
+ */
+#[cfg(test)]
+mod test;
+
+use std::collections::HashMap;
+use std::fmt;
+
+use chrono::{DateTime, Datelike, Duration, Local, NaiveDate, Timelike};
+use colored::Colorize;
+use serde::{Serialize, Serializer};
+
+use crate::task_manager::format_duration;
+
+/// Options of [TaskManager::report](crate::TaskManager::report).
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct ReportOptions {
+    /// Roll the time of tasks up into their ancestors at this depth, see
+    /// [task_path::ancestor](crate::task_path::ancestor).
+    pub depth: Option<usize>,
+    /// Only report the tasks that have all of these tags.
+    pub tags: Vec<String>,
+    /// Report the time per tag instead of per task.
+    pub by_tag: bool,
+    /// List the notes of the tasks and their entries under each row.
+    pub verbose: bool,
+    /// The budgets of the tasks that have an estimate, by task name.
+    pub budgets: HashMap<String, Budget>,
+}
+
+/// The time spent on a task across all days, against the time it is estimated to take.
+#[derive(Debug, Serialize, Clone, Copy, PartialEq)]
+pub struct Budget {
+    #[serde(rename = "spent_minutes", serialize_with = "minutes")]
+    pub spent: Duration,
+    #[serde(rename = "estimate_minutes", serialize_with = "minutes")]
+    pub estimate: Duration,
+}
+impl Budget {
+    /// Checks if more time was spent on the task than estimated, in whole minutes.
+    pub fn is_over(&self) -> bool {
+        self.whole_spent() > self.estimate
+    }
+
+    /// Returns the time spent in whole minutes, as it is shown.
+    fn whole_spent(&self) -> Duration {
+        Duration::minutes(self.spent.num_minutes())
+    }
+
+    /// Returns the budget as text, in red if the task is over it.
+    pub fn highlighted(&self) -> String {
+        match self.is_over() {
+            true => self.to_string().red().bold().to_string(),
+            false => self.to_string(),
+        }
+    }
+}
+impl fmt::Display for Budget {
+    /// Shows the time spent of the estimate, and the time left or the time spent over it. All of
+    /// them are in whole minutes, so that they add up.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let whole_spent = self.whole_spent();
+        let spent = format_duration(whole_spent);
+        let estimate = format_duration(self.estimate);
+        if self.is_over() {
+            let over = format_duration(whole_spent - self.estimate);
+            write!(f, "{spent} of {estimate}, {over} over")
+        } else {
+            let left = format_duration(self.estimate - whole_spent);
+            write!(f, "{spent} of {estimate}, {left} left")
+        }
+    }
+}
+
+/// The time spent on the tasks of a day, or of several days aggregated into one report. Its
+/// [Display](fmt::Display) is the table printed on the terminal.
+#[derive(Debug, Serialize, Clone, PartialEq)]
+pub struct Report {
+    /// The day of the report, or the first day of an aggregated report.
+    pub date: NaiveDate,
+    /// The last day of an aggregated report.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub to: Option<NaiveDate>,
+    pub rows: Vec<ReportRow>,
+    /// The time spent on the tasks reported. When reporting by tag, the rows can add up to more.
+    #[serde(rename = "total_minutes", serialize_with = "minutes")]
+    pub total: Duration,
+    /// The average time spent per day worked, for an aggregated report.
+    #[serde(
+        rename = "average_minutes",
+        skip_serializing_if = "Option::is_none",
+        serialize_with = "optional_minutes"
+    )]
+    pub average: Option<Duration>,
+    /// The time entries of the tasks reported, in order of start.
+    pub timeline: Vec<TimelineEntry>,
+}
+
+/// How [Report::aggregate] makes the rows of a report over several days.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Aggregation {
+    /// Adds up the rows of the days, so that there is a row per task, or per group of tasks.
+    Rows,
+    /// A row per day.
+    Day,
+    /// A row per ISO week, like 2024-W29.
+    Week,
+    /// A row per month, like 2024-07.
+    Month,
+}
+impl Aggregation {
+    /// Returns the name of the row that the time of a day counts towards, or `None` if the rows
+    /// of the days are kept.
+    fn row_name(self, date: NaiveDate) -> Option<String> {
+        match self {
+            Aggregation::Rows => None,
+            Aggregation::Day => Some(date.format("%F").to_string()),
+            Aggregation::Week => {
+                let week = date.iso_week();
+                Some(format!("{}-W{:02}", week.year(), week.week()))
+            }
+            Aggregation::Month => Some(date.format("%Y-%m").to_string()),
+        }
+    }
+}
+
+impl Report {
+    /// Returns the day of the report, or the days it spans, as text.
+    pub fn period(&self) -> String {
+        match self.to {
+            Some(to) => format!("{} to {}", self.date.format("%F"), to.format("%F")),
+            None => self.date.format("%F").to_string(),
+        }
+    }
+
+    /// Merges the reports of the days from `from` to `to` into a single report, with rows made
+    /// as set out by `aggregation`.
+    ///
+    /// Averages are taken over the days on which any time was spent, so that days off don't
+    /// lower them. The average of a day, week or month is taken over its own days.
+    pub fn aggregate(
+        from: NaiveDate,
+        to: NaiveDate,
+        reports: &[Report],
+        aggregation: Aggregation,
+    ) -> Report {
+        let worked = |report: &Report| i32::from(!report.total.is_zero());
+        let mut rows: Vec<(ReportRow, i32)> = vec![];
+        for report in reports {
+            let day_rows = match aggregation.row_name(report.date) {
+                None => report.rows.clone(),
+                Some(name) => vec![ReportRow {
+                    name,
+                    time: report.total,
+                    percent: 0.0,
+                    average: None,
+                    running: report.rows.iter().any(|row| row.running),
+                    notes: vec![],
+                    budget: None,
+                }],
+            };
+            for day_row in day_rows {
+                match rows.iter_mut().find(|(row, _)| row.name == day_row.name) {
+                    Some((row, days)) => {
+                        row.time += day_row.time;
+                        row.running |= day_row.running;
+                        row.notes.extend(day_row.notes);
+                        row.budget = row.budget.or(day_row.budget);
+                        *days += worked(report);
+                    }
+                    None => rows.push((day_row, worked(report))),
+                }
+            }
+        }
+        let total: Duration = reports.iter().map(|report| report.total).sum();
+        let days: i32 = reports.iter().map(worked).sum();
+        let rows = rows.into_iter().map(|(mut row, row_days)| {
+            let days = match aggregation {
+                Aggregation::Rows => days,
+                _ => row_days,
+            };
+            row.percent = percent(row.time, total);
+            row.average = Some(row.time / days.max(1));
+            row
+        });
+        Report {
+            date: from,
+            to: Some(to),
+            rows: rows.collect(),
+            total,
+            average: Some(total / days.max(1)),
+            timeline: reports
+                .iter()
+                .flat_map(|report| report.timeline.iter().cloned())
+                .collect(),
+        }
+    }
+}
+
+/// A time entry of a task in a report. The entry of a running task ends at the time of the
+/// report.
+#[derive(Debug, Serialize, Clone, PartialEq)]
+pub struct TimelineEntry {
+    /// The day the entry is reported on.
+    pub date: NaiveDate,
+    pub task: String,
+    pub start: DateTime<Local>,
+    pub end: DateTime<Local>,
+    pub running: bool,
+}
+
+/// A row of a report, for a task or for the tasks rolled up into it.
+#[derive(Debug, Serialize, Clone, PartialEq)]
+pub struct ReportRow {
+    pub name: String,
+    #[serde(rename = "minutes", serialize_with = "minutes")]
+    pub time: Duration,
+    /// The share of the total time, in percent.
+    pub percent: f64,
+    /// The average time spent per day worked, for an aggregated report.
+    #[serde(
+        rename = "average_minutes",
+        skip_serializing_if = "Option::is_none",
+        serialize_with = "optional_minutes"
+    )]
+    pub average: Option<Duration>,
+    /// Whether the task, or one of the tasks rolled up into the row, is running.
+    pub running: bool,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub notes: Vec<String>,
+    /// The budget of the task, for a row that is a single task with an estimate.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub budget: Option<Budget>,
+}
+
+impl fmt::Display for Report {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "  {} ", self.period())?;
+        let max_length = self
+            .rows
+            .iter()
+            .map(|row| row.name.len())
+            .max()
+            .unwrap_or(0)
+            .max(5);
+        for row in &self.rows {
+            let line = format!(
+                "    {:<max_length$} | {} | {:>5.1}%",
+                row.name,
+                format_duration(row.time),
+                row.percent
+            );
+            if row.running {
+                write!(f, "{}", line.green().bold())?;
+            } else {
+                write!(f, "{line}")?;
+            }
+            if let Some(average) = row.average {
+                write!(f, " | {}/day", format_duration(average))?;
+            }
+            if let Some(budget) = row.budget {
+                write!(f, " | {}", budget.highlighted())?;
+            }
+            writeln!(f)?;
+            for note in &row.notes {
+                writeln!(f, "      - {note}")?;
+            }
+        }
+        let average_length = if self.average.is_some() { 12 } else { 0 };
+        let budget_length = self
+            .rows
+            .iter()
+            .filter_map(|row| row.budget)
+            .map(|budget| budget.to_string().len() + 3)
+            .max()
+            .unwrap_or(0);
+        let length = max_length + 17 + average_length + budget_length;
+        writeln!(f, "    {:=>1$}", "", length)?;
+        write!(
+            f,
+            "    {:<max_length$} | {} | 100.0%",
+            "Total",
+            format_duration(self.total)
+        )?;
+        if let Some(average) = self.average {
+            write!(f, " | {}/day", format_duration(average))?;
+        }
+        writeln!(f)
+    }
+}
+
+/// Renders the reports of one or more days in a format.
+pub trait ReportFormatter {
+    /// Renders the reports, which are in order of date.
+    fn format(&self, reports: &[Report]) -> String;
+}
+
+/// Renders reports as the tables printed on the terminal, highlighting running tasks and tasks
+/// over their budget.
+pub struct TextFormatter;
+impl ReportFormatter for TextFormatter {
+    fn format(&self, reports: &[Report]) -> String {
+        let mut text = "\n".to_string();
+        for report in reports {
+            text += &format!("{report}\n");
+        }
+        text
+    }
+}
+
+/// Renders reports as a JSON array, with times in minutes.
+pub struct JsonFormatter;
+impl ReportFormatter for JsonFormatter {
+    fn format(&self, reports: &[Report]) -> String {
+        serde_json::to_string_pretty(reports).expect("should be able to serialize reports") + "\n"
+    }
+}
+
+/// Renders reports as a single table of delimited values, with times in minutes. Each report has
+/// a row per task, followed by a row for its total. The `kind` column tells them apart.
+pub struct DelimitedFormatter {
+    separator: char,
+}
+impl DelimitedFormatter {
+    /// Creates a formatter for comma-separated values, quoting fields as needed.
+    pub fn csv() -> Self {
+        Self { separator: ',' }
+    }
+
+    /// Creates a formatter for tab-separated values. Tabs and line breaks within fields are
+    /// replaced by spaces, as they cannot be quoted.
+    pub fn tsv() -> Self {
+        Self { separator: '\t' }
+    }
+
+    /// Returns a field, quoted or cleaned up so that it doesn't break the table.
+    fn field(&self, field: &str) -> String {
+        match self.separator {
+            ',' => csv_field(field),
+            separator => field.replace([separator, '\n', '\r'], " "),
+        }
+    }
+}
+impl ReportFormatter for DelimitedFormatter {
+    fn format(&self, reports: &[Report]) -> String {
+        let header = [
+            "kind",
+            "date",
+            "task",
+            "minutes",
+            "percent",
+            "running",
+            "estimate_minutes",
+            "average_minutes",
+        ];
+        let mut table = vec![header.map(str::to_string).to_vec()];
+        for report in reports {
+            let date = self.field(&report.period());
+            let minutes = |time: Option<Duration>| {
+                let minutes = time.map(|time| time.num_minutes().to_string());
+                minutes.unwrap_or_default()
+            };
+            for row in &report.rows {
+                table.push(vec![
+                    "task".to_string(),
+                    date.clone(),
+                    self.field(&row.name),
+                    row.time.num_minutes().to_string(),
+                    format!("{:.1}", row.percent),
+                    row.running.to_string(),
+                    minutes(row.budget.map(|budget| budget.estimate)),
+                    minutes(row.average),
+                ]);
+            }
+            table.push(vec![
+                "total".to_string(),
+                date,
+                String::new(),
+                report.total.num_minutes().to_string(),
+                "100.0".to_string(),
+                String::new(),
+                String::new(),
+                minutes(report.average),
+            ]);
+        }
+        let separator = self.separator.to_string();
+        let lines = table.into_iter().map(|row| row.join(&separator) + "\n");
+        lines.collect()
+    }
+}
+
+/// Renders reports as Markdown, with a heading and a table for each report. Running tasks are
+/// marked as such, and the budgets of tasks are shown when any task has one.
+pub struct MarkdownFormatter;
+impl ReportFormatter for MarkdownFormatter {
+    fn format(&self, reports: &[Report]) -> String {
+        let mut markdown = String::new();
+        for report in reports {
+            if !markdown.is_empty() {
+                markdown += "\n";
+            }
+            let averages = report.average.is_some();
+            let budgets = report.rows.iter().any(|row| row.budget.is_some());
+            markdown += &format!("## {}\n\n", report.period());
+            markdown += "| Task | Time | Share |";
+            markdown += if averages { " Per day |" } else { "" };
+            markdown += if budgets { " Estimate |\n" } else { "\n" };
+            markdown += "| :--- | ---: | ---: |";
+            markdown += if averages { " ---: |" } else { "" };
+            markdown += if budgets { " :--- |\n" } else { "\n" };
+            for row in &report.rows {
+                let running = if row.running { " (running)" } else { "" };
+                markdown += &format!(
+                    "| {}{running} | {} | {:.1}% |",
+                    row.name.replace('|', "\\|"),
+                    format_duration(row.time),
+                    row.percent
+                );
+                if averages {
+                    let average = row.average.map(format_duration);
+                    markdown += &format!(" {} |", average.unwrap_or_default());
+                }
+                if budgets {
+                    let budget = row.budget.map(|budget| budget.to_string());
+                    markdown += &format!(" {} |", budget.unwrap_or_default());
+                }
+                markdown += "\n";
+            }
+            markdown += &format!(
+                "| **Total** | **{}** | **100.0%** |",
+                format_duration(report.total)
+            );
+            if let Some(average) = report.average {
+                markdown += &format!(" **{}** |", format_duration(average));
+            }
+            markdown += if budgets { " |\n" } else { "\n" };
+        }
+        markdown
+    }
+}
+
+/// Renders reports as a self-contained HTML page, with a timeline of the entries of each day, a
+/// bar chart of the time per task and a table of the totals per task and day. Charts are inline
+/// SVG, so the page needs no network access.
+pub struct HtmlFormatter;
+impl HtmlFormatter {
+    /// The width of the charts, in pixels.
+    const WIDTH: f64 = 960.0;
+    /// The width of the labels left of the bars of the bar chart, in pixels.
+    const LABEL_WIDTH: f64 = 240.0;
+    /// The colors of the tasks, used in turn.
+    const COLORS: [&'static str; 10] = [
+        "#4e79a7", "#f28e2b", "#e15759", "#76b7b2", "#59a14f", "#edc948", "#b07aa1", "#ff9da7",
+        "#9c755f", "#bab0ac",
+    ];
+
+    /// Returns the color of a task or row, given the names of all of them.
+    fn color(names: &[&str], name: &str) -> &'static str {
+        let index = names.iter().position(|other| *other == name).unwrap_or(0);
+        Self::COLORS[index % Self::COLORS.len()]
+    }
+
+    /// Renders the entries of a day on a line spanning the hours they were worked in.
+    fn timeline(date: NaiveDate, entries: &[&TimelineEntry], names: &[&str]) -> String {
+        let mut html = format!("<h3>{}</h3>\n", date.format("%A %F"));
+        let starts = entries.iter().map(|entry| entry.start);
+        let ends = entries.iter().map(|entry| entry.end);
+        let (Some(begin), Some(end)) = (starts.min(), ends.max()) else {
+            return html + "<p>No time entries.</p>\n";
+        };
+        let hour = begin.date_naive().and_hms_opt(begin.hour(), 0, 0);
+        let hour = hour.and_then(|hour| hour.and_local_timezone(Local).earliest());
+        let begin = hour.unwrap_or(begin);
+        let hours = ((end - begin).num_minutes() + 59) / 60;
+        let hours = hours.max(1);
+        let x = |time: DateTime<Local>| {
+            (time - begin).num_seconds() as f64 / (hours * 3600) as f64 * Self::WIDTH
+        };
+        html += &format!(
+            "<svg class=\"timeline\" width=\"{0}\" height=\"48\" viewBox=\"0 0 {0} 48\">\n",
+            Self::WIDTH
+        );
+        for hour in 0..=hours {
+            let time = begin + Duration::hours(hour);
+            let x = x(time);
+            html += &format!(
+                "<line x1=\"{x:.1}\" y1=\"0\" x2=\"{x:.1}\" y2=\"32\"/>\n\
+                 <text x=\"{x:.1}\" y=\"44\">{}</text>\n",
+                time.format("%H:%M")
+            );
+        }
+        for entry in entries {
+            let running = if entry.running { " (running)" } else { "" };
+            html += &format!(
+                "<rect x=\"{:.1}\" y=\"4\" width=\"{:.1}\" height=\"24\" fill=\"{}\">\
+                 <title>{} {}-{}{running}</title></rect>\n",
+                x(entry.start),
+                (x(entry.end) - x(entry.start)).max(1.0),
+                Self::color(names, &entry.task),
+                escape_html(&entry.task),
+                entry.start.format("%H:%M"),
+                entry.end.format("%H:%M")
+            );
+        }
+        html + "</svg>\n"
+    }
+
+    /// Renders a bar for the time spent on each row across all days.
+    fn bar_chart(totals: &[(&str, Duration)], names: &[&str]) -> String {
+        let max = totals.iter().map(|(_, time)| *time).max();
+        let max = max.unwrap_or_else(Duration::zero).num_seconds().max(1) as f64;
+        let height = totals.len() * 24;
+        let mut html = format!(
+            "<svg class=\"bars\" width=\"{0}\" height=\"{height}\" \
+             viewBox=\"0 0 {0} {height}\">\n",
+            Self::WIDTH
+        );
+        let bar_width = Self::WIDTH - Self::LABEL_WIDTH - 60.0;
+        for (index, (name, time)) in totals.iter().enumerate() {
+            let y = index * 24;
+            let width = time.num_seconds() as f64 / max * bar_width;
+            html += &format!(
+                "<text class=\"label\" x=\"{:.1}\" y=\"{}\">{}</text>\n\
+                 <rect x=\"{:.1}\" y=\"{}\" width=\"{width:.1}\" height=\"18\" fill=\"{}\"/>\n\
+                 <text x=\"{:.1}\" y=\"{}\">{}</text>\n",
+                Self::LABEL_WIDTH - 8.0,
+                y + 14,
+                escape_html(name),
+                Self::LABEL_WIDTH,
+                y + 2,
+                Self::color(names, name),
+                Self::LABEL_WIDTH + width + 6.0,
+                y + 14,
+                format_duration(*time)
+            );
+        }
+        html + "</svg>\n"
+    }
+
+    /// Renders a table of the time spent on each row per day, with the totals.
+    fn totals_table(reports: &[Report], totals: &[(&str, Duration)]) -> String {
+        let mut html = "<table>\n<tr><th>Task</th>".to_string();
+        for report in reports {
+            html += &format!("<th>{}</th>", report.period());
+        }
+        html += "<th>Total</th></tr>\n";
+        for (name, total) in totals {
+            html += &format!("<tr><td>{}</td>", escape_html(name));
+            for report in reports {
+                let row = report.rows.iter().find(|row| row.name == *name);
+                let time = row.map(|row| format_duration(row.time));
+                html += &format!("<td>{}</td>", time.unwrap_or_default());
+            }
+            html += &format!("<td>{}</td></tr>\n", format_duration(*total));
+        }
+        html += "<tr class=\"total\"><td>Total</td>";
+        for report in reports {
+            html += &format!("<td>{}</td>", format_duration(report.total));
+        }
+        let total = reports.iter().map(|report| report.total).sum();
+        html + &format!("<td>{}</td></tr>\n</table>\n", format_duration(total))
+    }
+}
+impl ReportFormatter for HtmlFormatter {
+    fn format(&self, reports: &[Report]) -> String {
+        let title = match (reports.first(), reports.last()) {
+            (Some(first), Some(last)) if first.date != last.to.unwrap_or(last.date) => {
+                format!("Report, {} to {}", first.date, last.to.unwrap_or(last.date))
+            }
+            (Some(first), _) => format!("Report, {}", first.date),
+            _ => "Report".to_string(),
+        };
+        let mut totals: Vec<(&str, Duration)> = vec![];
+        for row in reports.iter().flat_map(|report| &report.rows) {
+            match totals.iter_mut().find(|(name, _)| *name == row.name) {
+                Some((_, total)) => *total += row.time,
+                None => totals.push((&row.name, row.time)),
+            }
+        }
+        let mut names: Vec<&str> = totals.iter().map(|(name, _)| *name).collect();
+        for entry in reports.iter().flat_map(|report| &report.timeline) {
+            if !names.contains(&entry.task.as_str()) {
+                names.push(&entry.task);
+            }
+        }
+        let mut html = format!(
+            "<!DOCTYPE html>\n\
+             <html lang=\"en\">\n\
+             <head>\n\
+             <meta charset=\"utf-8\">\n\
+             <title>{title}</title>\n\
+             <style>\n{HTML_STYLE}</style>\n\
+             </head>\n\
+             <body>\n\
+             <h1>{title}</h1>\n\
+             <h2>Timeline</h2>\n"
+        );
+        for report in reports {
+            let mut dates: Vec<NaiveDate> =
+                report.timeline.iter().map(|entry| entry.date).collect();
+            dates.dedup();
+            if dates.is_empty() {
+                dates.push(report.date);
+            }
+            for date in dates {
+                let entries = report.timeline.iter().filter(|entry| entry.date == date);
+                html += &Self::timeline(date, &entries.collect::<Vec<_>>(), &names);
+            }
+        }
+        html += "<h2>Time per task</h2>\n";
+        html += &Self::bar_chart(&totals, &names);
+        html += "<h2>Totals</h2>\n";
+        html += &Self::totals_table(reports, &totals);
+        html + "</body>\n</html>\n"
+    }
+}
+
+/// The style sheet of the HTML page.
+const HTML_STYLE: &str = "\
+body { font-family: sans-serif; margin: 2em; color: #222; }
+h3 { font-size: 1em; margin-bottom: 0.3em; }
+svg { display: block; overflow: visible; }
+svg text { font-size: 11px; fill: #555; }
+svg.timeline line { stroke: #ddd; }
+svg.timeline text { text-anchor: middle; }
+svg.bars text.label { text-anchor: end; fill: #222; }
+table { border-collapse: collapse; }
+th, td { padding: 0.3em 0.8em; border-bottom: 1px solid #ddd; text-align: right; }
+th:first-child, td:first-child { text-align: left; }
+tr.total td { font-weight: bold; border-top: 2px solid #222; }
+";
+
+/// Escapes the characters of a text that have a meaning in HTML.
+fn escape_html(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+/// Returns the share of a time in a total, in percent.
+pub(crate) fn percent(time: Duration, total: Duration) -> f64 {
+    time.num_milliseconds() as f64 / total.num_milliseconds() as f64 * 100.0
+}
+
+/// Serializes a duration as a whole number of minutes, if there is one.
+fn optional_minutes<S: Serializer>(
+    duration: &Option<Duration>,
+    serializer: S,
+) -> Result<S::Ok, S::Error> {
+    match duration {
+        Some(duration) => minutes(duration, serializer),
+        None => serializer.serialize_none(),
+    }
+}
+
+/// Serializes a duration as a whole number of minutes.
+fn minutes<S: Serializer>(duration: &Duration, serializer: S) -> Result<S::Ok, S::Error> {
+    serializer.serialize_i64(duration.num_minutes())
+}
+
+/// Quotes a CSV field if needed.
+pub(crate) fn csv_field(field: &str) -> String {
+    if field.contains([',', '"', '\n']) {
+        format!("\"{}\"", field.replace('"', "\"\""))
+    } else {
+        field.to_string()
+    }
+}
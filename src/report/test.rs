@@ -0,0 +1,320 @@
+/*
+ * tasklog - A command-line task tracking tool.
+ *
+ * Copyright (C) 2024 Lucas M. de Jong Larrarte
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with this program. If not, see <https://www.gnu.org/licenses/>.
+ */
+use chrono::TimeZone;
+
+use super::*;
+
+fn budget(spent: i64, estimate: i64) -> Budget {
+    Budget {
+        spent: Duration::minutes(spent),
+        estimate: Duration::minutes(estimate),
+    }
+}
+
+fn row(name: &str, minutes: i64, percent: f64) -> ReportRow {
+    ReportRow {
+        name: name.to_string(),
+        time: Duration::minutes(minutes),
+        percent,
+        average: None,
+        running: false,
+        notes: vec![],
+        budget: None,
+    }
+}
+
+fn entry(task: &str, from: u32, to: u32, running: bool) -> TimelineEntry {
+    let time = |hour| Local.with_ymd_and_hms(2024, 7, 15, hour, 0, 0).unwrap();
+    TimelineEntry {
+        date: NaiveDate::from_ymd_opt(2024, 7, 15).unwrap(),
+        task: task.to_string(),
+        start: time(from),
+        end: time(to),
+        running,
+    }
+}
+
+fn reports() -> Vec<Report> {
+    let date = |day| NaiveDate::from_ymd_opt(2024, 7, day).unwrap();
+    vec![
+        Report {
+            date: date(15),
+            to: None,
+            rows: vec![
+                ReportRow {
+                    budget: Some(budget(150, 120)),
+                    notes: vec!["Ticket #42".to_string()],
+                    ..row("coding, tests", 90, 75.0)
+                },
+                ReportRow {
+                    running: true,
+                    ..row("review", 30, 25.0)
+                },
+            ],
+            total: Duration::minutes(120),
+            average: None,
+            timeline: vec![
+                entry("coding, tests", 9, 10, false),
+                entry("review", 10, 11, true),
+            ],
+        },
+        Report {
+            date: date(16),
+            to: None,
+            rows: vec![row("a|b", 15, 100.0)],
+            total: Duration::minutes(15),
+            average: None,
+            timeline: vec![],
+        },
+    ]
+}
+
+fn names(report: &Report) -> Vec<(&str, i64, f64, Option<i64>)> {
+    let rows = report.rows.iter().map(|row| {
+        let average = row.average.map(|average| average.num_minutes());
+        (
+            row.name.as_str(),
+            row.time.num_minutes(),
+            row.percent,
+            average,
+        )
+    });
+    rows.collect()
+}
+
+#[test]
+fn test_aggregate() {
+    let mut reports = reports();
+    let date = |day| NaiveDate::from_ymd_opt(2024, 7, day).unwrap();
+    reports[1].rows.push(row("review", 45, 75.0));
+    reports[1].total = Duration::minutes(60);
+    reports.push(Report {
+        date: date(17),
+        to: None,
+        rows: vec![],
+        total: Duration::zero(),
+        average: None,
+        timeline: vec![],
+    });
+    let report = Report::aggregate(date(15), date(17), &reports, Aggregation::Rows);
+    assert_eq!(report.period(), "2024-07-15 to 2024-07-17");
+    assert_eq!(
+        names(&report),
+        vec![
+            ("coding, tests", 90, 50.0, Some(45)),
+            ("review", 75, 41.66666666666667, Some(37)),
+            ("a|b", 15, 8.333333333333332, Some(7)),
+        ]
+    );
+    assert!(report.rows[1].running);
+    assert_eq!(report.rows[0].budget, Some(budget(150, 120)));
+    assert_eq!(report.rows[0].notes, vec!["Ticket #42"]);
+    assert_eq!(report.total, Duration::minutes(180));
+    assert_eq!(report.average, Some(Duration::minutes(90)));
+    assert_eq!(report.timeline.len(), 2);
+}
+
+#[test]
+fn test_aggregate_by_period() {
+    let reports = reports();
+    let date = |day| NaiveDate::from_ymd_opt(2024, 7, day).unwrap();
+    let report = Report::aggregate(date(15), date(16), &reports, Aggregation::Day);
+    assert_eq!(
+        names(&report),
+        vec![
+            ("2024-07-15", 120, 88.88888888888889, Some(120)),
+            ("2024-07-16", 15, 11.11111111111111, Some(15)),
+        ]
+    );
+    assert!(report.rows[0].running);
+    assert_eq!(report.rows[0].budget, None);
+    let report = Report::aggregate(date(15), date(16), &reports, Aggregation::Week);
+    assert_eq!(names(&report), vec![("2024-W29", 135, 100.0, Some(67))]);
+    let report = Report::aggregate(date(15), date(16), &reports, Aggregation::Month);
+    assert_eq!(names(&report), vec![("2024-07", 135, 100.0, Some(67))]);
+}
+
+#[test]
+fn test_aggregated_report_display() {
+    colored::control::set_override(false);
+    let date = |day| NaiveDate::from_ymd_opt(2024, 7, day).unwrap();
+    let report = Report::aggregate(date(15), date(16), &reports(), Aggregation::Day);
+    let expected = [
+        "  2024-07-15 to 2024-07-16 ",
+        "    2024-07-15 | 02:00 |  88.9% | 02:00/day",
+        "    2024-07-16 | 00:15 |  11.1% | 00:15/day",
+        "    =======================================",
+        "    Total      | 02:15 | 100.0% | 01:07/day",
+    ];
+    assert_eq!(report.to_string(), expected.join("\n") + "\n");
+    let markdown = MarkdownFormatter.format(&[report]);
+    assert!(markdown.starts_with("## 2024-07-15 to 2024-07-16\n"));
+    assert!(markdown.contains("| Task | Time | Share | Per day |\n| :--- | ---: | ---: | ---: |\n"));
+    assert!(markdown.contains("| **Total** | **02:15** | **100.0%** | **01:07** |\n"));
+}
+
+#[test]
+fn test_percent() {
+    let minutes = Duration::minutes;
+    assert_eq!(percent(minutes(0), minutes(10)), 0.0);
+    assert_eq!(percent(minutes(1), minutes(10)), 10.0);
+    assert_eq!(percent(minutes(10), minutes(10)), 100.0);
+}
+
+#[test]
+fn test_budget_display() {
+    assert_eq!(budget(90, 120).to_string(), "01:30 of 02:00, 00:30 left");
+    assert!(!budget(120, 120).is_over());
+    assert_eq!(budget(150, 120).to_string(), "02:30 of 02:00, 00:30 over");
+    assert!(budget(150, 120).is_over());
+}
+
+#[test]
+fn test_budget_display_with_seconds() {
+    let budget = Budget {
+        spent: Duration::seconds(60 * 60 + 59),
+        estimate: Duration::hours(3),
+    };
+    assert_eq!(budget.to_string(), "01:00 of 03:00, 02:00 left");
+    let budget = Budget {
+        spent: Duration::seconds(3 * 60 * 60 + 30),
+        estimate: Duration::hours(3),
+    };
+    assert!(!budget.is_over());
+    assert_eq!(budget.to_string(), "03:00 of 03:00, 00:00 left");
+}
+
+#[test]
+fn test_text_formatter() {
+    colored::control::set_override(false);
+    let expected = [
+        "",
+        "  2024-07-15 ",
+        "    coding, tests | 01:30 |  75.0% | 02:30 of 02:00, 00:30 over",
+        "      - Ticket #42",
+        "    review        | 00:30 |  25.0%",
+        "    ===========================================================",
+        "    Total         | 02:00 | 100.0%",
+        "",
+        "  2024-07-16 ",
+        "    a|b   | 00:15 | 100.0%",
+        "    ======================",
+        "    Total | 00:15 | 100.0%",
+        "",
+    ];
+    assert_eq!(TextFormatter.format(&reports()), expected.join("\n") + "\n");
+}
+
+#[test]
+fn test_json_formatter() {
+    let json: serde_json::Value = serde_json::from_str(&JsonFormatter.format(&reports())).unwrap();
+    assert_eq!(json[0]["date"], "2024-07-15");
+    assert_eq!(json[0]["total_minutes"], 120);
+    assert_eq!(
+        json[0]["rows"][0],
+        serde_json::json!({
+            "name": "coding, tests",
+            "minutes": 90,
+            "percent": 75.0,
+            "running": false,
+            "notes": ["Ticket #42"],
+            "budget": {"spent_minutes": 150, "estimate_minutes": 120},
+        })
+    );
+    assert_eq!(json[0]["rows"][1]["running"], true);
+    assert_eq!(json[1]["rows"][0].get("notes"), None);
+}
+
+#[test]
+fn test_csv_formatter() {
+    let expected = "\
+        kind,date,task,minutes,percent,running,estimate_minutes,average_minutes\n\
+        task,2024-07-15,\"coding, tests\",90,75.0,false,120,\n\
+        task,2024-07-15,review,30,25.0,true,,\n\
+        total,2024-07-15,,120,100.0,,,\n\
+        task,2024-07-16,a|b,15,100.0,false,,\n\
+        total,2024-07-16,,15,100.0,,,\n";
+    assert_eq!(DelimitedFormatter::csv().format(&reports()), expected);
+}
+
+#[test]
+fn test_tsv_formatter() {
+    let mut reports = reports();
+    reports[1].rows[0].name = "a\tb".to_string();
+    let tsv = DelimitedFormatter::tsv().format(&reports);
+    let lines: Vec<_> = tsv.lines().collect();
+    assert_eq!(lines.len(), 6);
+    assert_eq!(
+        lines[1],
+        "task\t2024-07-15\tcoding, tests\t90\t75.0\tfalse\t120\t"
+    );
+    assert_eq!(lines[4], "task\t2024-07-16\ta b\t15\t100.0\tfalse\t\t");
+}
+
+#[test]
+fn test_markdown_formatter() {
+    let expected = [
+        "## 2024-07-15",
+        "",
+        "| Task | Time | Share | Estimate |",
+        "| :--- | ---: | ---: | :--- |",
+        "| coding, tests | 01:30 | 75.0% | 02:30 of 02:00, 00:30 over |",
+        "| review (running) | 00:30 | 25.0% |  |",
+        "| **Total** | **02:00** | **100.0%** | |",
+        "",
+        "## 2024-07-16",
+        "",
+        "| Task | Time | Share |",
+        "| :--- | ---: | ---: |",
+        "| a\\|b | 00:15 | 100.0% |",
+        "| **Total** | **00:15** | **100.0%** |",
+    ];
+    assert_eq!(
+        MarkdownFormatter.format(&reports()),
+        expected.join("\n") + "\n"
+    );
+}
+
+#[test]
+fn test_html_formatter() {
+    let html = HtmlFormatter.format(&reports());
+    assert!(html.starts_with("<!DOCTYPE html>"));
+    assert!(html.contains("<title>Report, 2024-07-15 to 2024-07-16</title>"));
+    assert!(!html.contains("http"));
+    assert!(html.contains("<text x=\"0.0\" y=\"44\">09:00</text>"));
+    assert!(html.contains("<text x=\"960.0\" y=\"44\">11:00</text>"));
+    assert!(html.contains(
+        "<rect x=\"480.0\" y=\"4\" width=\"480.0\" height=\"24\" fill=\"#f28e2b\">\
+         <title>review 10:00-11:00 (running)</title></rect>"
+    ));
+    assert!(html.contains("No time entries."));
+    assert!(html.contains("<text class=\"label\" x=\"232.0\" y=\"62\">a|b</text>"));
+    assert!(html.contains("<tr><td>coding, tests</td><td>01:30</td><td></td><td>01:30</td></tr>"));
+    assert!(html.contains(
+        "<tr class=\"total\"><td>Total</td><td>02:00</td><td>00:15</td><td>02:15</td></tr>"
+    ));
+}
+
+#[test]
+fn test_escape_html() {
+    assert_eq!(
+        escape_html("<b>\"Q&A\"</b>"),
+        "&lt;b&gt;&quot;Q&amp;A&quot;&lt;/b&gt;"
+    );
+}
@@ -0,0 +1,129 @@
+/*
+ * tasklog - A command-line task tracking tool.
+ *
+ * Copyright (C) 2024 Lucas M. de Jong Larrarte
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with this program. If not, see <https://www.gnu.org/licenses/>.
+ */
+#[cfg(test)]
+mod test;
+
+use serde::de::Error;
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use serde_json::{json, Value};
+
+use crate::{TaskManager, TaskRecord};
+
+/// The schema version of the day files written by this version of tasklog.
+pub const CURRENT_VERSION: u32 = 2;
+
+/// Envelope around the tasks of a day, recording the schema they were written with.
+#[derive(Debug, Serialize)]
+struct Envelope<'a> {
+    schema_version: u32,
+    tasks: &'a TaskManager,
+}
+
+/// Upgrades a document from schema version `from` to version `from + 1`.
+struct Migration {
+    from: u32,
+    migrate: fn(Value) -> Value,
+}
+
+/// Registry of all migrations, in order. Each schema version except the current one must have
+/// exactly one migration.
+const MIGRATIONS: &[Migration] = &[Migration {
+    from: 1,
+    migrate: wrap_in_envelope,
+}];
+
+/// Version 1 files hold the tasks directly, without an envelope.
+fn wrap_in_envelope(tasks: Value) -> Value {
+    json!({ "tasks": tasks })
+}
+
+/// Serializes the tasks with the current schema.
+pub fn to_string(tasks: &TaskManager) -> String {
+    let envelope = Envelope {
+        schema_version: CURRENT_VERSION,
+        tasks,
+    };
+    serde_json::to_string(&envelope).expect("should be able to serialize tasks")
+}
+
+/// Serializes tasks embedded in another document with the current schema, like [to_string].
+/// Meant for fields annotated with `#[serde(with = "crate::schema")]`.
+pub fn serialize<S: Serializer>(tasks: &TaskManager, serializer: S) -> Result<S::Ok, S::Error> {
+    let envelope = Envelope {
+        schema_version: CURRENT_VERSION,
+        tasks,
+    };
+    envelope.serialize(serializer)
+}
+
+/// Deserializes tasks embedded in another document with any known schema, like [from_str].
+/// Meant for fields annotated with `#[serde(with = "crate::schema")]`.
+pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<TaskManager, D::Error> {
+    let document = Value::deserialize(deserializer)?;
+    let (tasks, _) = migrate(document).map_err(D::Error::custom)?;
+    serde_json::from_value(tasks).map_err(D::Error::custom)
+}
+
+/// Deserializes tasks written with any known schema, migrating them if needed.
+/// Returns the tasks and the schema version the data was written with.
+pub fn from_str(data: &str) -> serde_json::Result<(TaskManager, u32)> {
+    let (tasks, version) = upgrade(data)?;
+    Ok((serde_json::from_value(tasks)?, version))
+}
+
+/// Deserializes tasks written with any known schema as records, without checking their
+/// invariants.
+pub fn records_from_str(data: &str) -> serde_json::Result<Vec<TaskRecord>> {
+    let (tasks, _) = upgrade(data)?;
+    TaskRecord::unchecked_from_json(tasks)
+}
+
+/// Parses a document written with any known schema and migrates it to the current one.
+/// Returns the JSON form of the tasks and the schema version the data was written with.
+fn upgrade(data: &str) -> serde_json::Result<(Value, u32)> {
+    migrate(serde_json::from_str(data)?)
+}
+
+/// Migrates a parsed document written with any known schema to the current one.
+/// Returns the JSON form of the tasks and the schema version the data was written with.
+fn migrate(mut document: Value) -> serde_json::Result<(Value, u32)> {
+    let version = version_of(&document)?;
+    if version > CURRENT_VERSION {
+        return Err(serde_json::Error::custom(format!(
+            "schema version {version} is newer than the supported version {CURRENT_VERSION}"
+        )));
+    }
+    for migration in MIGRATIONS.iter().filter(|m| m.from >= version) {
+        document = (migration.migrate)(document);
+        document["schema_version"] = json!(migration.from + 1);
+    }
+    Ok((document["tasks"].take(), version))
+}
+
+/// Returns the schema version of the document. Documents without one are version 1.
+fn version_of(document: &Value) -> serde_json::Result<u32> {
+    match document.get("schema_version") {
+        None => Ok(1),
+        Some(version) => version
+            .as_u64()
+            .and_then(|version| u32::try_from(version).ok())
+            .filter(|version| *version >= 1)
+            .ok_or_else(|| serde_json::Error::custom("schema_version must be a positive integer")),
+    }
+}
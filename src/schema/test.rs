@@ -0,0 +1,130 @@
+/*
+ * tasklog - A command-line task tracking tool.
+ *
+ * Copyright (C) 2024 Lucas M. de Jong Larrarte
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with this program. If not, see <https://www.gnu.org/licenses/>.
+ */
+use super::*;
+use chrono::{Duration, Local};
+use serde_json::error::Category;
+
+#[test]
+fn test_migrations_are_contiguous() {
+    for (i, migration) in MIGRATIONS.iter().enumerate() {
+        assert_eq!(migration.from, i as u32 + 1);
+    }
+    assert_eq!(MIGRATIONS.len() as u32 + 1, CURRENT_VERSION);
+}
+
+#[test]
+fn test_to_string_writes_current_version() {
+    let data = to_string(&TaskManager::new());
+    let document: Value = serde_json::from_str(&data).unwrap();
+    assert_eq!(document["schema_version"], json!(CURRENT_VERSION));
+    assert_eq!(document["tasks"], json!({ "stopped": [], "running": null }));
+}
+
+#[test]
+fn test_from_str_round_trip() {
+    let now = Local::now();
+    let mut tasks = TaskManager::new();
+    tasks.start_new_task("Test".to_string(), now).unwrap();
+    tasks
+        .stop_running_task_with_time(now + Duration::minutes(5))
+        .unwrap();
+    let data = to_string(&tasks);
+    let (read, version) = from_str(&data).unwrap();
+    assert_eq!(version, CURRENT_VERSION);
+    assert_eq!(read.list_tasks(), vec!["Test"]);
+    assert_eq!(to_string(&read), data);
+}
+
+#[test]
+fn test_from_str_migrates_version_1() {
+    let data = r#"{"stopped":[],"running":{"name":"Test","entries":[],"last_entry":{"start":"2024-07-16T09:00:00+02:00"}}}"#;
+    let (read, version) = from_str(data).unwrap();
+    assert_eq!(version, 1);
+    assert_eq!(read.running_task(), Some("Test"));
+}
+
+#[test]
+fn test_from_str_when_newer_version() {
+    let data = r#"{"schema_version":999,"tasks":{"stopped":[],"running":null}}"#;
+    let error = from_str(data).unwrap_err();
+    assert!(error.to_string().contains("999"));
+}
+
+#[test]
+fn test_from_str_when_invalid_version() {
+    let data = r#"{"schema_version":"two","tasks":{"stopped":[],"running":null}}"#;
+    assert!(from_str(data).is_err());
+}
+
+#[test]
+fn test_from_str_when_version_0() {
+    let data = r#"{"schema_version":0,"tasks":{"stopped":[],"running":null}}"#;
+    let error = from_str(data).unwrap_err();
+    assert!(error.to_string().contains("schema_version"));
+}
+
+#[test]
+fn test_from_str_when_truncated() {
+    let data = to_string(&TaskManager::new());
+    let error = from_str(&data[..data.len() / 2]).unwrap_err();
+    assert_eq!(error.classify(), Category::Eof);
+}
+
+#[test]
+fn test_records_from_str_when_invariants_are_broken() {
+    let data = r#"{"schema_version":2,"tasks":{"stopped":[{"name":"Test","entries":[{"start":"2024-07-16T10:00:00+02:00","end":"2024-07-16T09:00:00+02:00"}],"last_entry":{"start":"2024-07-16T08:00:00+02:00","end":"2024-07-16T08:30:00+02:00"}}],"running":null}}"#;
+    assert!(from_str(data).is_err());
+    let records = records_from_str(data).unwrap();
+    assert_eq!(records.len(), 1);
+    assert_eq!(records[0].name, "Test");
+    assert_eq!(records[0].entries.len(), 2);
+    assert!(records[0].entries[0].0 > records[0].entries[0].1);
+    assert_eq!(records[0].running_since, None);
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct Embedded {
+    #[serde(with = "crate::schema")]
+    tasks: TaskManager,
+}
+
+#[test]
+fn test_serialize_embedded_writes_current_version() {
+    let embedded = Embedded {
+        tasks: TaskManager::new(),
+    };
+    let document = serde_json::to_value(&embedded).unwrap();
+    assert_eq!(document["tasks"]["schema_version"], json!(CURRENT_VERSION));
+    let read: Embedded = serde_json::from_value(document).unwrap();
+    assert_eq!(read.tasks, TaskManager::new());
+}
+
+#[test]
+fn test_deserialize_embedded_migrates_version_1() {
+    let data = r#"{"tasks":{"stopped":[],"running":{"name":"Test","entries":[],"last_entry":{"start":"2024-07-16T09:00:00+02:00"}}}}"#;
+    let read: Embedded = serde_json::from_str(data).unwrap();
+    assert_eq!(read.tasks.running_task(), Some("Test"));
+}
+
+#[test]
+fn test_deserialize_embedded_when_newer_version() {
+    let data = r#"{"tasks":{"schema_version":999,"tasks":{"stopped":[],"running":null}}}"#;
+    let error = serde_json::from_str::<Embedded>(data).unwrap_err();
+    assert!(error.to_string().contains("999"));
+}
@@ -0,0 +1,157 @@
+/*
+ * tasklog - A command-line task tracking tool.
+ *
+ * Copyright (C) 2024 Lucas M. de Jong Larrarte
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with this program. If not, see <https://www.gnu.org/licenses/>.
+ */
+#[cfg(test)]
+mod test;
+
+mod json;
+mod memory;
+#[cfg(feature = "sqlite")]
+mod sqlite;
+
+use std::fs::{File, TryLockError};
+use std::io;
+use std::path::{Path, PathBuf};
+use std::rc::{Rc, Weak};
+use std::thread;
+use std::time::{Duration, Instant};
+
+use chrono::NaiveDate;
+
+use crate::journal::JournalEntry;
+pub(crate) use crate::storage::json::write_atomically;
+pub use crate::storage::json::JsonDirStorage;
+pub use crate::storage::memory::MemoryStorage;
+#[cfg(feature = "sqlite")]
+pub use crate::storage::sqlite::SqliteStorage;
+use crate::{TaskError, TaskManager, TaskRecord, TaskResult};
+
+/// Persistence of the tasks logged on each day.
+pub trait Storage {
+    /// Loads the tasks of the given date. Dates without any data yield an empty [TaskManager].
+    fn load(&self, date: NaiveDate) -> TaskResult<TaskManager>;
+
+    /// Loads the tasks of the given date as records, without checking their invariants, so that
+    /// damaged data can be inspected.
+    fn load_records(&self, date: NaiveDate) -> TaskResult<Vec<TaskRecord>> {
+        Ok(self.load(date)?.to_records())
+    }
+
+    /// Saves the tasks of the given date, replacing any previous data.
+    fn save(&self, tasks: &TaskManager, date: NaiveDate) -> TaskResult<()>;
+
+    /// Lists the dates that hold data, in chronological order.
+    fn dates(&self) -> TaskResult<Vec<NaiveDate>>;
+
+    /// Deletes the data of the given date, if any.
+    fn delete(&self, date: NaiveDate) -> TaskResult<()>;
+
+    /// Removes the data of a date that cannot be loaded, so that it can be replaced. Stores that
+    /// can keep a copy of the data do so, and return where it was kept.
+    fn set_aside(&self, date: NaiveDate) -> TaskResult<Option<PathBuf>> {
+        self.delete(date)?;
+        Ok(None)
+    }
+
+    /// Appends entries to the journal of the given date.
+    fn append_journal(&self, date: NaiveDate, entries: &[JournalEntry]) -> TaskResult<()>;
+
+    /// Reads the journal of the given date, oldest entry first.
+    fn journal(&self, date: NaiveDate) -> TaskResult<Vec<JournalEntry>>;
+
+    /// Lists the dates that have a journal, in chronological order.
+    fn journal_dates(&self) -> TaskResult<Vec<NaiveDate>>;
+
+    /// Loads a named document holding data that isn't tied to a single day, if it exists.
+    fn load_document(&self, name: &str) -> TaskResult<Option<String>>;
+
+    /// Saves a named document, replacing any previous version.
+    fn save_document(&self, name: &str, data: &str) -> TaskResult<()>;
+
+    /// Acquires exclusive access to the store, which lasts until the returned lock is dropped.
+    fn lock(&self) -> TaskResult<StorageLock>;
+
+    /// Upgrades all data to the current schema, or only checks what would be upgraded if
+    /// `dry_run` is set. Returns the upgraded dates with the schema version they had.
+    fn migrate(&self, _dry_run: bool) -> TaskResult<Vec<(NaiveDate, u32)>> {
+        Ok(vec![])
+    }
+}
+
+/// Exclusive access to a [Storage], released on drop.
+#[derive(Debug)]
+pub struct StorageLock {
+    file: Option<Rc<File>>,
+}
+impl StorageLock {
+    /// Creates a lock held through the given locked file.
+    fn file(file: File) -> Self {
+        Self {
+            file: Some(Rc::new(file)),
+        }
+    }
+
+    /// Creates a lock for stores that need no locking.
+    fn none() -> Self {
+        Self { file: None }
+    }
+
+    /// Returns a handle telling whether this lock is still held, which doesn't keep it held.
+    fn watch(&self) -> LockWatch {
+        LockWatch(self.file.as_ref().map(Rc::downgrade).unwrap_or_default())
+    }
+}
+
+/// Tells whether a [StorageLock] is still held.
+#[derive(Debug, Default)]
+struct LockWatch(Weak<File>);
+impl LockWatch {
+    /// Checks if the lock is still held.
+    fn is_held(&self) -> bool {
+        self.0.strong_count() > 0
+    }
+}
+
+/// Locks the given lock file, creating it if needed and waiting at most `timeout` for other
+/// processes to release it.
+fn lock_file(path: &Path, timeout: Duration) -> TaskResult<StorageLock> {
+    let file = File::options()
+        .create(true)
+        .truncate(false)
+        .write(true)
+        .open(path)
+        .map_err(file_io(path))?;
+    let deadline = Instant::now() + timeout;
+    loop {
+        match file.try_lock() {
+            Ok(()) => return Ok(StorageLock::file(file)),
+            Err(TryLockError::WouldBlock) if Instant::now() < deadline => {
+                thread::sleep(Duration::from_millis(50))
+            }
+            Err(TryLockError::WouldBlock) => {
+                return Err(TaskError::LockTimeout(path.to_path_buf()))
+            }
+            Err(TryLockError::Error(e)) => return Err(TaskError::FileIO(path.to_path_buf(), e)),
+        }
+    }
+}
+
+/// Returns a function that wraps an IO error with the path it occurred on.
+pub(crate) fn file_io(path: &Path) -> impl FnOnce(io::Error) -> TaskError + '_ {
+    move |e| TaskError::FileIO(path.to_path_buf(), e)
+}
@@ -0,0 +1,345 @@
+/*
+ * tasklog - A command-line task tracking tool.
+ *
+ * Copyright (C) 2024 Lucas M. de Jong Larrarte
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with this program. If not, see <https://www.gnu.org/licenses/>.
+ */
+use std::cell::RefCell;
+use std::fs;
+use std::fs::File;
+use std::io::{ErrorKind, Read, Seek, SeekFrom, Write};
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+use chrono::{Local, NaiveDate};
+use serde_json::error::Category;
+
+use crate::journal::JournalEntry;
+use crate::schema;
+use crate::storage::{file_io, lock_file, LockWatch, Storage, StorageLock};
+use crate::{TaskError, TaskManager, TaskRecord, TaskResult};
+
+/// Storage keeping one JSON file per day, named `YYYY-MM-DD.json`, in a data directory.
+#[derive(Debug)]
+pub struct JsonDirStorage {
+    data_dir: PathBuf,
+    lock_timeout: Duration,
+    /// The lock last taken through this store, to tell whether it is still held.
+    lock: RefCell<LockWatch>,
+}
+impl JsonDirStorage {
+    /// Creates a store in the given directory, creating the directory if needed.
+    /// Locking the store waits at most `lock_timeout` for other processes to release it.
+    pub fn new(data_dir: impl Into<PathBuf>, lock_timeout: Duration) -> TaskResult<Self> {
+        let data_dir = data_dir.into();
+        fs::create_dir_all(&data_dir).map_err(file_io(&data_dir))?;
+        Ok(Self {
+            data_dir,
+            lock_timeout,
+            lock: RefCell::default(),
+        })
+    }
+
+    /// Gets the file path for the given date.
+    fn get_file(&self, date: NaiveDate) -> PathBuf {
+        self.data_dir.join(date.format("%F.json").to_string())
+    }
+
+    /// Gets the directory holding the journals.
+    fn journal_dir(&self) -> PathBuf {
+        self.data_dir.join("journal")
+    }
+
+    /// Gets the journal file path for the given date.
+    fn get_journal_file(&self, date: NaiveDate) -> PathBuf {
+        self.journal_dir().join(date.format("%F.jsonl").to_string())
+    }
+}
+
+impl Storage for JsonDirStorage {
+    /// Reads the tasks from the file for the given date.
+    ///
+    /// A missing file means no tasks have been logged on that date yet. Any other failure to
+    /// read the file is reported, rather than risking that it gets overwritten with an empty day.
+    ///
+    /// A file that ends abruptly was left behind by an interrupted write. While the store is
+    /// locked, it is moved aside so that the next command can start afresh, and
+    /// [TaskError::TruncatedFile] is returned. Otherwise it is left for a command holding the
+    /// lock to deal with.
+    fn load(&self, date: NaiveDate) -> TaskResult<TaskManager> {
+        let file = self.get_file(date);
+        let task_manager = match fs::read_to_string(&file) {
+            Ok(data) => match schema::from_str(&data) {
+                Ok((task_manager, _)) => task_manager,
+                Err(e) if e.classify() == Category::Eof && self.lock.borrow().is_held() => {
+                    let moved_to = quarantine(&file)?;
+                    return Err(TaskError::TruncatedFile(file, moved_to));
+                }
+                Err(e) => return Err(TaskError::InvalidFile(file, e)),
+            },
+            Err(e) if e.kind() == ErrorKind::NotFound => TaskManager::default(),
+            Err(e) => return Err(TaskError::FileIO(file, e)),
+        };
+        Ok(task_manager)
+    }
+
+    /// Reads the tasks from the file for the given date without checking their invariants.
+    fn load_records(&self, date: NaiveDate) -> TaskResult<Vec<TaskRecord>> {
+        let file = self.get_file(date);
+        match fs::read_to_string(&file) {
+            Ok(data) => {
+                schema::records_from_str(&data).map_err(|e| TaskError::InvalidFile(file, e))
+            }
+            Err(e) if e.kind() == ErrorKind::NotFound => Ok(vec![]),
+            Err(e) => Err(TaskError::FileIO(file, e)),
+        }
+    }
+
+    /// Writes the tasks to the file for the given date.
+    fn save(&self, tasks: &TaskManager, date: NaiveDate) -> TaskResult<()> {
+        let file = self.get_file(date);
+        check_overwritable(&file)?;
+        write_atomically(&file, schema::to_string(tasks).as_bytes())
+    }
+
+    fn dates(&self) -> TaskResult<Vec<NaiveDate>> {
+        let mut dates = vec![];
+        for entry in fs::read_dir(&self.data_dir).map_err(file_io(&self.data_dir))? {
+            let entry = entry.map_err(file_io(&self.data_dir))?;
+            let name = entry.file_name();
+            let date = name
+                .to_str()
+                .and_then(|name| NaiveDate::parse_from_str(name, "%F.json").ok());
+            if let Some(date) = date {
+                dates.push(date);
+            }
+        }
+        dates.sort();
+        Ok(dates)
+    }
+
+    fn delete(&self, date: NaiveDate) -> TaskResult<()> {
+        let file = self.get_file(date);
+        match fs::remove_file(&file) {
+            Ok(()) => sync_parent_dir(&file),
+            Err(e) if e.kind() == ErrorKind::NotFound => Ok(()),
+            Err(e) => Err(TaskError::FileIO(file, e)),
+        }
+    }
+
+    /// Moves the file for the given date aside, like a truncated file.
+    fn set_aside(&self, date: NaiveDate) -> TaskResult<Option<PathBuf>> {
+        let file = self.get_file(date);
+        if !file.exists() {
+            return Ok(None);
+        }
+        Ok(Some(quarantine(&file)?))
+    }
+
+    /// Appends the entries to the journal file of the given date, one JSON object per line.
+    fn append_journal(&self, date: NaiveDate, entries: &[JournalEntry]) -> TaskResult<()> {
+        if entries.is_empty() {
+            return Ok(());
+        }
+        let journal_dir = self.journal_dir();
+        fs::create_dir_all(&journal_dir).map_err(file_io(&journal_dir))?;
+        let file = self.get_journal_file(date);
+        let mut data = String::new();
+        if ends_with_partial_line(&file)? {
+            data.push('\n');
+        }
+        for entry in entries {
+            data += &serde_json::to_string(entry).expect("should be able to serialize events");
+            data.push('\n');
+        }
+        File::options()
+            .create(true)
+            .append(true)
+            .open(&file)
+            .and_then(|mut journal| {
+                journal.write_all(data.as_bytes())?;
+                journal.sync_all()
+            })
+            .map_err(file_io(&file))?;
+        sync_parent_dir(&file)
+    }
+
+    /// Reads the journal file of the given date.
+    ///
+    /// Lines that end abruptly were left behind by an interrupted append, whose mutation was
+    /// never saved, so they are skipped.
+    fn journal(&self, date: NaiveDate) -> TaskResult<Vec<JournalEntry>> {
+        let file = self.get_journal_file(date);
+        let data = match fs::read_to_string(&file) {
+            Ok(data) => data,
+            Err(e) if e.kind() == ErrorKind::NotFound => return Ok(vec![]),
+            Err(e) => return Err(TaskError::FileIO(file, e)),
+        };
+        let mut entries = vec![];
+        for line in data.lines().filter(|line| !line.is_empty()) {
+            match serde_json::from_str(line) {
+                Ok(entry) => entries.push(entry),
+                Err(e) if e.classify() == Category::Eof => continue,
+                Err(e) => return Err(TaskError::InvalidFile(file, e)),
+            }
+        }
+        Ok(entries)
+    }
+
+    fn journal_dates(&self) -> TaskResult<Vec<NaiveDate>> {
+        let journal_dir = self.journal_dir();
+        let entries = match fs::read_dir(&journal_dir) {
+            Ok(entries) => entries,
+            Err(e) if e.kind() == ErrorKind::NotFound => return Ok(vec![]),
+            Err(e) => return Err(TaskError::FileIO(journal_dir, e)),
+        };
+        let mut dates = vec![];
+        for entry in entries {
+            let entry = entry.map_err(file_io(&journal_dir))?;
+            let name = entry.file_name();
+            let date = name
+                .to_str()
+                .and_then(|name| NaiveDate::parse_from_str(name, "%F.jsonl").ok());
+            if let Some(date) = date {
+                dates.push(date);
+            }
+        }
+        dates.sort();
+        Ok(dates)
+    }
+
+    /// Reads the document from `name.json` in the data directory.
+    fn load_document(&self, name: &str) -> TaskResult<Option<String>> {
+        let file = self.data_dir.join(format!("{name}.json"));
+        match fs::read_to_string(&file) {
+            Ok(data) => Ok(Some(data)),
+            Err(e) if e.kind() == ErrorKind::NotFound => Ok(None),
+            Err(e) => Err(TaskError::FileIO(file, e)),
+        }
+    }
+
+    /// Writes the document to `name.json` in the data directory.
+    fn save_document(&self, name: &str, data: &str) -> TaskResult<()> {
+        let file = self.data_dir.join(format!("{name}.json"));
+        write_atomically(&file, data.as_bytes())
+    }
+
+    /// Locks the data directory, waiting at most the configured timeout.
+    fn lock(&self) -> TaskResult<StorageLock> {
+        let lock = lock_file(&self.data_dir.join(".lock"), self.lock_timeout)?;
+        *self.lock.borrow_mut() = lock.watch();
+        Ok(lock)
+    }
+
+    /// Rewrites every day file that uses an older schema with the current one.
+    fn migrate(&self, dry_run: bool) -> TaskResult<Vec<(NaiveDate, u32)>> {
+        let mut migrated = vec![];
+        for date in self.dates()? {
+            let file = self.get_file(date);
+            let data = fs::read_to_string(&file).map_err(file_io(&file))?;
+            let (tasks, version) =
+                schema::from_str(&data).map_err(|e| TaskError::InvalidFile(file.clone(), e))?;
+            if version == schema::CURRENT_VERSION {
+                continue;
+            }
+            if !dry_run {
+                write_atomically(&file, schema::to_string(&tasks).as_bytes())?;
+            }
+            migrated.push((date, version));
+        }
+        Ok(migrated)
+    }
+}
+
+/// Moves a damaged file out of the way, returning its new path.
+fn quarantine(file: &Path) -> TaskResult<PathBuf> {
+    let mut moved_to = file.as_os_str().to_owned();
+    moved_to.push(Local::now().format(".%Y%m%d%H%M%S.corrupt").to_string());
+    let moved_to = PathBuf::from(moved_to);
+    fs::rename(file, &moved_to).map_err(file_io(file))?;
+    Ok(moved_to)
+}
+
+/// Checks whether `file` exists and its last line lacks a line break, which happens when an
+/// append was interrupted.
+fn ends_with_partial_line(file: &Path) -> TaskResult<bool> {
+    let mut journal = match File::open(file) {
+        Ok(journal) => journal,
+        Err(e) if e.kind() == ErrorKind::NotFound => return Ok(false),
+        Err(e) => return Err(TaskError::FileIO(file.to_path_buf(), e)),
+    };
+    let mut last = [0];
+    let len = journal.metadata().map_err(file_io(file))?.len();
+    if len == 0 {
+        return Ok(false);
+    }
+    journal
+        .seek(SeekFrom::End(-1))
+        .and_then(|_| journal.read_exact(&mut last))
+        .map_err(file_io(file))?;
+    Ok(last[0] != b'\n')
+}
+
+/// Checks that `file` either doesn't exist or holds valid tasks, so that data that could not
+/// be read is never replaced.
+fn check_overwritable(file: &Path) -> TaskResult<()> {
+    match fs::read_to_string(file) {
+        Ok(data) => match schema::from_str(&data) {
+            Ok(_) => Ok(()),
+            Err(e) => Err(TaskError::RefusingToOverwrite(file.to_path_buf(), e)),
+        },
+        Err(e) if e.kind() == ErrorKind::NotFound => Ok(()),
+        Err(e) => Err(TaskError::FileIO(file.to_path_buf(), e)),
+    }
+}
+
+/// Replaces the contents of `file` with `data` so that, even on a crash, the file holds either
+/// its old or its new contents.
+///
+/// The data is written and synced to a temporary file next to `file`, which is then renamed
+/// over it. The parent directory is synced afterwards so the rename itself is durable.
+pub(crate) fn write_atomically(file: &Path, data: &[u8]) -> TaskResult<()> {
+    let mut tmp_file = file.as_os_str().to_owned();
+    tmp_file.push(".tmp");
+    let tmp_file = PathBuf::from(tmp_file);
+    let result = File::create(&tmp_file)
+        .and_then(|mut tmp| {
+            tmp.write_all(data)?;
+            tmp.sync_all()
+        })
+        .and_then(|_| fs::rename(&tmp_file, file));
+    if let Err(e) = result {
+        let _ = fs::remove_file(&tmp_file);
+        return Err(TaskError::FileIO(file.to_path_buf(), e));
+    }
+    sync_parent_dir(file)
+}
+
+/// Syncs the directory containing `file`, persisting any renames within it.
+#[cfg(unix)]
+fn sync_parent_dir(file: &Path) -> TaskResult<()> {
+    if let Some(parent) = file.parent() {
+        File::open(parent)
+            .and_then(|dir| dir.sync_all())
+            .map_err(file_io(parent))?;
+    }
+    Ok(())
+}
+
+/// Syncs the directory containing `file`. Directories cannot be synced on this platform.
+#[cfg(not(unix))]
+fn sync_parent_dir(_file: &Path) -> TaskResult<()> {
+    Ok(())
+}
@@ -0,0 +1,93 @@
+/*
+ * tasklog - A command-line task tracking tool.
+ *
+ * Copyright (C) 2024 Lucas M. de Jong Larrarte
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with this program. If not, see <https://www.gnu.org/licenses/>.
+ */
+use std::cell::RefCell;
+use std::collections::BTreeMap;
+
+use chrono::NaiveDate;
+
+use crate::journal::JournalEntry;
+use crate::storage::{Storage, StorageLock};
+use crate::{TaskManager, TaskResult};
+
+/// Storage that keeps all data in memory, for tests and embedding.
+#[derive(Debug, Default)]
+pub struct MemoryStorage {
+    days: RefCell<BTreeMap<NaiveDate, TaskManager>>,
+    journals: RefCell<BTreeMap<NaiveDate, Vec<JournalEntry>>>,
+    documents: RefCell<BTreeMap<String, String>>,
+}
+impl MemoryStorage {
+    /// Creates an empty store.
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+impl Storage for MemoryStorage {
+    fn load(&self, date: NaiveDate) -> TaskResult<TaskManager> {
+        Ok(self.days.borrow().get(&date).cloned().unwrap_or_default())
+    }
+
+    fn save(&self, tasks: &TaskManager, date: NaiveDate) -> TaskResult<()> {
+        self.days.borrow_mut().insert(date, tasks.clone());
+        Ok(())
+    }
+
+    fn dates(&self) -> TaskResult<Vec<NaiveDate>> {
+        Ok(self.days.borrow().keys().copied().collect())
+    }
+
+    fn delete(&self, date: NaiveDate) -> TaskResult<()> {
+        self.days.borrow_mut().remove(&date);
+        Ok(())
+    }
+
+    fn append_journal(&self, date: NaiveDate, entries: &[JournalEntry]) -> TaskResult<()> {
+        let mut journals = self.journals.borrow_mut();
+        journals.entry(date).or_default().extend_from_slice(entries);
+        Ok(())
+    }
+
+    fn journal(&self, date: NaiveDate) -> TaskResult<Vec<JournalEntry>> {
+        Ok(self
+            .journals
+            .borrow()
+            .get(&date)
+            .cloned()
+            .unwrap_or_default())
+    }
+
+    fn journal_dates(&self) -> TaskResult<Vec<NaiveDate>> {
+        Ok(self.journals.borrow().keys().copied().collect())
+    }
+
+    fn load_document(&self, name: &str) -> TaskResult<Option<String>> {
+        Ok(self.documents.borrow().get(name).cloned())
+    }
+
+    fn save_document(&self, name: &str, data: &str) -> TaskResult<()> {
+        let mut documents = self.documents.borrow_mut();
+        documents.insert(name.to_string(), data.to_string());
+        Ok(())
+    }
+
+    fn lock(&self) -> TaskResult<StorageLock> {
+        Ok(StorageLock::none())
+    }
+}
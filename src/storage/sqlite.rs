@@ -0,0 +1,313 @@
+/*
+ * tasklog - A command-line task tracking tool.
+ *
+ * Copyright (C) 2024 Lucas M. de Jong Larrarte
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with this program. If not, see <https://www.gnu.org/licenses/>.
+ */
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+use chrono::{DateTime, Local, NaiveDate};
+use rusqlite::{params, Connection, OptionalExtension};
+
+use crate::journal::JournalEntry;
+use crate::storage::{lock_file, Storage, StorageLock};
+use crate::{TaskError, TaskManager, TaskRecord, TaskResult};
+
+/// Database schema. Timestamps are stored as RFC 3339 strings and dates as `YYYY-MM-DD`.
+/// Notes on a time entry store its position in `entry`, and notes on a task leave it null.
+const SCHEMA: &str = "
+    CREATE TABLE IF NOT EXISTS days (
+        date TEXT PRIMARY KEY
+    );
+    CREATE TABLE IF NOT EXISTS tasks (
+        id INTEGER PRIMARY KEY,
+        date TEXT NOT NULL REFERENCES days (date) ON DELETE CASCADE,
+        position INTEGER NOT NULL,
+        name TEXT NOT NULL,
+        running_since TEXT
+    );
+    CREATE TABLE IF NOT EXISTS time_entries (
+        task_id INTEGER NOT NULL REFERENCES tasks (id) ON DELETE CASCADE,
+        position INTEGER NOT NULL,
+        start TEXT NOT NULL,
+        end TEXT NOT NULL
+    );
+    CREATE TABLE IF NOT EXISTS task_tags (
+        task_id INTEGER NOT NULL REFERENCES tasks (id) ON DELETE CASCADE,
+        tag TEXT NOT NULL
+    );
+    CREATE TABLE IF NOT EXISTS notes (
+        task_id INTEGER NOT NULL REFERENCES tasks (id) ON DELETE CASCADE,
+        entry INTEGER,
+        note TEXT NOT NULL
+    );
+    CREATE TABLE IF NOT EXISTS journal (
+        id INTEGER PRIMARY KEY,
+        date TEXT NOT NULL,
+        entry TEXT NOT NULL
+    );
+    CREATE TABLE IF NOT EXISTS documents (
+        name TEXT PRIMARY KEY,
+        data TEXT NOT NULL
+    );
+    CREATE INDEX IF NOT EXISTS tasks_date ON tasks (date);
+    CREATE INDEX IF NOT EXISTS time_entries_task_id ON time_entries (task_id);
+    CREATE INDEX IF NOT EXISTS task_tags_task_id ON task_tags (task_id);
+    CREATE INDEX IF NOT EXISTS notes_task_id ON notes (task_id);
+    CREATE INDEX IF NOT EXISTS journal_date ON journal (date);
+";
+
+/// Storage keeping all days in a single SQLite database file.
+#[derive(Debug)]
+pub struct SqliteStorage {
+    connection: Connection,
+    lock_path: PathBuf,
+    lock_timeout: Duration,
+}
+impl SqliteStorage {
+    /// Opens the database at the given path, creating it if needed.
+    /// Locking the store waits at most `lock_timeout` for other processes to release it.
+    pub fn open(path: impl AsRef<Path>, lock_timeout: Duration) -> TaskResult<Self> {
+        let path = path.as_ref();
+        let connection = Connection::open(path)?;
+        connection.pragma_update(None, "foreign_keys", true)?;
+        connection.busy_timeout(lock_timeout)?;
+        connection.execute_batch(SCHEMA)?;
+        let mut lock_path = path.as_os_str().to_owned();
+        lock_path.push(".lock");
+        Ok(Self {
+            connection,
+            lock_path: PathBuf::from(lock_path),
+            lock_timeout,
+        })
+    }
+
+    /// Reads the records of the tasks of the given date, in order.
+    fn records(&self, date: NaiveDate) -> TaskResult<Vec<TaskRecord>> {
+        let mut tasks = self.connection.prepare_cached(
+            "SELECT id, name, running_since FROM tasks WHERE date = ?1 ORDER BY position",
+        )?;
+        let mut entries = self.connection.prepare_cached(
+            "SELECT start, end FROM time_entries WHERE task_id = ?1 ORDER BY position",
+        )?;
+        let mut tags = self
+            .connection
+            .prepare_cached("SELECT tag FROM task_tags WHERE task_id = ?1 ORDER BY tag")?;
+        let mut notes = self
+            .connection
+            .prepare_cached("SELECT entry, note FROM notes WHERE task_id = ?1 ORDER BY rowid")?;
+        let tasks = tasks
+            .query_map(params![date.to_string()], |row| {
+                Ok((
+                    row.get::<_, i64>(0)?,
+                    row.get::<_, String>(1)?,
+                    row.get::<_, Option<String>>(2)?,
+                ))
+            })?
+            .collect::<Result<Vec<_>, _>>()?;
+        let mut records = vec![];
+        for (id, name, running_since) in tasks {
+            let entries = entries
+                .query_map(params![id], |row| {
+                    Ok((
+                        parse_time(row.get_ref(0)?.as_str()?)?,
+                        parse_time(row.get_ref(1)?.as_str()?)?,
+                    ))
+                })?
+                .collect::<Result<Vec<_>, _>>()?;
+            let tags = tags
+                .query_map(params![id], |row| row.get(0))?
+                .collect::<Result<Vec<_>, _>>()?;
+            let mut task_notes = vec![];
+            let mut entry_notes = vec![None; entries.len()];
+            let rows = notes.query_map(params![id], |row| {
+                Ok((row.get::<_, Option<usize>>(0)?, row.get::<_, String>(1)?))
+            })?;
+            for row in rows {
+                match row? {
+                    (None, note) => task_notes.push(note),
+                    (Some(entry), note) => {
+                        if let Some(slot) = entry_notes.get_mut(entry) {
+                            *slot = Some(note);
+                        }
+                    }
+                }
+            }
+            let running_since = running_since.as_deref().map(parse_time).transpose()?;
+            records.push(TaskRecord {
+                name,
+                tags,
+                notes: task_notes,
+                entries,
+                entry_notes,
+                running_since,
+            });
+        }
+        Ok(records)
+    }
+}
+
+impl Storage for SqliteStorage {
+    fn load(&self, date: NaiveDate) -> TaskResult<TaskManager> {
+        let records = self.records(date)?;
+        TaskManager::from_records(records).map_err(|e| TaskError::InvalidData(date, e))
+    }
+
+    fn load_records(&self, date: NaiveDate) -> TaskResult<Vec<TaskRecord>> {
+        self.records(date)
+    }
+
+    fn save(&self, tasks: &TaskManager, date: NaiveDate) -> TaskResult<()> {
+        let date = date.to_string();
+        let transaction = self.connection.unchecked_transaction()?;
+        transaction.execute("DELETE FROM days WHERE date = ?1", params![date])?;
+        transaction.execute("INSERT INTO days (date) VALUES (?1)", params![date])?;
+        for (position, record) in tasks.to_records().into_iter().enumerate() {
+            transaction.execute(
+                "INSERT INTO tasks (date, position, name, running_since) VALUES (?1, ?2, ?3, ?4)",
+                params![
+                    date,
+                    position,
+                    record.name,
+                    record.running_since.map(|time| time.to_rfc3339())
+                ],
+            )?;
+            let task_id = transaction.last_insert_rowid();
+            for tag in record.tags {
+                transaction.execute(
+                    "INSERT INTO task_tags (task_id, tag) VALUES (?1, ?2)",
+                    params![task_id, tag],
+                )?;
+            }
+            for (position, (start, end)) in record.entries.into_iter().enumerate() {
+                transaction.execute(
+                    "INSERT INTO time_entries (task_id, position, start, end) VALUES (?1, ?2, ?3, ?4)",
+                    params![task_id, position, start.to_rfc3339(), end.to_rfc3339()],
+                )?;
+            }
+            let entry_notes = record.entry_notes.into_iter().enumerate();
+            let entry_notes = entry_notes.filter_map(|(entry, note)| Some((Some(entry), note?)));
+            let task_notes = record.notes.into_iter().map(|note| (None, note));
+            for (entry, note) in task_notes.chain(entry_notes) {
+                transaction.execute(
+                    "INSERT INTO notes (task_id, entry, note) VALUES (?1, ?2, ?3)",
+                    params![task_id, entry, note],
+                )?;
+            }
+        }
+        transaction.commit()?;
+        Ok(())
+    }
+
+    fn dates(&self) -> TaskResult<Vec<NaiveDate>> {
+        let mut statement = self
+            .connection
+            .prepare_cached("SELECT date FROM days ORDER BY date")?;
+        let dates = statement
+            .query_map([], |row| parse_date(row.get_ref(0)?.as_str()?))?
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(dates)
+    }
+
+    fn delete(&self, date: NaiveDate) -> TaskResult<()> {
+        self.connection.execute(
+            "DELETE FROM days WHERE date = ?1",
+            params![date.to_string()],
+        )?;
+        Ok(())
+    }
+
+    /// Appends the entries to the journal table, stored as JSON.
+    fn append_journal(&self, date: NaiveDate, entries: &[JournalEntry]) -> TaskResult<()> {
+        let date = date.to_string();
+        let transaction = self.connection.unchecked_transaction()?;
+        for entry in entries {
+            let entry = serde_json::to_string(entry).expect("should be able to serialize events");
+            transaction.execute(
+                "INSERT INTO journal (date, entry) VALUES (?1, ?2)",
+                params![date, entry],
+            )?;
+        }
+        transaction.commit()?;
+        Ok(())
+    }
+
+    fn journal(&self, date: NaiveDate) -> TaskResult<Vec<JournalEntry>> {
+        let mut statement = self
+            .connection
+            .prepare_cached("SELECT entry FROM journal WHERE date = ?1 ORDER BY id")?;
+        let entries = statement
+            .query_map(params![date.to_string()], |row| row.get::<_, String>(0))?
+            .collect::<Result<Vec<_>, _>>()?;
+        let entries = entries
+            .iter()
+            .map(|entry| serde_json::from_str(entry))
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(entries)
+    }
+
+    fn journal_dates(&self) -> TaskResult<Vec<NaiveDate>> {
+        let mut statement = self
+            .connection
+            .prepare_cached("SELECT DISTINCT date FROM journal ORDER BY date")?;
+        let dates = statement
+            .query_map([], |row| parse_date(row.get_ref(0)?.as_str()?))?
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(dates)
+    }
+
+    fn load_document(&self, name: &str) -> TaskResult<Option<String>> {
+        let data = self
+            .connection
+            .query_row(
+                "SELECT data FROM documents WHERE name = ?1",
+                params![name],
+                |row| row.get(0),
+            )
+            .optional()?;
+        Ok(data)
+    }
+
+    fn save_document(&self, name: &str, data: &str) -> TaskResult<()> {
+        self.connection.execute(
+            "INSERT OR REPLACE INTO documents (name, data) VALUES (?1, ?2)",
+            params![name, data],
+        )?;
+        Ok(())
+    }
+
+    /// Locks the database through a lock file next to it, waiting at most the configured
+    /// timeout.
+    fn lock(&self) -> TaskResult<StorageLock> {
+        lock_file(&self.lock_path, self.lock_timeout)
+    }
+}
+
+/// Parses a date stored in the database.
+fn parse_date(date: &str) -> rusqlite::Result<NaiveDate> {
+    NaiveDate::parse_from_str(date, "%F").map_err(|e| {
+        rusqlite::Error::FromSqlConversionFailure(0, rusqlite::types::Type::Text, Box::new(e))
+    })
+}
+
+/// Parses a timestamp stored in the database.
+fn parse_time(time: &str) -> rusqlite::Result<DateTime<Local>> {
+    DateTime::parse_from_rfc3339(time)
+        .map(|time| time.with_timezone(&Local))
+        .map_err(|e| {
+            rusqlite::Error::FromSqlConversionFailure(0, rusqlite::types::Type::Text, Box::new(e))
+        })
+}
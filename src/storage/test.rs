@@ -0,0 +1,482 @@
+/*
+ * tasklog - A command-line task tracking tool.
+ *
+ * Copyright (C) 2024 Lucas M. de Jong Larrarte
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with this program. If not, see <https://www.gnu.org/licenses/>.
+ */
+use super::*;
+use crate::journal::Event;
+use crate::test_util::date;
+use chrono::{Duration, Local};
+use std::fs;
+use std::time::Duration as StdDuration;
+use tempfile::TempDir;
+
+fn tasks(name: &str) -> TaskManager {
+    let now = Local::now() - Duration::minutes(30);
+    let mut tasks = TaskManager::new();
+    tasks.start_new_task(name.to_string(), now).unwrap();
+    tasks
+        .stop_running_task_with_time(now + Duration::minutes(10))
+        .unwrap();
+    tasks
+}
+
+fn entry(task: &str) -> JournalEntry {
+    let event = Event::Deleted {
+        task: task.to_string(),
+    };
+    JournalEntry::new(event, Local::now())
+}
+
+fn json_storage() -> (TempDir, JsonDirStorage) {
+    let dir = TempDir::new().unwrap();
+    let storage = JsonDirStorage::new(dir.path(), StdDuration::from_millis(100)).unwrap();
+    (dir, storage)
+}
+
+mod memory_storage {
+    use super::*;
+
+    #[test]
+    fn test_memory_storage_load_when_empty() {
+        let storage = MemoryStorage::new();
+        let loaded = storage.load(date(16)).unwrap();
+        assert!(loaded.list_tasks().is_empty());
+        assert!(storage.dates().unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_memory_storage_save_and_load() {
+        let storage = MemoryStorage::new();
+        storage.save(&tasks("Test2"), date(17)).unwrap();
+        storage.save(&tasks("Test1"), date(16)).unwrap();
+        assert_eq!(storage.load(date(16)).unwrap().list_tasks(), vec!["Test1"]);
+        assert_eq!(storage.load(date(17)).unwrap().list_tasks(), vec!["Test2"]);
+        assert_eq!(storage.dates().unwrap(), vec![date(16), date(17)]);
+    }
+
+    #[test]
+    fn test_memory_storage_journal() {
+        let storage = MemoryStorage::new();
+        assert!(storage.journal(date(16)).unwrap().is_empty());
+        storage.append_journal(date(17), &[entry("Test1")]).unwrap();
+        storage
+            .append_journal(date(17), &[entry("Test2"), entry("Test3")])
+            .unwrap();
+        let journal = storage.journal(date(17)).unwrap();
+        assert_eq!(journal.len(), 3);
+        assert_eq!(journal[2].event, entry("Test3").event);
+        assert_eq!(storage.journal_dates().unwrap(), vec![date(17)]);
+    }
+
+    #[test]
+    fn test_memory_storage_documents() {
+        let storage = MemoryStorage::new();
+        assert_eq!(storage.load_document("test").unwrap(), None);
+        storage.save_document("test", "data1").unwrap();
+        storage.save_document("test", "data2").unwrap();
+        assert_eq!(
+            storage.load_document("test").unwrap().as_deref(),
+            Some("data2")
+        );
+        assert!(storage.dates().unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_memory_storage_set_aside() {
+        let storage = MemoryStorage::new();
+        let saved = tasks("Test");
+        storage.save(&saved, date(16)).unwrap();
+        assert_eq!(storage.load_records(date(16)).unwrap(), saved.to_records());
+        assert_eq!(storage.set_aside(date(16)).unwrap(), None);
+        assert!(storage.dates().unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_memory_storage_delete() {
+        let storage = MemoryStorage::new();
+        storage.save(&tasks("Test"), date(16)).unwrap();
+        storage.delete(date(16)).unwrap();
+        assert!(storage.load(date(16)).unwrap().list_tasks().is_empty());
+        assert!(storage.dates().unwrap().is_empty());
+    }
+}
+
+mod json_dir_storage {
+    use super::*;
+
+    #[test]
+    fn test_json_dir_storage_load_when_missing() {
+        let (_dir, storage) = json_storage();
+        let loaded = storage.load(date(16)).unwrap();
+        assert!(loaded.list_tasks().is_empty());
+    }
+
+    #[test]
+    fn test_json_dir_storage_save_and_load() {
+        let (dir, storage) = json_storage();
+        storage.save(&tasks("Test"), date(16)).unwrap();
+        assert!(dir.path().join("2024-07-16.json").exists());
+        assert!(!dir.path().join("2024-07-16.json.tmp").exists());
+        assert_eq!(storage.load(date(16)).unwrap().list_tasks(), vec!["Test"]);
+    }
+
+    #[test]
+    fn test_json_dir_storage_dates_ignores_other_files() {
+        let (dir, storage) = json_storage();
+        storage.save(&tasks("Test"), date(17)).unwrap();
+        storage.save(&tasks("Test"), date(16)).unwrap();
+        fs::write(dir.path().join("2024-07-18.json.tmp"), "").unwrap();
+        fs::write(dir.path().join("settings.toml"), "").unwrap();
+        assert_eq!(storage.dates().unwrap(), vec![date(16), date(17)]);
+    }
+
+    #[test]
+    fn test_json_dir_storage_journal() {
+        let (dir, storage) = json_storage();
+        assert!(storage.journal(date(16)).unwrap().is_empty());
+        assert!(storage.journal_dates().unwrap().is_empty());
+        storage.append_journal(date(17), &[entry("Test1")]).unwrap();
+        storage
+            .append_journal(date(17), &[entry("Test2"), entry("Test3")])
+            .unwrap();
+        assert!(dir.path().join("journal").join("2024-07-17.jsonl").exists());
+        let events: Vec<_> = storage
+            .journal(date(17))
+            .unwrap()
+            .into_iter()
+            .map(|entry| entry.event)
+            .collect();
+        let expected = [entry("Test1"), entry("Test2"), entry("Test3")].map(|entry| entry.event);
+        assert_eq!(events, expected);
+        assert_eq!(storage.journal_dates().unwrap(), vec![date(17)]);
+        assert_eq!(storage.dates().unwrap(), vec![]);
+    }
+
+    #[test]
+    fn test_json_dir_storage_journal_when_append_was_interrupted() {
+        let (dir, storage) = json_storage();
+        storage.append_journal(date(16), &[entry("Test1")]).unwrap();
+        let file = dir.path().join("journal").join("2024-07-16.jsonl");
+        let mut data = fs::read_to_string(&file).unwrap();
+        data += r#"{"recorded":"2024-07-16T09:00"#;
+        fs::write(&file, data).unwrap();
+        assert_eq!(storage.journal(date(16)).unwrap().len(), 1);
+        storage.append_journal(date(16), &[entry("Test2")]).unwrap();
+        let journal = storage.journal(date(16)).unwrap();
+        assert_eq!(journal.len(), 2);
+        assert_eq!(journal[1].event, entry("Test2").event);
+    }
+
+    #[test]
+    fn test_json_dir_storage_documents() {
+        let (_dir, storage) = json_storage();
+        assert_eq!(storage.load_document("test").unwrap(), None);
+        storage.save_document("test", "data1").unwrap();
+        storage.save_document("test", "data2").unwrap();
+        assert_eq!(
+            storage.load_document("test").unwrap().as_deref(),
+            Some("data2")
+        );
+        assert!(storage.dates().unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_json_dir_storage_delete() {
+        let (dir, storage) = json_storage();
+        storage.save(&tasks("Test"), date(16)).unwrap();
+        storage.delete(date(16)).unwrap();
+        storage.delete(date(16)).unwrap();
+        assert!(!dir.path().join("2024-07-16.json").exists());
+    }
+
+    #[test]
+    fn test_json_dir_storage_load_when_truncated() {
+        let (dir, storage) = json_storage();
+        let file = dir.path().join("2024-07-16.json");
+        let data = r#"{"schema_version":2,"tasks":{"stopp"#;
+        fs::write(&file, data).unwrap();
+        let _lock = storage.lock().unwrap();
+        let error = storage.load(date(16)).unwrap_err();
+        let TaskError::TruncatedFile(_, moved_to) = error else {
+            panic!("expected a truncated file error, got {error:?}");
+        };
+        assert!(!file.exists());
+        assert_eq!(moved_to.parent(), Some(dir.path()));
+        let name = moved_to.file_name().unwrap().to_str().unwrap();
+        assert!(name.starts_with("2024-07-16.json."));
+        assert!(name.ends_with(".corrupt"));
+        assert_eq!(fs::read_to_string(&moved_to).unwrap(), data);
+        assert!(storage.load(date(16)).unwrap().list_tasks().is_empty());
+    }
+
+    #[test]
+    fn test_json_dir_storage_load_when_truncated_and_unlocked() {
+        let (dir, storage) = json_storage();
+        let file = dir.path().join("2024-07-16.json");
+        fs::write(&file, r#"{"schema_version":2,"tasks":{"stopp"#).unwrap();
+        let error = storage.load(date(16)).unwrap_err();
+        assert!(matches!(error, TaskError::InvalidFile(..)));
+        assert!(file.exists());
+        drop(storage.lock().unwrap());
+        assert!(matches!(
+            storage.load(date(16)),
+            Err(TaskError::InvalidFile(..))
+        ));
+        assert!(file.exists());
+    }
+
+    #[test]
+    fn test_write_atomically() {
+        let dir = TempDir::new().unwrap();
+        let file = dir.path().join("data.json");
+        write_atomically(&file, b"old").unwrap();
+        write_atomically(&file, b"new").unwrap();
+        assert_eq!(fs::read_to_string(&file).unwrap(), "new");
+        let names: Vec<_> = fs::read_dir(dir.path())
+            .unwrap()
+            .map(|entry| entry.unwrap().file_name())
+            .collect();
+        assert_eq!(names, ["data.json"]);
+    }
+
+    #[test]
+    fn test_write_atomically_when_rename_fails() {
+        let dir = TempDir::new().unwrap();
+        let file = dir.path().join("data.json");
+        fs::create_dir(&file).unwrap();
+        fs::write(file.join("other"), "").unwrap();
+        let error = write_atomically(&file, b"new").unwrap_err();
+        assert!(matches!(error, TaskError::FileIO(..)));
+        assert!(file.is_dir());
+        assert!(!dir.path().join("data.json.tmp").exists());
+    }
+
+    #[test]
+    fn test_json_dir_storage_load_when_version_0() {
+        let (dir, storage) = json_storage();
+        let file = dir.path().join("2024-07-16.json");
+        let data = r#"{"schema_version":0,"tasks":{"stopped":[],"running":null}}"#;
+        fs::write(&file, data).unwrap();
+        let error = storage.load(date(16)).unwrap_err();
+        assert!(matches!(error, TaskError::InvalidFile(..)));
+    }
+
+    #[test]
+    fn test_json_dir_storage_load_when_unreadable() {
+        let (dir, storage) = json_storage();
+        fs::create_dir(dir.path().join("2024-07-16.json")).unwrap();
+        let error = storage.load(date(16)).unwrap_err();
+        assert!(matches!(error, TaskError::FileIO(..)));
+    }
+
+    #[test]
+    fn test_json_dir_storage_save_when_invalid() {
+        let (dir, storage) = json_storage();
+        let file = dir.path().join("2024-07-16.json");
+        fs::write(&file, r#"{"stopped":[{"name":"Test"}],"running":null}"#).unwrap();
+        let error = storage.load(date(16)).unwrap_err();
+        assert!(matches!(error, TaskError::InvalidFile(..)));
+        let error = storage.save(&TaskManager::new(), date(16)).unwrap_err();
+        assert!(matches!(error, TaskError::RefusingToOverwrite(..)));
+        assert!(fs::read_to_string(&file).unwrap().contains("Test"));
+    }
+
+    #[test]
+    fn test_json_dir_storage_save_when_damaged_after_load() {
+        let (dir, storage) = json_storage();
+        let file = dir.path().join("2024-07-16.json");
+        storage.save(&tasks("Test"), date(16)).unwrap();
+        let _lock = storage.lock().unwrap();
+        let mut loaded = storage.load(date(16)).unwrap();
+        fs::write(&file, "not json").unwrap();
+        loaded
+            .start_new_task("Other".to_string(), Local::now())
+            .unwrap();
+        let error = storage.save(&loaded, date(16)).unwrap_err();
+        assert!(matches!(error, TaskError::RefusingToOverwrite(..)));
+        assert_eq!(fs::read_to_string(&file).unwrap(), "not json");
+    }
+
+    #[test]
+    fn test_json_dir_storage_load_records_when_invariants_are_broken() {
+        let (dir, storage) = json_storage();
+        let saved = tasks("Test");
+        storage.save(&saved, date(17)).unwrap();
+        assert_eq!(storage.load_records(date(17)).unwrap(), saved.to_records());
+        let file = dir.path().join("2024-07-16.json");
+        fs::write(&file, r#"{"stopped":[],"running":{"name":"Test","entries":[{"start":"2024-07-16T11:00:00+02:00","end":"2024-07-16T12:00:00+02:00"}],"last_entry":{"start":"2024-07-16T10:00:00+02:00"}}}"#).unwrap();
+        assert!(storage.load(date(16)).is_err());
+        let records = storage.load_records(date(16)).unwrap();
+        assert_eq!(records.len(), 1);
+        assert!(records[0].running_since.is_some());
+        assert!(storage.load_records(date(18)).unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_json_dir_storage_set_aside() {
+        let (dir, storage) = json_storage();
+        let file = dir.path().join("2024-07-16.json");
+        fs::write(&file, r#"{"stopped":[{"name":"Test"}],"running":null}"#).unwrap();
+        let moved_to = storage.set_aside(date(16)).unwrap().unwrap();
+        assert!(!file.exists());
+        assert!(fs::read_to_string(moved_to).unwrap().contains("Test"));
+        storage.save(&tasks("Test"), date(16)).unwrap();
+        assert_eq!(storage.set_aside(date(17)).unwrap(), None);
+    }
+
+    #[test]
+    fn test_json_dir_storage_lock_when_already_locked() {
+        let (dir, storage) = json_storage();
+        let other = JsonDirStorage::new(dir.path(), StdDuration::from_millis(100)).unwrap();
+        let lock = storage.lock().unwrap();
+        let error = other.lock().unwrap_err();
+        assert!(matches!(error, TaskError::LockTimeout(_)));
+        drop(lock);
+        assert!(other.lock().is_ok());
+    }
+
+    #[test]
+    fn test_lock_file_times_out() {
+        let dir = TempDir::new().unwrap();
+        let path = dir.path().join(".lock");
+        let timeout = StdDuration::from_millis(200);
+        let _lock = lock_file(&path, timeout).unwrap();
+        let started = std::time::Instant::now();
+        let error = lock_file(&path, timeout).unwrap_err();
+        assert!(started.elapsed() >= timeout);
+        assert!(matches!(error, TaskError::LockTimeout(locked) if locked == path));
+    }
+
+    #[test]
+    fn test_json_dir_storage_migrate() {
+        let (dir, storage) = json_storage();
+        storage.save(&tasks("Test"), date(17)).unwrap();
+        let file = dir.path().join("2024-07-16.json");
+        fs::write(&file, r#"{"stopped":[],"running":null}"#).unwrap();
+        assert_eq!(storage.migrate(true).unwrap(), vec![(date(16), 1)]);
+        assert!(!fs::read_to_string(&file)
+            .unwrap()
+            .contains("schema_version"));
+        assert_eq!(storage.migrate(false).unwrap(), vec![(date(16), 1)]);
+        assert!(fs::read_to_string(&file)
+            .unwrap()
+            .contains("schema_version"));
+        assert!(storage.migrate(false).unwrap().is_empty());
+    }
+}
+
+#[cfg(feature = "sqlite")]
+mod sqlite_storage {
+    use super::*;
+
+    fn sqlite_storage() -> (TempDir, SqliteStorage) {
+        let dir = TempDir::new().unwrap();
+        let storage =
+            SqliteStorage::open(dir.path().join("tasklog.db"), StdDuration::from_millis(100))
+                .unwrap();
+        (dir, storage)
+    }
+
+    #[test]
+    fn test_sqlite_storage_load_when_missing() {
+        let (_dir, storage) = sqlite_storage();
+        assert!(storage.load(date(16)).unwrap().list_tasks().is_empty());
+        assert!(storage.dates().unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_sqlite_storage_save_and_load() {
+        let (_dir, storage) = sqlite_storage();
+        let now = Local::now() - Duration::minutes(30);
+        let mut saved = tasks("Test1");
+        saved
+            .start_new_task("Test2".to_string(), now + Duration::minutes(15))
+            .unwrap();
+        let tags = vec!["billable".to_string(), "review".to_string()];
+        saved.tag_task("Test2".to_string(), tags, vec![]).unwrap();
+        saved
+            .note_task("Test2".to_string(), "Ticket #42".to_string())
+            .unwrap();
+        saved
+            .note_entry("Test1".to_string(), 0, "Fixed the bug".to_string())
+            .unwrap();
+        storage.save(&saved, date(16)).unwrap();
+        let loaded = storage.load(date(16)).unwrap();
+        assert_eq!(loaded.to_records(), saved.to_records());
+        assert_eq!(loaded.running_task(), Some("Test2"));
+    }
+
+    #[test]
+    fn test_sqlite_storage_save_replaces_day() {
+        let (_dir, storage) = sqlite_storage();
+        storage.save(&tasks("Test1"), date(16)).unwrap();
+        storage.save(&tasks("Test2"), date(17)).unwrap();
+        storage.save(&tasks("Test3"), date(16)).unwrap();
+        assert_eq!(storage.load(date(16)).unwrap().list_tasks(), vec!["Test3"]);
+        assert_eq!(storage.load(date(17)).unwrap().list_tasks(), vec!["Test2"]);
+        assert_eq!(storage.dates().unwrap(), vec![date(16), date(17)]);
+    }
+
+    #[test]
+    fn test_sqlite_storage_journal() {
+        let (_dir, storage) = sqlite_storage();
+        assert!(storage.journal(date(16)).unwrap().is_empty());
+        storage.append_journal(date(17), &[entry("Test1")]).unwrap();
+        storage
+            .append_journal(date(17), &[entry("Test2"), entry("Test3")])
+            .unwrap();
+        let journal = storage.journal(date(17)).unwrap();
+        assert_eq!(journal.len(), 3);
+        assert_eq!(journal[2].event, entry("Test3").event);
+        assert_eq!(storage.journal_dates().unwrap(), vec![date(17)]);
+        storage.delete(date(17)).unwrap();
+        assert_eq!(storage.journal(date(17)).unwrap().len(), 3);
+    }
+
+    #[test]
+    fn test_sqlite_storage_documents() {
+        let (_dir, storage) = sqlite_storage();
+        assert_eq!(storage.load_document("test").unwrap(), None);
+        storage.save_document("test", "data1").unwrap();
+        storage.save_document("test", "data2").unwrap();
+        assert_eq!(
+            storage.load_document("test").unwrap().as_deref(),
+            Some("data2")
+        );
+        assert!(storage.dates().unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_sqlite_storage_delete() {
+        let (_dir, storage) = sqlite_storage();
+        storage.save(&tasks("Test"), date(16)).unwrap();
+        storage.delete(date(16)).unwrap();
+        assert!(storage.load(date(16)).unwrap().list_tasks().is_empty());
+        assert!(storage.dates().unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_sqlite_storage_lock_when_already_locked() {
+        let (dir, storage) = sqlite_storage();
+        let other =
+            SqliteStorage::open(dir.path().join("tasklog.db"), StdDuration::from_millis(100))
+                .unwrap();
+        let _lock = storage.lock().unwrap();
+        let error = other.lock().unwrap_err();
+        assert!(matches!(error, TaskError::LockTimeout(_)));
+    }
+}
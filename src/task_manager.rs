@@ -20,12 +20,17 @@
 mod test;
 
 use chrono::{DateTime, Duration, Local, NaiveDate};
-use colored::Colorize;
 use serde::de;
 use serde::{Deserialize, Deserializer, Serialize};
+use std::cmp::Ordering;
 use std::mem;
+use std::path::PathBuf;
 use thiserror::Error;
 
+use crate::journal::Event;
+use crate::report::{self, Report, ReportOptions, ReportRow, TimelineEntry};
+use crate::task_path;
+
 /// Errors that can occur while managing tasks.
 #[derive(Error, Debug)]
 pub enum TaskError {
@@ -45,10 +50,53 @@ pub enum TaskError {
     InvalidStopTime,
     #[error("Invalid start time. Must not be after the task's stop time")]
     InvalidStartTime,
-    #[error("File IO error: {0}")]
-    FileIO(#[from] std::io::Error),
+    #[error("Invalid start time. Must not be before the end of any entry of the day, but an entry of task '{0}' ends later")]
+    StartBeforeEntryEnd(String),
+    #[error("Invalid time range. The start must not be after the end")]
+    InvalidTimeRange,
+    #[error("The time entry overlaps with an entry of task '{0}'")]
+    OverlappingEntry(String),
+    #[error("Task '{1}' has no entry {0}")]
+    EntryNotFound(usize, String),
+    #[error("Entry {0} of task '{1}' is still running")]
+    EntryRunning(usize, String),
+    #[error("Invalid split time. Must be within the entry")]
+    InvalidSplitTime,
+    #[error("Invalid time {0}. Must not be in the future")]
+    FutureTime(String),
+    #[error("Time {0} is not on {1}")]
+    TimeOutsideDate(String, NaiveDate),
+    #[error("Time {0} does not exist in the local time zone")]
+    NonexistentTime(String),
+    #[error("Invalid time. Must not be that far in the past")]
+    TimeOutOfRange,
+    #[error("File IO error on '{}': {1}", .0.display())]
+    FileIO(PathBuf, #[source] std::io::Error),
+    #[error("File '{}' was truncated by an interrupted write and has been moved to '{}'", .0.display(), .1.display())]
+    TruncatedFile(PathBuf, PathBuf),
+    #[error("Timed out waiting for the lock on '{}'. Is another tasklog command running?", .0.display())]
+    LockTimeout(PathBuf),
+    #[error("File '{}' could not be parsed: {1}", .0.display())]
+    InvalidFile(PathBuf, #[source] serde_json::Error),
+    #[error("Refusing to overwrite '{}', which could not be parsed: {1}", .0.display())]
+    RefusingToOverwrite(PathBuf, #[source] serde_json::Error),
+    #[error("Terminal IO error: {0}")]
+    TerminalIO(#[source] std::io::Error),
+    #[error("Could not run the editor '{0}': {1}")]
+    EditorIO(String, #[source] std::io::Error),
+    #[error("The editor '{0}' exited with {1}")]
+    EditorFailed(String, std::process::ExitStatus),
+    #[error("The tasks of {0} were changed while being edited; nothing was saved")]
+    ChangedWhileEditing(NaiveDate),
     #[error("Serialization error: {0}")]
     Serialization(#[from] serde_json::Error),
+    #[error("The tasks of {0} were changed since; cannot undo or redo across that change")]
+    HistoryConflict(NaiveDate),
+    #[error("Invalid data stored for {0}: {1}")]
+    InvalidData(NaiveDate, String),
+    #[cfg(feature = "sqlite")]
+    #[error("Database error: {0}")]
+    Database(#[from] rusqlite::Error),
     #[error("Configuration error: {0}")]
     ConfigError(#[from] config::ConfigError),
 }
@@ -64,6 +112,12 @@ pub type TaskResult<T> = Result<T, TaskError>;
 #[derive(Debug, Serialize, PartialEq, Clone)]
 struct Task<T: TimeEntry> {
     name: String,
+    /// The tags of the task, in order and without duplicates.
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    tags: Vec<String>,
+    /// Free-text notes on the task, oldest first.
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    notes: Vec<String>,
     entries: Vec<CompletedTimeEntry>,
     last_entry: T,
 }
@@ -76,6 +130,8 @@ impl RunningTask {
     pub fn new(name: impl ToString, now: DateTime<Local>) -> Self {
         RunningTask {
             name: name.to_string(),
+            tags: vec![],
+            notes: vec![],
             entries: vec![],
             last_entry: OngoingTimeEntry::new(now),
         }
@@ -89,6 +145,8 @@ impl RunningTask {
         assert!(self.can_stop(now));
         StoppedTask {
             name: self.name,
+            tags: self.tags,
+            notes: self.notes,
             entries: self.entries,
             last_entry: self.last_entry.complete(now),
         }
@@ -125,6 +183,8 @@ impl StoppedTask {
         entries.push(self.last_entry);
         Task {
             name: self.name,
+            tags: self.tags,
+            notes: self.notes,
             entries,
             last_entry: OngoingTimeEntry::new(now),
         }
@@ -154,6 +214,10 @@ impl StoppedTask {
 #[derive(Debug, Deserialize)]
 struct TaskDeser<T: TimeEntry> {
     name: String,
+    #[serde(default)]
+    tags: Vec<String>,
+    #[serde(default)]
+    notes: Vec<String>,
     entries: Vec<CompletedTimeEntry>,
     last_entry: T,
 }
@@ -180,8 +244,13 @@ impl<T: TimeEntry> TryFrom<TaskDeser<T>> for Task<T> {
                 Err("last_entry must be after the end of the last segment")?;
             }
         }
+        let mut tags = value.tags;
+        tags.sort();
+        tags.dedup();
         Ok(Task {
             name: value.name,
+            tags,
+            notes: value.notes,
             entries,
             last_entry: value.last_entry,
         })
@@ -201,6 +270,9 @@ trait TimeEntry {
 struct CompletedTimeEntry {
     start: DateTime<Local>,
     end: DateTime<Local>,
+    /// A free-text note on the work done in the entry.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    note: Option<String>,
 }
 impl TimeEntry for CompletedTimeEntry {
     fn start(&self) -> DateTime<Local> {
@@ -214,8 +286,18 @@ impl CompletedTimeEntry {
     /// - `start` must be before `end`
     fn new(start: DateTime<Local>, end: DateTime<Local>) -> Self {
         assert!(start <= end);
-        CompletedTimeEntry { start, end }
+        CompletedTimeEntry {
+            start,
+            end,
+            note: None,
+        }
+    }
+
+    /// Returns the entry with the given note.
+    fn with_note(self, note: Option<String>) -> Self {
+        CompletedTimeEntry { note, ..self }
     }
+
     /// Returns the duration of the time entry.
     fn duration(&self) -> Duration {
         self.end - self.start
@@ -261,6 +343,8 @@ impl OngoingTimeEntry {
 struct CompletedTimeEntryDeser {
     start: DateTime<Local>,
     end: DateTime<Local>,
+    #[serde(default)]
+    note: Option<String>,
 }
 impl<'de> Deserialize<'de> for CompletedTimeEntry {
     fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
@@ -280,16 +364,149 @@ impl TryFrom<CompletedTimeEntryDeser> for CompletedTimeEntry {
             Ok(CompletedTimeEntry {
                 start: value.start,
                 end: value.end,
+                note: value.note,
+            })
+        }
+    }
+}
+
+/// Flat representation of a task, for storage backends that don't store the JSON form.
+#[derive(Debug, PartialEq, Clone)]
+pub struct TaskRecord {
+    pub name: String,
+    /// The tags of the task, in order.
+    pub tags: Vec<String>,
+    /// The notes on the task, oldest first.
+    pub notes: Vec<String>,
+    /// The completed time entries of the task, as `(start, end)` pairs.
+    pub entries: Vec<(DateTime<Local>, DateTime<Local>)>,
+    /// The notes on the completed time entries, one for each of `entries`.
+    pub entry_notes: Vec<Option<String>>,
+    /// The start time of the ongoing entry, if the task is running.
+    pub running_since: Option<DateTime<Local>>,
+}
+impl TaskRecord {
+    /// Reads records from the JSON form of a [TaskManager] without checking any of its
+    /// invariants, so that damaged data can still be inspected.
+    pub fn unchecked_from_json(tasks: serde_json::Value) -> serde_json::Result<Vec<Self>> {
+        let tasks: UncheckedTaskManager = serde_json::from_value(tasks)?;
+        let mut records: Vec<_> = tasks
+            .stopped
+            .into_iter()
+            .map(|task| {
+                let mut entries = task.entry_pairs();
+                entries.push((task.last_entry.start, task.last_entry.end));
+                let mut entry_notes = task.entry_notes();
+                entry_notes.push(task.last_entry.note);
+                TaskRecord {
+                    name: task.name,
+                    tags: task.tags,
+                    notes: task.notes,
+                    entries,
+                    entry_notes,
+                    running_since: None,
+                }
             })
+            .collect();
+        records.extend(tasks.running.map(|task| TaskRecord {
+            entries: task.entry_pairs(),
+            entry_notes: task.entry_notes(),
+            name: task.name,
+            tags: task.tags,
+            notes: task.notes,
+            running_since: Some(task.last_entry.start),
+        }));
+        Ok(records)
+    }
+}
+/// Helper for reading tasks without checking their invariants.
+#[derive(Debug, Deserialize)]
+struct UncheckedTaskManager {
+    stopped: Vec<UncheckedTask<CompletedTimeEntryDeser>>,
+    running: Option<UncheckedTask<OngoingTimeEntry>>,
+}
+/// Helper for reading a task without checking its invariants.
+#[derive(Debug, Deserialize)]
+struct UncheckedTask<T> {
+    name: String,
+    #[serde(default)]
+    tags: Vec<String>,
+    #[serde(default)]
+    notes: Vec<String>,
+    entries: Vec<CompletedTimeEntryDeser>,
+    last_entry: T,
+}
+impl<T> UncheckedTask<T> {
+    /// Converts the completed entries of the task into `(start, end)` pairs.
+    fn entry_pairs(&self) -> Vec<(DateTime<Local>, DateTime<Local>)> {
+        self.entries
+            .iter()
+            .map(|entry| (entry.start, entry.end))
+            .collect()
+    }
+
+    /// Returns the notes of the completed entries of the task.
+    fn entry_notes(&self) -> Vec<Option<String>> {
+        self.entries
+            .iter()
+            .map(|entry| entry.note.clone())
+            .collect()
+    }
+}
+impl<T: TimeEntry> Task<T> {
+    /// Converts the completed entries of the task into `(start, end)` pairs.
+    fn entry_pairs(&self) -> Vec<(DateTime<Local>, DateTime<Local>)> {
+        self.entries
+            .iter()
+            .map(|entry| (entry.start, entry.end))
+            .collect()
+    }
+
+    /// Returns the notes of the completed entries of the task.
+    fn entry_notes(&self) -> Vec<Option<String>> {
+        self.entries
+            .iter()
+            .map(|entry| entry.note.clone())
+            .collect()
+    }
+}
+impl From<&StoppedTask> for TaskRecord {
+    fn from(task: &StoppedTask) -> Self {
+        let mut entries = task.entry_pairs();
+        entries.push((task.last_entry.start, task.last_entry.end));
+        let mut entry_notes = task.entry_notes();
+        entry_notes.push(task.last_entry.note.clone());
+        TaskRecord {
+            name: task.name.clone(),
+            tags: task.tags.clone(),
+            notes: task.notes.clone(),
+            entries,
+            entry_notes,
+            running_since: None,
+        }
+    }
+}
+impl From<&RunningTask> for TaskRecord {
+    fn from(task: &RunningTask) -> Self {
+        TaskRecord {
+            name: task.name.clone(),
+            tags: task.tags.clone(),
+            notes: task.notes.clone(),
+            entries: task.entry_pairs(),
+            entry_notes: task.entry_notes(),
+            running_since: Some(task.last_entry.start),
         }
     }
 }
 
 /// List of current tasks.
-#[derive(Debug, Serialize, Deserialize, Default)]
+#[derive(Debug, Serialize, Deserialize, Default, Clone, PartialEq)]
 pub struct TaskManager {
     stopped: Vec<StoppedTask>,
     running: Option<RunningTask>,
+    /// Events recorded since the last call to [TaskManager::take_events].
+    #[serde(skip)]
+    events: Vec<Event>,
 }
 impl TaskManager {
     /// Creates a new task manager.
@@ -335,7 +552,10 @@ impl TaskManager {
     ) -> TaskResult<String> {
         self.check_no_running_task()?;
         match self.index_of(|task| task.name == task_name)? {
-            None => Ok(self.do_start_new_task(task_name, start)),
+            None => {
+                self.check_after_entries(start)?;
+                Ok(self.do_start_new_task(task_name, start))
+            }
             Some(_) => Err(TaskError::TaskAlreadyExists(task_name)),
         }
     }
@@ -344,6 +564,10 @@ impl TaskManager {
     fn do_start_new_task(&mut self, task_name: String, start: DateTime<Local>) -> String {
         let new_task = RunningTask::new(task_name.clone(), start);
         self.running = Some(new_task);
+        self.events.push(Event::Started {
+            task: task_name.clone(),
+            at: start,
+        });
         task_name
     }
 
@@ -372,6 +596,7 @@ impl TaskManager {
         let task = self.running.take().unwrap();
         let name = task.name.to_string();
         self.stopped.push(task.stop(end));
+        self.events.push(Event::Stopped { at: end });
         name
     }
 
@@ -403,6 +628,28 @@ impl TaskManager {
         }
     }
 
+    /// Checks that a task can be started at the given time, which must not be before the end of
+    /// any completed entry of the day, like [TaskManager::check_no_overlap] checks entries.
+    fn check_after_entries(&self, start: DateTime<Local>) -> TaskResult<()> {
+        let ends_later = |entry: &CompletedTimeEntry| start < entry.end;
+        for task in &self.stopped {
+            if task
+                .entries
+                .iter()
+                .chain([&task.last_entry])
+                .any(ends_later)
+            {
+                return Err(TaskError::StartBeforeEntryEnd(task.name.clone()));
+            }
+        }
+        if let Some(task) = &self.running {
+            if task.entries.iter().any(ends_later) {
+                return Err(TaskError::StartBeforeEntryEnd(task.name.clone()));
+            }
+        }
+        Ok(())
+    }
+
     /// Resumes the last task.
     pub fn resume_last_task(&mut self, start: DateTime<Local>) -> TaskResult<String> {
         self.check_no_running_task()?;
@@ -411,6 +658,7 @@ impl TaskManager {
             len => {
                 let index = len - 1;
                 self.check_can_start(index, start)?;
+                self.check_after_entries(start)?;
                 let name = self.do_resume_task(index, start);
                 Ok(name)
             }
@@ -424,6 +672,7 @@ impl TaskManager {
             None => Err(TaskError::TaskNotFound(task_name)),
             Some(index) => {
                 self.check_can_start(index, start)?;
+                self.check_after_entries(start)?;
                 Ok(self.do_resume_task(index, start))
             }
         }
@@ -434,6 +683,10 @@ impl TaskManager {
         let task = self.stopped.remove(index);
         let task_name = task.name.clone();
         self.running = Some(task.start(start));
+        self.events.push(Event::Resumed {
+            task: task_name.clone(),
+            at: start,
+        });
         task_name
     }
 
@@ -446,6 +699,7 @@ impl TaskManager {
         match self.index_of(|task| task.name == task_name)? {
             Some(_) => Err(TaskError::TaskAlreadyExists(task_name)),
             None => {
+                self.check_after_entries(now)?;
                 self.stop_running_task_with_time(now)?;
                 let task = self.do_start_new_task(task_name, now);
                 Ok(task)
@@ -460,8 +714,9 @@ impl TaskManager {
             len => {
                 let index = len - 1;
                 self.check_can_start(index, now)?;
+                self.check_after_entries(now)?;
                 self.stop_running_task_with_time(now)?;
-                let task = self.do_resume_task(len - 1, now);
+                let task = self.do_resume_task(index, now);
                 Ok(task)
             }
         }
@@ -473,6 +728,7 @@ impl TaskManager {
             None => Err(TaskError::TaskNotFound(task_name)),
             Some(index) => {
                 self.check_can_start(index, now)?;
+                self.check_after_entries(now)?;
                 self.stop_running_task_with_time(now)?;
                 let task = self.do_resume_task(index, now);
                 Ok(task)
@@ -482,26 +738,37 @@ impl TaskManager {
 
     /// Deletes the given task.
     pub fn delete_task(&mut self, task_name: String) -> TaskResult<String> {
-        let index = self.index_of(|task| task.name.contains(&task_name))?;
+        self.delete_task_matching(task_name, |task, name| task.contains(name))
+    }
+
+    /// Deletes the task whose name matches `task_name` according to `matches`.
+    fn delete_task_matching(
+        &mut self,
+        task_name: String,
+        matches: impl Fn(&str, &str) -> bool,
+    ) -> TaskResult<String> {
+        let index = self.index_of(|task| matches(&task.name, &task_name))?;
         let running_task = self
             .running
             .as_ref()
-            .filter(|task| task.name.contains(&task_name));
-        match (index, running_task) {
-            (None, None) => Err(TaskError::TaskNotFound(task_name)),
+            .filter(|task| matches(&task.name, &task_name));
+        let name = match (index, running_task) {
+            (None, None) => Err(TaskError::TaskNotFound(task_name))?,
             (Some(index), None) => {
                 let task = self.stopped.remove(index);
-                Ok(task.name)
+                task.name
             }
             (None, Some(_)) => {
                 let task = self
                     .running
                     .take()
                     .expect("Should exist since running_task is Some");
-                Ok(task.name)
+                task.name
             }
-            _ => Err(TaskError::MultipleTasksFound),
-        }
+            _ => Err(TaskError::MultipleTasksFound)?,
+        };
+        self.events.push(Event::Deleted { task: name.clone() });
+        Ok(name)
     }
 
     /// Renames the given task.
@@ -509,11 +776,21 @@ impl TaskManager {
         &mut self,
         task_name: String,
         new_name: String,
+    ) -> TaskResult<(String, String)> {
+        self.rename_task_matching(task_name, new_name, |task, name| task.contains(name))
+    }
+
+    /// Renames the task whose name matches `task_name` according to `matches`.
+    fn rename_task_matching(
+        &mut self,
+        task_name: String,
+        new_name: String,
+        matches: impl Fn(&str, &str) -> bool,
     ) -> TaskResult<(String, String)> {
         let mut tasks: Vec<_> = self
             .stopped
             .iter_mut()
-            .filter(|task| task.name.contains(&task_name))
+            .filter(|task| matches(&task.name, &task_name))
             .collect();
         let task = tasks.pop();
         if !tasks.is_empty() {
@@ -522,21 +799,366 @@ impl TaskManager {
         let running_task = self
             .running
             .as_mut()
-            .filter(|task| task.name.contains(&task_name));
-        match (task, running_task) {
-            (None, None) => Err(TaskError::TaskNotFound(task_name)),
-            (Some(task), None) => {
-                let task_name = mem::replace(&mut task.name, new_name.clone());
-                Ok((task_name, new_name))
+            .filter(|task| matches(&task.name, &task_name));
+        let task_name = match (task, running_task) {
+            (None, None) => Err(TaskError::TaskNotFound(task_name))?,
+            (Some(task), None) => mem::replace(&mut task.name, new_name.clone()),
+            (None, Some(task)) => mem::replace(&mut task.name, new_name.clone()),
+            _ => Err(TaskError::MultipleTasksFound)?,
+        };
+        self.events.push(Event::Renamed {
+            from: task_name.clone(),
+            to: new_name.clone(),
+        });
+        Ok((task_name, new_name))
+    }
+
+    /// Adds tags to and removes tags from the task with the given name. A tag both added and
+    /// removed ends up removed.
+    pub fn tag_task(
+        &mut self,
+        task_name: String,
+        add: Vec<String>,
+        remove: Vec<String>,
+    ) -> TaskResult<String> {
+        let tags = match self.stopped.iter_mut().find(|task| task.name == task_name) {
+            Some(task) => &mut task.tags,
+            None => match self.running.as_mut().filter(|task| task.name == task_name) {
+                Some(task) => &mut task.tags,
+                None => Err(TaskError::TaskNotFound(task_name.clone()))?,
+            },
+        };
+        tags.extend(add.iter().cloned());
+        tags.retain(|tag| !remove.contains(tag));
+        tags.sort();
+        tags.dedup();
+        self.events.push(Event::Tagged {
+            task: task_name.clone(),
+            added: add,
+            removed: remove,
+        });
+        Ok(task_name)
+    }
+
+    /// Adds a note to the task with the given name.
+    pub fn note_task(&mut self, task_name: String, note: String) -> TaskResult<String> {
+        let notes = match self.stopped.iter_mut().find(|task| task.name == task_name) {
+            Some(task) => &mut task.notes,
+            None => match self.running.as_mut().filter(|task| task.name == task_name) {
+                Some(task) => &mut task.notes,
+                None => Err(TaskError::TaskNotFound(task_name.clone()))?,
+            },
+        };
+        notes.push(note.clone());
+        self.events.push(Event::Noted {
+            task: task_name.clone(),
+            index: None,
+            note,
+        });
+        Ok(task_name)
+    }
+
+    /// Sets the note of the completed entry at `index` of the given task, replacing any note it
+    /// had.
+    pub fn note_entry(
+        &mut self,
+        task_name: String,
+        index: usize,
+        note: String,
+    ) -> TaskResult<String> {
+        self.entry(&task_name, index)?;
+        let entry = match self.stopped.iter_mut().find(|task| task.name == task_name) {
+            Some(task) => task
+                .entries
+                .iter_mut()
+                .chain([&mut task.last_entry])
+                .nth(index),
+            None => self
+                .running
+                .as_mut()
+                .and_then(|task| task.entries.get_mut(index)),
+        };
+        entry.expect("entry should exist").note = Some(note.clone());
+        self.events.push(Event::Noted {
+            task: task_name.clone(),
+            index: Some(index),
+            note,
+        });
+        Ok(task_name)
+    }
+
+    /// Adds a completed time entry to the task with the given name, creating the task if there
+    /// is none. The entry must not overlap with any other entry of the day.
+    pub fn add_entry(
+        &mut self,
+        task_name: String,
+        start: DateTime<Local>,
+        end: DateTime<Local>,
+    ) -> TaskResult<String> {
+        if start > end {
+            return Err(TaskError::InvalidTimeRange);
+        }
+        self.check_no_overlap(start, end)?;
+        self.insert_entry(&task_name, CompletedTimeEntry::new(start, end))?;
+        self.events.push(Event::Added {
+            task: task_name.clone(),
+            start,
+            end,
+        });
+        Ok(task_name)
+    }
+
+    /// Inserts an entry into the task with the given name in chronological order, creating the
+    /// task if there is none.
+    ///
+    /// ### Preconditions
+    /// - The entry must not overlap with any other entry of the day.
+    fn insert_entry(&mut self, task_name: &str, entry: CompletedTimeEntry) -> TaskResult<()> {
+        match &mut self.running {
+            Some(task) if task.name == task_name => {
+                let index = task
+                    .entries
+                    .partition_point(|other| other.start < entry.start);
+                task.entries.insert(index, entry);
             }
-            (None, Some(task)) => {
-                let task_name = mem::replace(&mut task.name, new_name.clone());
-                Ok((task_name, new_name))
+            _ => {
+                let task = match self.index_of(|task| task.name == task_name)? {
+                    Some(index) => {
+                        let mut task = self.stopped.remove(index);
+                        let mut entries = mem::take(&mut task.entries);
+                        entries.push(task.last_entry);
+                        let index = entries.partition_point(|other| other.start < entry.start);
+                        entries.insert(index, entry);
+                        task.last_entry = entries.pop().expect("entries should not be empty");
+                        task.entries = entries;
+                        task
+                    }
+                    None => StoppedTask {
+                        name: task_name.to_string(),
+                        tags: vec![],
+                        notes: vec![],
+                        entries: vec![],
+                        last_entry: entry,
+                    },
+                };
+                self.insert_stopped(task);
             }
+        }
+        Ok(())
+    }
+
+    /// Inserts a stopped task, keeping the stopped tasks ordered by stop time.
+    fn insert_stopped(&mut self, task: StoppedTask) {
+        let index = self
+            .stopped
+            .partition_point(|other| other.stop_time() <= task.stop_time());
+        self.stopped.insert(index, task);
+    }
+
+    /// Removes the completed entry at `index` from the task with the given name. A stopped task
+    /// left without entries is removed too.
+    fn take_entry(&mut self, task_name: &str, index: usize) -> TaskResult<CompletedTimeEntry> {
+        if let Some(task) = self.running.as_mut().filter(|task| task.name == task_name) {
+            return match index.cmp(&task.entries.len()) {
+                Ordering::Less => Ok(task.entries.remove(index)),
+                Ordering::Equal => Err(TaskError::EntryRunning(index + 1, task_name.to_string())),
+                Ordering::Greater => {
+                    Err(TaskError::EntryNotFound(index + 1, task_name.to_string()))
+                }
+            };
+        }
+        let position = self
+            .index_of(|task| task.name == task_name)?
+            .ok_or_else(|| TaskError::TaskNotFound(task_name.to_string()))?;
+        if index > self.stopped[position].entries.len() {
+            return Err(TaskError::EntryNotFound(index + 1, task_name.to_string()));
+        }
+        let mut task = self.stopped.remove(position);
+        let mut entries = mem::take(&mut task.entries);
+        entries.push(task.last_entry);
+        let entry = entries.remove(index);
+        if let Some(last_entry) = entries.pop() {
+            self.insert_stopped(StoppedTask {
+                name: task.name,
+                tags: task.tags,
+                notes: task.notes,
+                entries,
+                last_entry,
+            });
+        }
+        Ok(entry)
+    }
+
+    /// Returns the tags and notes of the stopped task with the given name, to give back with
+    /// [Self::restore_details] once the entries of the task have been reinserted.
+    fn details(&self, task_name: &str) -> (Vec<String>, Vec<String>) {
+        self.stopped
+            .iter()
+            .find(|task| task.name == task_name)
+            .map_or_else(Default::default, |task| {
+                (task.tags.clone(), task.notes.clone())
+            })
+    }
+
+    /// Gives the stopped task with the given name the tags and notes it had before
+    /// [Self::take_entry] took its only entry.
+    fn restore_details(&mut self, task_name: &str, (tags, notes): (Vec<String>, Vec<String>)) {
+        if let Some(task) = self.stopped.iter_mut().find(|task| task.name == task_name) {
+            task.tags = tags;
+            task.notes = notes;
+        }
+    }
+
+    /// Returns the start and end of the completed entry at `index` of the given task.
+    pub fn entry(
+        &self,
+        task_name: &str,
+        index: usize,
+    ) -> TaskResult<(DateTime<Local>, DateTime<Local>)> {
+        let record = self.task_record(task_name)?;
+        match record.entries.get(index) {
+            Some(&entry) => Ok(entry),
+            None if index == record.entries.len() && record.running_since.is_some() => {
+                Err(TaskError::EntryRunning(index + 1, record.name))
+            }
+            None => Err(TaskError::EntryNotFound(index + 1, record.name)),
+        }
+    }
+
+    /// Returns the name of the only task whose name contains `task_name`.
+    pub fn find_task(&self, task_name: &str) -> TaskResult<String> {
+        let mut names = self
+            .list_tasks()
+            .into_iter()
+            .filter(|name| name.contains(task_name));
+        match (names.next(), names.next()) {
+            (None, _) => Err(TaskError::TaskNotFound(task_name.to_string())),
+            (Some(name), None) => Ok(name.to_string()),
             _ => Err(TaskError::MultipleTasksFound),
         }
     }
 
+    /// Returns the record of the task with the given name. The entry commands number its entries
+    /// in this order, from 0.
+    pub fn task_record(&self, task_name: &str) -> TaskResult<TaskRecord> {
+        self.to_records()
+            .into_iter()
+            .find(|record| record.name == task_name)
+            .ok_or_else(|| TaskError::TaskNotFound(task_name.to_string()))
+    }
+
+    /// Changes the start and end of the completed entry at `index` of the given task. The entry
+    /// must not overlap with any other entry of the day.
+    pub fn edit_entry(
+        &mut self,
+        task_name: String,
+        index: usize,
+        start: DateTime<Local>,
+        end: DateTime<Local>,
+    ) -> TaskResult<String> {
+        if start > end {
+            return Err(TaskError::InvalidTimeRange);
+        }
+        let details = self.details(&task_name);
+        let entry = self.take_entry(&task_name, index)?;
+        if let Err(e) = self.check_no_overlap(start, end) {
+            self.insert_entry(&task_name, entry)?;
+            self.restore_details(&task_name, details);
+            return Err(e);
+        }
+        let edited = CompletedTimeEntry::new(start, end).with_note(entry.note);
+        self.insert_entry(&task_name, edited)?;
+        self.restore_details(&task_name, details);
+        self.events.push(Event::EntryEdited {
+            task: task_name.clone(),
+            index,
+            start,
+            end,
+        });
+        Ok(task_name)
+    }
+
+    /// Deletes the completed entry at `index` of the given task, and the task if it was its only
+    /// entry.
+    pub fn delete_entry(&mut self, task_name: String, index: usize) -> TaskResult<String> {
+        self.take_entry(&task_name, index)?;
+        self.events.push(Event::EntryDeleted {
+            task: task_name.clone(),
+            index,
+        });
+        Ok(task_name)
+    }
+
+    /// Splits the completed entry at `index` of the given task in two at the given time. Both
+    /// halves keep the note of the entry.
+    pub fn split_entry(
+        &mut self,
+        task_name: String,
+        index: usize,
+        at: DateTime<Local>,
+    ) -> TaskResult<String> {
+        let (start, end) = self.entry(&task_name, index)?;
+        if at <= start || end <= at {
+            return Err(TaskError::InvalidSplitTime);
+        }
+        let details = self.details(&task_name);
+        let entry = self.take_entry(&task_name, index)?;
+        let first = CompletedTimeEntry::new(entry.start, at).with_note(entry.note.clone());
+        self.insert_entry(&task_name, first)?;
+        let second = CompletedTimeEntry::new(at, entry.end).with_note(entry.note);
+        self.insert_entry(&task_name, second)?;
+        self.restore_details(&task_name, details);
+        self.events.push(Event::EntrySplit {
+            task: task_name.clone(),
+            index,
+            at,
+        });
+        Ok(task_name)
+    }
+
+    /// Moves the completed entry at `index` of the given task to the task named `to`, creating it
+    /// if there is none. When the task's only entry is moved back onto it or onto a new task, its
+    /// tags and notes go along.
+    pub fn move_entry(
+        &mut self,
+        task_name: String,
+        index: usize,
+        to: String,
+    ) -> TaskResult<(String, String)> {
+        self.entry(&task_name, index)?;
+        self.index_of(|task| task.name == to)?;
+        let details = self.details(&task_name);
+        let created = self.task_record(&to).is_err();
+        let entry = self.take_entry(&task_name, index)?;
+        let emptied = self.task_record(&task_name).is_err();
+        self.insert_entry(&to, entry)?;
+        if emptied && (created || to == task_name) {
+            self.restore_details(&to, details);
+        }
+        self.events.push(Event::EntryMoved {
+            task: task_name.clone(),
+            index,
+            to: to.clone(),
+        });
+        Ok((task_name, to))
+    }
+
+    /// Checks that the time from `start` to `end` doesn't overlap with any entry of the day.
+    fn check_no_overlap(&self, start: DateTime<Local>, end: DateTime<Local>) -> TaskResult<()> {
+        let overlaps = |entry: &CompletedTimeEntry| start < entry.end && entry.start < end;
+        for task in &self.stopped {
+            if task.entries.iter().chain([&task.last_entry]).any(overlaps) {
+                return Err(TaskError::OverlappingEntry(task.name.clone()));
+            }
+        }
+        if let Some(task) = &self.running {
+            if task.entries.iter().any(overlaps) || task.last_entry.start < end {
+                return Err(TaskError::OverlappingEntry(task.name.clone()));
+            }
+        }
+        Ok(())
+    }
+
     /// Returns a list of all tasks.
     pub fn list_tasks(&self) -> Vec<&str> {
         let mut tasks: Vec<_> = self.stopped.iter().map(|task| task.name.as_str()).collect();
@@ -546,74 +1168,290 @@ impl TaskManager {
         tasks
     }
 
-    /// Generates a report of the tasks.
-    pub fn generate_report(&self, date: NaiveDate, time: DateTime<Local>) -> String {
-        let mut report = format!("  {} \n", date.format("%F"));
-        let total = self.stopped.iter().fold(
-            self.running
-                .as_ref()
-                .map(|task| task.time_spent(time))
-                .unwrap_or_default(),
-            |total, task| total + task.time_spent(),
-        );
-        let max_length = self
+    /// Returns the tags of the task with the given name, which are empty if there is no such
+    /// task.
+    pub fn tags(&self, task_name: &str) -> &[String] {
+        let running = self.running.iter().map(|task| (&task.name, &task.tags));
+        self.stopped
+            .iter()
+            .map(|task| (&task.name, &task.tags))
+            .chain(running)
+            .find(|(name, _)| *name == task_name)
+            .map_or(&[], |(_, tags)| tags)
+    }
+
+    /// Returns the time spent on each task, in the order of [TaskManager::list_tasks].
+    pub fn times(&self, now: DateTime<Local>) -> Vec<(&str, Duration)> {
+        let mut times: Vec<_> = self
             .stopped
             .iter()
-            .map(|task| task.name.len())
-            .max()
-            .unwrap_or(0)
-            .max(
-                self.running
-                    .as_ref()
-                    .map(|task| task.name.len())
-                    .unwrap_or(0),
-            )
-            .max(5);
-        for task in &self.stopped {
-            let time = task.time_spent();
-            let percent = percent(
-                time.num_milliseconds() as u32,
-                total.num_milliseconds() as u32,
-            );
-            report += &format!(
-                "    {:<max_length$} | {} | {percent:>5.1}%\n",
-                task.name,
-                format_duration(time)
-            );
-        }
+            .map(|task| (task.name.as_str(), task.time_spent()))
+            .collect();
         if let Some(task) = &self.running {
-            let time = task.time_spent(time);
-            let percent = percent(
-                time.num_milliseconds() as u32,
-                total.num_milliseconds() as u32,
-            );
-            report += &format!(
-                "    {:<max_length$} | {} | {percent:>5.1}%\n",
-                task.name,
-                format_duration(time)
-            )
-            .green()
-            .bold()
-            .to_string();
-        }
-        report += &format!("    {:=>1$}\n", "", max_length + 17);
-        report += &format!(
-            "    {:<max_length$} | {} | 100.0%\n",
-            "Total",
-            format_duration(total)
-        );
-        report
+            times.push((task.name.as_str(), task.time_spent(now)));
+        }
+        times
+    }
+
+    /// Returns the events recorded since the last call, oldest first.
+    pub fn take_events(&mut self) -> Vec<Event> {
+        mem::take(&mut self.events)
     }
+
+    /// Applies an event, which is recorded like any other mutation.
+    pub fn apply(&mut self, event: Event) -> TaskResult<()> {
+        match event {
+            Event::Started { task, at } => {
+                self.start_new_task(task, at)?;
+            }
+            Event::Stopped { at } => {
+                self.stop_running_task_with_time(at)?;
+            }
+            Event::Resumed { task, at } => {
+                self.check_no_running_task()?;
+                match self.index_of(|stopped| stopped.name == task)? {
+                    None => Err(TaskError::TaskNotFound(task))?,
+                    Some(index) => {
+                        self.check_can_start(index, at)?;
+                        self.check_after_entries(at)?;
+                        self.do_resume_task(index, at);
+                    }
+                }
+            }
+            Event::Added { task, start, end } => {
+                self.add_entry(task, start, end)?;
+            }
+            Event::EntryEdited {
+                task,
+                index,
+                start,
+                end,
+            } => {
+                self.edit_entry(task, index, start, end)?;
+            }
+            Event::EntryDeleted { task, index } => {
+                self.delete_entry(task, index)?;
+            }
+            Event::EntrySplit { task, index, at } => {
+                self.split_entry(task, index, at)?;
+            }
+            Event::EntryMoved { task, index, to } => {
+                self.move_entry(task, index, to)?;
+            }
+            Event::Renamed { from, to } => {
+                self.rename_task_matching(from, to, |task, name| task == name)?;
+            }
+            Event::Tagged {
+                task,
+                added,
+                removed,
+            } => {
+                self.tag_task(task, added, removed)?;
+            }
+            Event::Noted { task, index, note } => match index {
+                None => {
+                    self.note_task(task, note)?;
+                }
+                Some(index) => {
+                    self.note_entry(task, index, note)?;
+                }
+            },
+            Event::Deleted { task } => {
+                self.delete_task_matching(task, |task, name| task == name)?;
+            }
+            Event::Snapshot { tasks } => {
+                self.stopped = tasks.stopped.clone();
+                self.running = tasks.running.clone();
+                self.events.push(Event::Snapshot { tasks });
+            }
+        }
+        Ok(())
+    }
+
+    /// Rebuilds the tasks of a day by applying the given events to an empty task manager.
+    pub fn replay(events: impl IntoIterator<Item = Event>) -> TaskResult<Self> {
+        let mut task_manager = TaskManager::new();
+        for event in events {
+            task_manager.apply(event)?;
+        }
+        task_manager.take_events();
+        Ok(task_manager)
+    }
+
+    /// Returns the tasks as records: the stopped tasks in order, followed by the running task.
+    pub fn to_records(&self) -> Vec<TaskRecord> {
+        let mut records: Vec<_> = self.stopped.iter().map(TaskRecord::from).collect();
+        records.extend(self.running.as_ref().map(TaskRecord::from));
+        records
+    }
+
+    /// Builds a task manager from records, checking the same invariants as deserialization.
+    pub fn from_records(records: Vec<TaskRecord>) -> Result<Self, String> {
+        let mut task_manager = TaskManager::new();
+        for record in records {
+            let mut notes = record.entry_notes.into_iter();
+            let mut entries = record
+                .entries
+                .into_iter()
+                .map(|(start, end)| {
+                    let note = notes.next().flatten();
+                    CompletedTimeEntryDeser { start, end, note }.try_into()
+                })
+                .collect::<Result<Vec<CompletedTimeEntry>, _>>()?;
+            match record.running_since {
+                Some(start) => {
+                    if task_manager.running.is_some() {
+                        Err("only one task can be running")?;
+                    }
+                    let deser = TaskDeser {
+                        name: record.name,
+                        tags: record.tags,
+                        notes: record.notes,
+                        entries,
+                        last_entry: OngoingTimeEntry::new(start),
+                    };
+                    task_manager.running = Some(deser.try_into()?);
+                }
+                None => {
+                    let last_entry = entries
+                        .pop()
+                        .ok_or_else(|| format!("stopped task '{}' has no entries", record.name))?;
+                    let deser = TaskDeser {
+                        name: record.name,
+                        tags: record.tags,
+                        notes: record.notes,
+                        entries,
+                        last_entry,
+                    };
+                    task_manager.stopped.push(deser.try_into()?);
+                }
+            }
+        }
+        Ok(task_manager)
+    }
+
+    /// Reports the time spent on the tasks, as set out by the `options`.
+    ///
+    /// When reporting by tag, a task counts towards each of its tags, so the rows can add up to
+    /// more than the total. Tasks without tags are reported as `(untagged)`.
+    pub fn report(
+        &self,
+        date: NaiveDate,
+        time: DateTime<Local>,
+        options: &ReportOptions,
+    ) -> Report {
+        let running_index = self.running.as_ref().map(|_| self.stopped.len());
+        let mut rows: Vec<ReportRow> = vec![];
+        let mut timeline = vec![];
+        let mut total = Duration::zero();
+        let tasks = self.times(time).into_iter().zip(self.to_records());
+        for (index, ((name, spent), record)) in tasks.enumerate() {
+            let tags = self.tags(name);
+            if !options.tags.iter().all(|tag| tags.contains(tag)) {
+                continue;
+            }
+            total += spent;
+            let running = Some(index) == running_index;
+            let task_name = name;
+            let entries = record
+                .entries
+                .iter()
+                .map(|&(start, end)| (start, end, false));
+            let running_entry = record.running_since.map(|start| (start, time, true));
+            for (start, end, running) in entries.chain(running_entry) {
+                timeline.push(TimelineEntry {
+                    date,
+                    task: name.to_string(),
+                    start,
+                    end,
+                    running,
+                });
+            }
+            let notes = if options.verbose {
+                note_lines(&record)
+            } else {
+                vec![]
+            };
+            let names = if options.by_tag && tags.is_empty() {
+                vec!["(untagged)".to_string()]
+            } else if options.by_tag {
+                tags.to_vec()
+            } else if let Some(depth) = options.depth {
+                vec![task_path::ancestor(name, depth)]
+            } else {
+                vec![name.to_string()]
+            };
+            for name in names {
+                match rows.iter_mut().find(|row| row.name == name) {
+                    Some(row) => {
+                        row.time += spent;
+                        row.budget = None;
+                        row.running |= running;
+                        row.notes.extend(notes.iter().cloned());
+                    }
+                    None => rows.push(ReportRow {
+                        budget: options
+                            .budgets
+                            .get(task_name)
+                            .copied()
+                            .filter(|_| !options.by_tag && name == task_name),
+                        name,
+                        time: spent,
+                        percent: 0.0,
+                        average: None,
+                        running,
+                        notes: notes.clone(),
+                    }),
+                }
+            }
+        }
+        for row in &mut rows {
+            row.percent = report::percent(row.time, total);
+        }
+        timeline.sort_by_key(|entry| entry.start);
+        Report {
+            date,
+            to: None,
+            rows,
+            total,
+            average: None,
+            timeline,
+        }
+    }
+
+    /// Generates a report of the tasks as the text printed on the terminal, see [Self::report].
+    pub fn generate_report(
+        &self,
+        date: NaiveDate,
+        time: DateTime<Local>,
+        options: &ReportOptions,
+    ) -> String {
+        self.report(date, time, options).to_string()
+    }
+}
+
+/// Returns the notes of a task followed by the notes of its entries, which are prefixed with the
+/// time of the entry.
+fn note_lines(record: &TaskRecord) -> Vec<String> {
+    let entry_notes =
+        record
+            .entries
+            .iter()
+            .zip(&record.entry_notes)
+            .filter_map(|((start, end), note)| {
+                let note = note.as_ref()?;
+                Some(format!(
+                    "{}-{} {note}",
+                    start.format("%H:%M"),
+                    end.format("%H:%M")
+                ))
+            });
+    record.notes.iter().cloned().chain(entry_notes).collect()
 }
 
 /// Formats a duration in hours and minutes.
-fn format_duration(duration: Duration) -> String {
+pub(crate) fn format_duration(duration: Duration) -> String {
     let minutes = duration.num_minutes() % 60;
     let hours = duration.num_hours();
     format!("{hours:0>2}:{minutes:0>2}")
 }
-
-/// Calculates the percentage of a number.
-fn percent(numerator: u32, denominator: u32) -> f64 {
-    numerator as f64 / denominator as f64 * 100.0
-}
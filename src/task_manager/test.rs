@@ -36,6 +36,8 @@ mod running_task {
         let after = Local::now() + Duration::minutes(10);
         let task = RunningTask {
             name: "Test".to_string(),
+            tags: vec![],
+            notes: vec![],
             entries: vec![],
             last_entry: OngoingTimeEntry::new(before),
         }
@@ -53,6 +55,8 @@ mod running_task {
         let before = Local::now() - Duration::minutes(10);
         RunningTask {
             name: "Test".to_string(),
+            tags: vec![],
+            notes: vec![],
             entries: vec![],
             last_entry: OngoingTimeEntry::new(now),
         }
@@ -65,6 +69,8 @@ mod running_task {
         let after = Local::now() + Duration::minutes(10);
         let task = RunningTask {
             name: "Test".to_string(),
+            tags: vec![],
+            notes: vec![],
             entries: vec![],
             last_entry: OngoingTimeEntry::new(before),
         };
@@ -76,6 +82,8 @@ mod running_task {
         let start = Local::now();
         let task = RunningTask {
             name: "Test".to_string(),
+            tags: vec![],
+            notes: vec![],
             entries: vec![
                 CompletedTimeEntry::new(start, start + Duration::minutes(1)),
                 CompletedTimeEntry::new(start + Duration::minutes(2), start + Duration::minutes(4)),
@@ -98,6 +106,8 @@ mod stopped_task {
         let now = Local::now() + Duration::minutes(20);
         let task = StoppedTask {
             name: "Test".to_string(),
+            tags: vec![],
+            notes: vec![],
             entries: vec![],
             last_entry: time_entry.clone(),
         }
@@ -115,6 +125,8 @@ mod stopped_task {
         let now = Local::now() + Duration::minutes(5);
         StoppedTask {
             name: "Test".to_string(),
+            tags: vec![],
+            notes: vec![],
             entries: vec![],
             last_entry: time_entry.clone(),
         }
@@ -127,6 +139,8 @@ mod stopped_task {
         let end = start + Duration::minutes(10);
         let task = StoppedTask {
             name: "Test".to_string(),
+            tags: vec![],
+            notes: vec![],
             entries: vec![
                 CompletedTimeEntry::new(start, start + Duration::minutes(1)),
                 CompletedTimeEntry::new(start + Duration::minutes(2), start + Duration::minutes(4)),
@@ -143,6 +157,8 @@ mod stopped_task {
         let after = Local::now() + Duration::minutes(10);
         let task = StoppedTask {
             name: "Test".to_string(),
+            tags: vec![],
+            notes: vec![],
             entries: vec![],
             last_entry: CompletedTimeEntry::new(before, after),
         };
@@ -154,6 +170,8 @@ mod stopped_task {
         let start = Local::now();
         let task = StoppedTask {
             name: "Test".to_string(),
+            tags: vec![],
+            notes: vec![],
             entries: vec![
                 CompletedTimeEntry::new(start, start + Duration::minutes(1)),
                 CompletedTimeEntry::new(start + Duration::minutes(2), start + Duration::minutes(4)),
@@ -242,7 +260,7 @@ mod completed_time_entry {
     fn test_completed_time_entry_duration() {
         let start = Local::now();
         let end = start + Duration::minutes(10);
-        let time_entry = CompletedTimeEntry { start, end };
+        let time_entry = CompletedTimeEntry::new(start, end);
         assert_eq!(time_entry.duration().num_minutes(), 10);
     }
 }
@@ -262,6 +280,7 @@ mod task_manager {
         let mut task_manager = TaskManager {
             stopped: vec![],
             running: None,
+            ..Default::default()
         };
         let now = Local::now();
         let result = task_manager.start_new_task("Test".to_string(), now);
@@ -277,12 +296,15 @@ mod task_manager {
         let now = Local::now();
         let stopped_task = StoppedTask {
             name: "OtherTest".to_string(),
+            tags: vec![],
+            notes: vec![],
             entries: vec![],
             last_entry: CompletedTimeEntry::new(now, now + Duration::minutes(10)),
         };
         let mut task_manager = TaskManager {
             stopped: vec![stopped_task.clone()],
             running: None,
+            ..Default::default()
         };
         let result = task_manager.start_new_task("Test".to_string(), now + Duration::minutes(20));
         assert_eq!(
@@ -295,6 +317,27 @@ mod task_manager {
         assert_eq!(task_name, "Test");
     }
 
+    #[test]
+    fn test_task_manager_start_new_task_when_before_other_ends() {
+        let now = Local::now();
+        let stopped_task = StoppedTask {
+            name: "OtherTest".to_string(),
+            tags: vec![],
+            notes: vec![],
+            entries: vec![],
+            last_entry: CompletedTimeEntry::new(now, now + Duration::minutes(10)),
+        };
+        let mut task_manager = TaskManager {
+            stopped: vec![stopped_task.clone()],
+            running: None,
+            ..Default::default()
+        };
+        let result = task_manager.start_new_task("Test".to_string(), now + Duration::minutes(5));
+        assert!(task_manager.running.is_none());
+        assert_eq!(task_manager.stopped, vec![stopped_task]);
+        assert!(matches!(result, Err(TaskError::StartBeforeEntryEnd(name)) if name == "OtherTest"));
+    }
+
     #[test]
     fn test_task_manager_start_new_task_when_already_running() {
         let now = Local::now();
@@ -302,6 +345,7 @@ mod task_manager {
         let mut task_manager = TaskManager {
             stopped: vec![],
             running: Some(task.clone()),
+            ..Default::default()
         };
         let result = task_manager.start_new_task("Test2".to_string(), now + Duration::minutes(10));
         assert_eq!(task_manager.running, Some(task));
@@ -316,12 +360,15 @@ mod task_manager {
         let now = Local::now();
         let stopped_task = StoppedTask {
             name: "Test".to_string(),
+            tags: vec![],
+            notes: vec![],
             entries: vec![],
             last_entry: CompletedTimeEntry::new(now, now + Duration::minutes(10)),
         };
         let mut task_manager = TaskManager {
             stopped: vec![stopped_task.clone()],
             running: None,
+            ..Default::default()
         };
         let result = task_manager.start_new_task("Test".to_string(), now + Duration::minutes(20));
         assert!(task_manager.running.is_none());
@@ -338,6 +385,7 @@ mod task_manager {
         let mut task_manager = TaskManager {
             stopped: vec![],
             running: Some(task.clone()),
+            ..Default::default()
         };
         let result = task_manager.stop_running_task_with_time(now + Duration::minutes(10));
         assert!(task_manager.running.is_none());
@@ -357,6 +405,7 @@ mod task_manager {
         let mut task_manager = TaskManager {
             stopped: vec![],
             running: Some(task.clone()),
+            ..Default::default()
         };
         let result = task_manager.stop_running_task_with_time(now - Duration::minutes(10));
         assert_eq!(task_manager.running, Some(task));
@@ -372,6 +421,7 @@ mod task_manager {
         let mut task_manager = TaskManager {
             stopped: vec![],
             running: None,
+            ..Default::default()
         };
         let result = task_manager.stop_running_task_with_time(now + Duration::minutes(10));
         assert!(task_manager.running.is_none());
@@ -388,6 +438,7 @@ mod task_manager {
         let mut task_manager = TaskManager {
             stopped: vec![],
             running: Some(task.clone()),
+            ..Default::default()
         };
         let result = task_manager
             .stop_running_task_with_duration(Duration::minutes(10), now + Duration::minutes(20));
@@ -407,6 +458,7 @@ mod task_manager {
         let mut task_manager = TaskManager {
             stopped: vec![],
             running: None,
+            ..Default::default()
         };
         let result = task_manager
             .stop_running_task_with_duration(Duration::minutes(10), now + Duration::minutes(20));
@@ -424,6 +476,7 @@ mod task_manager {
         let mut task_manager = TaskManager {
             stopped: vec![],
             running: Some(task.clone()),
+            ..Default::default()
         };
         let result = task_manager
             .stop_running_task_with_duration(Duration::minutes(20), now + Duration::minutes(10));
@@ -439,6 +492,7 @@ mod task_manager {
         let mut task_manager = TaskManager {
             stopped: vec![],
             running: None,
+            ..Default::default()
         };
         let result = task_manager.resume_last_task(Local::now());
         assert!(task_manager.running.is_none());
@@ -455,6 +509,7 @@ mod task_manager {
         let mut task_manager = TaskManager {
             stopped: vec![],
             running: Some(task.clone()),
+            ..Default::default()
         };
         let result = task_manager.resume_last_task(now + Duration::minutes(10));
         assert!(task_manager.stopped.is_empty());
@@ -472,17 +527,22 @@ mod task_manager {
             CompletedTimeEntry::new(now + Duration::minutes(6), now + Duration::minutes(7));
         let task1 = StoppedTask {
             name: "Test1".to_string(),
+            tags: vec![],
+            notes: vec![],
             entries: vec![],
             last_entry: time_entry1.clone(),
         };
         let task2 = StoppedTask {
             name: "Test2".to_string(),
+            tags: vec![],
+            notes: vec![],
             entries: vec![],
             last_entry: time_entry2.clone(),
         };
         let mut task_manager = TaskManager {
             stopped: vec![task1.clone(), task2.clone()],
             running: None,
+            ..Default::default()
         };
         let result = task_manager.resume_last_task(now - Duration::minutes(10));
         assert_eq!(task_manager.stopped, vec![task1, task2]);
@@ -500,17 +560,22 @@ mod task_manager {
             CompletedTimeEntry::new(now + Duration::minutes(6), now + Duration::minutes(7));
         let task1 = StoppedTask {
             name: "Test1".to_string(),
+            tags: vec![],
+            notes: vec![],
             entries: vec![],
             last_entry: time_entry1.clone(),
         };
         let task2 = StoppedTask {
             name: "Test2".to_string(),
+            tags: vec![],
+            notes: vec![],
             entries: vec![],
             last_entry: time_entry2.clone(),
         };
         let mut task_manager = TaskManager {
             stopped: vec![task1.clone(), task2.clone()],
             running: None,
+            ..Default::default()
         };
         let result = task_manager.resume_last_task(now + Duration::minutes(10));
         assert_eq!(task_manager.stopped, vec![task1]);
@@ -528,6 +593,7 @@ mod task_manager {
         let mut task_manager = TaskManager {
             stopped: vec![],
             running: None,
+            ..Default::default()
         };
         let result = task_manager.resume_task("Test".to_string(), Local::now());
         assert!(task_manager.running.is_none());
@@ -544,6 +610,7 @@ mod task_manager {
         let mut task_manager = TaskManager {
             stopped: vec![],
             running: Some(task.clone()),
+            ..Default::default()
         };
         let result = task_manager.resume_task("Test".to_string(), now + Duration::minutes(10));
         assert!(task_manager.stopped.is_empty());
@@ -561,17 +628,22 @@ mod task_manager {
             CompletedTimeEntry::new(now + Duration::minutes(6), now + Duration::minutes(7));
         let task1 = StoppedTask {
             name: "Test10".to_string(),
+            tags: vec![],
+            notes: vec![],
             entries: vec![],
             last_entry: time_entry1.clone(),
         };
         let task2 = StoppedTask {
             name: "Test2".to_string(),
+            tags: vec![],
+            notes: vec![],
             entries: vec![],
             last_entry: time_entry2.clone(),
         };
         let mut task_manager = TaskManager {
             stopped: vec![task1.clone(), task2.clone()],
             running: None,
+            ..Default::default()
         };
         let result = task_manager.resume_task("Test1".to_string(), now + Duration::minutes(10));
         assert_eq!(task_manager.stopped, vec![task2]);
@@ -584,6 +656,37 @@ mod task_manager {
         assert_eq!(task_name, "Test10");
     }
 
+    #[test]
+    fn test_task_manager_resume_task_when_before_other_ends() {
+        let now = Local::now();
+        let task1 = StoppedTask {
+            name: "Test1".to_string(),
+            tags: vec![],
+            notes: vec![],
+            entries: vec![],
+            last_entry: CompletedTimeEntry::new(now, now + Duration::minutes(5)),
+        };
+        let task2 = StoppedTask {
+            name: "Test2".to_string(),
+            tags: vec![],
+            notes: vec![],
+            entries: vec![],
+            last_entry: CompletedTimeEntry::new(
+                now + Duration::minutes(6),
+                now + Duration::minutes(10),
+            ),
+        };
+        let mut task_manager = TaskManager {
+            stopped: vec![task1.clone(), task2.clone()],
+            running: None,
+            ..Default::default()
+        };
+        let result = task_manager.resume_task("Test1".to_string(), now + Duration::minutes(8));
+        assert_eq!(task_manager.stopped, vec![task1, task2]);
+        assert_eq!(task_manager.running, None);
+        assert!(matches!(result, Err(TaskError::StartBeforeEntryEnd(name)) if name == "Test2"));
+    }
+
     #[test]
     fn test_task_manager_resume_task_when_none_running_and_now_before_start() {
         let now = Local::now();
@@ -592,17 +695,22 @@ mod task_manager {
             CompletedTimeEntry::new(now + Duration::minutes(6), now + Duration::minutes(7));
         let task1 = StoppedTask {
             name: "Test10".to_string(),
+            tags: vec![],
+            notes: vec![],
             entries: vec![],
             last_entry: time_entry1.clone(),
         };
         let task2 = StoppedTask {
             name: "Test2".to_string(),
+            tags: vec![],
+            notes: vec![],
             entries: vec![],
             last_entry: time_entry2.clone(),
         };
         let mut task_manager = TaskManager {
             stopped: vec![task1.clone(), task2.clone()],
             running: None,
+            ..Default::default()
         };
         let result = task_manager.resume_task("Test1".to_string(), now - Duration::minutes(10));
         assert_eq!(task_manager.stopped, vec![task1, task2]);
@@ -620,17 +728,22 @@ mod task_manager {
             CompletedTimeEntry::new(now + Duration::minutes(6), now + Duration::minutes(7));
         let task1 = StoppedTask {
             name: "Test1".to_string(),
+            tags: vec![],
+            notes: vec![],
             entries: vec![],
             last_entry: time_entry1.clone(),
         };
         let task2 = StoppedTask {
             name: "Test2".to_string(),
+            tags: vec![],
+            notes: vec![],
             entries: vec![],
             last_entry: time_entry2.clone(),
         };
         let mut task_manager = TaskManager {
             stopped: vec![task1.clone(), task2.clone()],
             running: None,
+            ..Default::default()
         };
         let result = task_manager.resume_task("Test".to_string(), now + Duration::minutes(10));
         assert_eq!(task_manager.stopped, vec![task1, task2]);
@@ -645,6 +758,7 @@ mod task_manager {
         let mut task_manager = TaskManager {
             stopped: vec![],
             running: None,
+            ..Default::default()
         };
         let result = task_manager.switch_new_task("Test".to_string(), Local::now());
         assert!(task_manager.running.is_none());
@@ -659,6 +773,8 @@ mod task_manager {
         let now = Local::now();
         let task1 = StoppedTask {
             name: "Test".to_string(),
+            tags: vec![],
+            notes: vec![],
             entries: vec![],
             last_entry: CompletedTimeEntry::new(now, now + Duration::minutes(10)),
         };
@@ -666,6 +782,7 @@ mod task_manager {
         let mut task_manager = TaskManager {
             stopped: vec![task1.clone()],
             running: Some(task2.clone()),
+            ..Default::default()
         };
         let result = task_manager.switch_new_task("Test".to_string(), now + Duration::minutes(20));
         assert_eq!(task_manager.running, Some(task2));
@@ -675,11 +792,35 @@ mod task_manager {
         assert!(matches!(error, TaskError::TaskAlreadyExists(name) if name == "Test"));
     }
 
+    #[test]
+    fn test_task_manager_switch_new_task_when_before_other_ends() {
+        let now = Local::now();
+        let stopped_task = StoppedTask {
+            name: "Test1".to_string(),
+            tags: vec![],
+            notes: vec![],
+            entries: vec![],
+            last_entry: CompletedTimeEntry::new(now, now + Duration::minutes(10)),
+        };
+        let running_task = RunningTask::new("Test2", now + Duration::minutes(15));
+        let mut task_manager = TaskManager {
+            stopped: vec![stopped_task.clone()],
+            running: Some(running_task.clone()),
+            ..Default::default()
+        };
+        let result = task_manager.switch_new_task("Test3".to_string(), now + Duration::minutes(5));
+        assert_eq!(task_manager.stopped, vec![stopped_task]);
+        assert_eq!(task_manager.running, Some(running_task));
+        assert!(matches!(result, Err(TaskError::StartBeforeEntryEnd(name)) if name == "Test1"));
+    }
+
     #[test]
     fn test_task_manager_switch_new_task_when_not_exists() {
         let now = Local::now();
         let task1 = StoppedTask {
             name: "Test1".to_string(),
+            tags: vec![],
+            notes: vec![],
             entries: vec![],
             last_entry: CompletedTimeEntry::new(now, now + Duration::minutes(10)),
         };
@@ -687,6 +828,7 @@ mod task_manager {
         let mut task_manager = TaskManager {
             stopped: vec![task1.clone()],
             running: Some(task2.clone()),
+            ..Default::default()
         };
         let result = task_manager.switch_new_task("Test".to_string(), now + Duration::minutes(20));
         assert_eq!(
@@ -707,6 +849,7 @@ mod task_manager {
         let mut task_manager = TaskManager {
             stopped: vec![],
             running: None,
+            ..Default::default()
         };
         let result = task_manager.switch_last_task(Local::now());
         assert!(task_manager.running.is_none());
@@ -721,12 +864,15 @@ mod task_manager {
         let now = Local::now();
         let task = StoppedTask {
             name: "Test".to_string(),
+            tags: vec![],
+            notes: vec![],
             entries: vec![],
             last_entry: CompletedTimeEntry::new(now, now + Duration::minutes(10)),
         };
         let mut task_manager = TaskManager {
             stopped: vec![task.clone()],
             running: None,
+            ..Default::default()
         };
         let result = task_manager.switch_last_task(now + Duration::minutes(20));
         assert!(task_manager.running.is_none());
@@ -743,6 +889,7 @@ mod task_manager {
         let mut task_manager = TaskManager {
             stopped: vec![],
             running: Some(task.clone()),
+            ..Default::default()
         };
         let result = task_manager.switch_last_task(now + Duration::minutes(20));
         assert_eq!(task_manager.running, Some(task));
@@ -757,6 +904,8 @@ mod task_manager {
         let now = Local::now();
         let task1 = StoppedTask {
             name: "Test1".to_string(),
+            tags: vec![],
+            notes: vec![],
             entries: vec![],
             last_entry: CompletedTimeEntry::new(now, now + Duration::minutes(10)),
         };
@@ -764,6 +913,7 @@ mod task_manager {
         let mut task_manager = TaskManager {
             stopped: vec![task1.clone()],
             running: Some(task2.clone()),
+            ..Default::default()
         };
         let result = task_manager.switch_last_task(now + Duration::minutes(20));
         assert_eq!(
@@ -784,6 +934,8 @@ mod task_manager {
         let now = Local::now();
         let task1 = StoppedTask {
             name: "Test1".to_string(),
+            tags: vec![],
+            notes: vec![],
             entries: vec![],
             last_entry: CompletedTimeEntry::new(now, now + Duration::minutes(10)),
         };
@@ -791,6 +943,7 @@ mod task_manager {
         let mut task_manager = TaskManager {
             stopped: vec![task1.clone()],
             running: Some(task2.clone()),
+            ..Default::default()
         };
         let result = task_manager.switch_last_task(now + Duration::minutes(12));
         assert_eq!(task_manager.running, Some(task2));
@@ -805,6 +958,8 @@ mod task_manager {
         let now = Local::now();
         let task1 = StoppedTask {
             name: "Test1".to_string(),
+            tags: vec![],
+            notes: vec![],
             entries: vec![],
             last_entry: CompletedTimeEntry::new(now, now + Duration::minutes(15)),
         };
@@ -812,6 +967,7 @@ mod task_manager {
         let mut task_manager = TaskManager {
             stopped: vec![task1.clone()],
             running: Some(task2.clone()),
+            ..Default::default()
         };
         let result = task_manager.switch_last_task(now + Duration::minutes(12));
         assert_eq!(task_manager.running, Some(task2));
@@ -826,6 +982,7 @@ mod task_manager {
         let mut task_manager = TaskManager {
             stopped: vec![],
             running: None,
+            ..Default::default()
         };
         let result = task_manager.switch_task("Test".to_string(), Local::now());
         assert!(task_manager.running.is_none());
@@ -840,12 +997,15 @@ mod task_manager {
         let now = Local::now();
         let stopped_task = StoppedTask {
             name: "Test".to_string(),
+            tags: vec![],
+            notes: vec![],
             entries: vec![],
             last_entry: CompletedTimeEntry::new(now, now + Duration::minutes(10)),
         };
         let mut task_manager = TaskManager {
             stopped: vec![stopped_task.clone()],
             running: None,
+            ..Default::default()
         };
         let result = task_manager.switch_task("Test".to_string(), now + Duration::minutes(20));
         assert!(task_manager.running.is_none());
@@ -860,11 +1020,15 @@ mod task_manager {
         let now = Local::now();
         let task1 = StoppedTask {
             name: "Test1".to_string(),
+            tags: vec![],
+            notes: vec![],
             entries: vec![],
             last_entry: CompletedTimeEntry::new(now, now + Duration::minutes(5)),
         };
         let task2 = StoppedTask {
             name: "Test2".to_string(),
+            tags: vec![],
+            notes: vec![],
             entries: vec![],
             last_entry: CompletedTimeEntry::new(
                 now + Duration::minutes(5),
@@ -875,6 +1039,7 @@ mod task_manager {
         let mut task_manager = TaskManager {
             stopped: vec![task1.clone(), task2.clone()],
             running: Some(task3.clone()),
+            ..Default::default()
         };
         let result = task_manager.switch_task("Test3".to_string(), now + Duration::minutes(20));
         assert_eq!(task_manager.running, Some(task3));
@@ -889,11 +1054,15 @@ mod task_manager {
         let now = Local::now();
         let task1 = StoppedTask {
             name: "Test1".to_string(),
+            tags: vec![],
+            notes: vec![],
             entries: vec![],
             last_entry: CompletedTimeEntry::new(now, now + Duration::minutes(5)),
         };
         let task2 = StoppedTask {
             name: "Test2".to_string(),
+            tags: vec![],
+            notes: vec![],
             entries: vec![],
             last_entry: CompletedTimeEntry::new(
                 now + Duration::minutes(5),
@@ -904,6 +1073,7 @@ mod task_manager {
         let mut task_manager = TaskManager {
             stopped: vec![task1.clone(), task2.clone()],
             running: Some(task3.clone()),
+            ..Default::default()
         };
         let result = task_manager.switch_task("Test1".to_string(), now + Duration::minutes(20));
         assert_eq!(
@@ -924,6 +1094,8 @@ mod task_manager {
         let now = Local::now();
         let task1 = StoppedTask {
             name: "Test1".to_string(),
+            tags: vec![],
+            notes: vec![],
             entries: vec![],
             last_entry: CompletedTimeEntry::new(now, now + Duration::minutes(5)),
         };
@@ -931,6 +1103,7 @@ mod task_manager {
         let mut task_manager = TaskManager {
             stopped: vec![task1.clone()],
             running: Some(task2.clone()),
+            ..Default::default()
         };
         let result = task_manager.switch_task("Test1".to_string(), now + Duration::minutes(10));
         assert_eq!(task_manager.running, Some(task2));
@@ -945,6 +1118,8 @@ mod task_manager {
         let now = Local::now();
         let task1 = StoppedTask {
             name: "Test1".to_string(),
+            tags: vec![],
+            notes: vec![],
             entries: vec![],
             last_entry: CompletedTimeEntry::new(now, now + Duration::minutes(15)),
         };
@@ -952,6 +1127,7 @@ mod task_manager {
         let mut task_manager = TaskManager {
             stopped: vec![task1.clone()],
             running: Some(task2.clone()),
+            ..Default::default()
         };
         let result = task_manager.switch_task("Test1".to_string(), now + Duration::minutes(10));
         assert_eq!(task_manager.running, Some(task2));
@@ -966,11 +1142,15 @@ mod task_manager {
         let now = Local::now();
         let task1 = StoppedTask {
             name: "Test1".to_string(),
+            tags: vec![],
+            notes: vec![],
             entries: vec![],
             last_entry: CompletedTimeEntry::new(now, now + Duration::minutes(5)),
         };
         let task2 = StoppedTask {
             name: "Test2".to_string(),
+            tags: vec![],
+            notes: vec![],
             entries: vec![],
             last_entry: CompletedTimeEntry::new(
                 now + Duration::minutes(5),
@@ -981,6 +1161,7 @@ mod task_manager {
         let mut task_manager = TaskManager {
             stopped: vec![task1.clone(), task2.clone()],
             running: Some(task3.clone()),
+            ..Default::default()
         };
         let result = task_manager.switch_task("Test".to_string(), now + Duration::minutes(20));
         assert_eq!(task_manager.running, Some(task3));
@@ -995,11 +1176,15 @@ mod task_manager {
         let now = Local::now();
         let task1 = StoppedTask {
             name: "Test1".to_string(),
+            tags: vec![],
+            notes: vec![],
             entries: vec![],
             last_entry: CompletedTimeEntry::new(now, now + Duration::minutes(5)),
         };
         let task2 = StoppedTask {
             name: "Abc".to_string(),
+            tags: vec![],
+            notes: vec![],
             entries: vec![],
             last_entry: CompletedTimeEntry::new(
                 now + Duration::minutes(5),
@@ -1010,6 +1195,7 @@ mod task_manager {
         let mut task_manager = TaskManager {
             stopped: vec![task1.clone(), task2.clone()],
             running: Some(task3.clone()),
+            ..Default::default()
         };
         let result = task_manager.switch_task("Test".to_string(), now + Duration::minutes(20));
         assert_eq!(
@@ -1025,11 +1211,436 @@ mod task_manager {
         assert_eq!(task_name, "Test1");
     }
 
+    #[test]
+    fn test_task_manager_add_entry_when_no_tasks() {
+        let now = Local::now();
+        let mut task_manager = TaskManager::new();
+        let result = task_manager.add_entry("Test".to_string(), now, now + Duration::minutes(5));
+        assert_eq!(result.unwrap(), "Test");
+        assert_eq!(
+            task_manager.stopped,
+            vec![StoppedTask {
+                name: "Test".to_string(),
+                tags: vec![],
+                notes: vec![],
+                entries: vec![],
+                last_entry: CompletedTimeEntry::new(now, now + Duration::minutes(5)),
+            }]
+        );
+    }
+
+    #[test]
+    fn test_task_manager_add_entry_keeps_chronological_order() {
+        let now = Local::now();
+        let entry = |from, to| {
+            CompletedTimeEntry::new(now + Duration::minutes(from), now + Duration::minutes(to))
+        };
+        let task1 = StoppedTask {
+            name: "Test1".to_string(),
+            tags: vec![],
+            notes: vec![],
+            entries: vec![],
+            last_entry: entry(10, 20),
+        };
+        let task2 = StoppedTask {
+            name: "Test2".to_string(),
+            tags: vec![],
+            notes: vec![],
+            entries: vec![],
+            last_entry: entry(20, 30),
+        };
+        let mut task_manager = TaskManager {
+            stopped: vec![task1, task2.clone()],
+            running: None,
+            ..Default::default()
+        };
+        task_manager
+            .add_entry("Test1".to_string(), now, now + Duration::minutes(5))
+            .unwrap();
+        task_manager
+            .add_entry(
+                "Test1".to_string(),
+                now + Duration::minutes(40),
+                now + Duration::minutes(50),
+            )
+            .unwrap();
+        assert_eq!(
+            task_manager.stopped,
+            vec![
+                task2,
+                StoppedTask {
+                    name: "Test1".to_string(),
+                    tags: vec![],
+                    notes: vec![],
+                    entries: vec![entry(0, 5), entry(10, 20)],
+                    last_entry: entry(40, 50),
+                }
+            ]
+        );
+    }
+
+    #[test]
+    fn test_task_manager_add_entry_to_running_task() {
+        let now = Local::now();
+        let task = RunningTask::new("Test", now);
+        let mut task_manager = TaskManager {
+            stopped: vec![],
+            running: Some(task),
+            ..Default::default()
+        };
+        task_manager
+            .add_entry(
+                "Test".to_string(),
+                now - Duration::minutes(10),
+                now - Duration::minutes(5),
+            )
+            .unwrap();
+        let running = task_manager.running.unwrap();
+        assert_eq!(
+            running.entries,
+            vec![CompletedTimeEntry::new(
+                now - Duration::minutes(10),
+                now - Duration::minutes(5)
+            )]
+        );
+        assert_eq!(running.last_entry, OngoingTimeEntry::new(now));
+    }
+
+    #[test]
+    fn test_task_manager_add_entry_when_overlapping() {
+        let now = Local::now();
+        let stopped_task = StoppedTask {
+            name: "Test1".to_string(),
+            tags: vec![],
+            notes: vec![],
+            entries: vec![],
+            last_entry: CompletedTimeEntry::new(now, now + Duration::minutes(10)),
+        };
+        let running_task = RunningTask::new("Test2", now + Duration::minutes(20));
+        let mut task_manager = TaskManager {
+            stopped: vec![stopped_task],
+            running: Some(running_task),
+            ..Default::default()
+        };
+        let before = task_manager.clone();
+        let error = task_manager
+            .add_entry(
+                "Test3".to_string(),
+                now + Duration::minutes(5),
+                now + Duration::minutes(15),
+            )
+            .unwrap_err();
+        assert!(matches!(error, TaskError::OverlappingEntry(name) if name == "Test1"));
+        let error = task_manager
+            .add_entry(
+                "Test3".to_string(),
+                now + Duration::minutes(15),
+                now + Duration::minutes(25),
+            )
+            .unwrap_err();
+        assert!(matches!(error, TaskError::OverlappingEntry(name) if name == "Test2"));
+        assert_eq!(task_manager, before);
+        task_manager
+            .add_entry(
+                "Test3".to_string(),
+                now + Duration::minutes(10),
+                now + Duration::minutes(20),
+            )
+            .unwrap();
+    }
+
+    #[test]
+    fn test_task_manager_add_entry_when_start_after_end() {
+        let now = Local::now();
+        let mut task_manager = TaskManager::new();
+        let error = task_manager
+            .add_entry("Test".to_string(), now, now - Duration::minutes(5))
+            .unwrap_err();
+        assert!(matches!(error, TaskError::InvalidTimeRange));
+        assert!(task_manager.list_tasks().is_empty());
+    }
+
+    /// Returns a task manager with "Test1" worked on from 0 to 10 and 20 to 30 minutes after
+    /// `now`, and "Test2" running since 40 minutes after `now`.
+    fn task_manager_with_entries(now: DateTime<Local>) -> TaskManager {
+        TaskManager {
+            stopped: vec![StoppedTask {
+                name: "Test1".to_string(),
+                tags: vec![],
+                notes: vec![],
+                entries: vec![CompletedTimeEntry::new(now, now + Duration::minutes(10))],
+                last_entry: CompletedTimeEntry::new(
+                    now + Duration::minutes(20),
+                    now + Duration::minutes(30),
+                ),
+            }],
+            running: Some(RunningTask::new("Test2", now + Duration::minutes(40))),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_task_manager_find_task() {
+        let now = Local::now();
+        let task_manager = task_manager_with_entries(now);
+        assert_eq!(task_manager.find_task("2").unwrap(), "Test2");
+        let error = task_manager.find_task("Test").unwrap_err();
+        assert!(matches!(error, TaskError::MultipleTasksFound));
+        let error = task_manager.find_task("Other").unwrap_err();
+        assert!(matches!(error, TaskError::TaskNotFound(name) if name == "Other"));
+    }
+
+    #[test]
+    fn test_task_manager_task_record() {
+        let now = Local::now();
+        let task_manager = task_manager_with_entries(now);
+        let record = task_manager.task_record("Test2").unwrap();
+        assert!(record.entries.is_empty());
+        assert_eq!(record.running_since, Some(now + Duration::minutes(40)));
+        let record = task_manager.task_record("Test1").unwrap();
+        assert_eq!(record.entries.len(), 2);
+    }
+
+    #[test]
+    fn test_task_manager_edit_entry() {
+        let now = Local::now();
+        let mut task_manager = task_manager_with_entries(now);
+        task_manager
+            .edit_entry(
+                "Test1".to_string(),
+                0,
+                now + Duration::minutes(30),
+                now + Duration::minutes(35),
+            )
+            .unwrap();
+        assert_eq!(
+            task_manager.stopped,
+            vec![StoppedTask {
+                name: "Test1".to_string(),
+                tags: vec![],
+                notes: vec![],
+                entries: vec![CompletedTimeEntry::new(
+                    now + Duration::minutes(20),
+                    now + Duration::minutes(30)
+                )],
+                last_entry: CompletedTimeEntry::new(
+                    now + Duration::minutes(30),
+                    now + Duration::minutes(35)
+                ),
+            }]
+        );
+    }
+
+    #[test]
+    fn test_task_manager_edit_entry_when_overlapping() {
+        let now = Local::now();
+        let mut task_manager = task_manager_with_entries(now);
+        let before = task_manager.clone();
+        let error = task_manager
+            .edit_entry(
+                "Test1".to_string(),
+                0,
+                now + Duration::minutes(5),
+                now + Duration::minutes(25),
+            )
+            .unwrap_err();
+        assert!(matches!(error, TaskError::OverlappingEntry(name) if name == "Test1"));
+        let error = task_manager
+            .edit_entry(
+                "Test1".to_string(),
+                1,
+                now + Duration::minutes(20),
+                now + Duration::minutes(45),
+            )
+            .unwrap_err();
+        assert!(matches!(error, TaskError::OverlappingEntry(name) if name == "Test2"));
+        assert_eq!(task_manager, before);
+    }
+
+    #[test]
+    fn test_task_manager_edit_entry_when_running() {
+        let now = Local::now();
+        let mut task_manager = task_manager_with_entries(now);
+        let error = task_manager
+            .edit_entry("Test2".to_string(), 0, now, now + Duration::minutes(5))
+            .unwrap_err();
+        assert!(matches!(error, TaskError::EntryRunning(1, name) if name == "Test2"));
+        let error = task_manager
+            .edit_entry("Test1".to_string(), 2, now, now + Duration::minutes(5))
+            .unwrap_err();
+        assert!(matches!(error, TaskError::EntryNotFound(3, name) if name == "Test1"));
+    }
+
+    #[test]
+    fn test_task_manager_delete_entry() {
+        let now = Local::now();
+        let mut task_manager = task_manager_with_entries(now);
+        task_manager.delete_entry("Test1".to_string(), 1).unwrap();
+        assert_eq!(
+            task_manager.stopped,
+            vec![StoppedTask {
+                name: "Test1".to_string(),
+                tags: vec![],
+                notes: vec![],
+                entries: vec![],
+                last_entry: CompletedTimeEntry::new(now, now + Duration::minutes(10)),
+            }]
+        );
+        task_manager.delete_entry("Test1".to_string(), 0).unwrap();
+        assert_eq!(task_manager.list_tasks(), vec!["Test2"]);
+    }
+
+    #[test]
+    fn test_task_manager_split_entry() {
+        let now = Local::now();
+        let mut task_manager = task_manager_with_entries(now);
+        task_manager
+            .split_entry("Test1".to_string(), 0, now + Duration::minutes(4))
+            .unwrap();
+        let record = task_manager.task_record("Test1").unwrap();
+        assert_eq!(
+            record.entries,
+            vec![
+                (now, now + Duration::minutes(4)),
+                (now + Duration::minutes(4), now + Duration::minutes(10)),
+                (now + Duration::minutes(20), now + Duration::minutes(30)),
+            ]
+        );
+    }
+
+    fn task_manager_with_single_entry(now: DateTime<Local>) -> TaskManager {
+        TaskManager {
+            stopped: vec![StoppedTask {
+                name: "Test1".to_string(),
+                tags: vec!["tag".to_string()],
+                notes: vec!["note".to_string()],
+                entries: vec![],
+                last_entry: CompletedTimeEntry::new(now, now + Duration::minutes(10))
+                    .with_note(Some("entry".to_string())),
+            }],
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_task_manager_edit_entry_keeps_tags() {
+        let now = Local::now();
+        let mut task_manager = task_manager_with_single_entry(now);
+        task_manager
+            .edit_entry(
+                "Test1".to_string(),
+                0,
+                now + Duration::minutes(5),
+                now + Duration::minutes(20),
+            )
+            .unwrap();
+        assert_eq!(task_manager.tags("Test1"), ["tag"]);
+    }
+
+    #[test]
+    fn test_task_manager_split_entry_keeps_tags() {
+        let now = Local::now();
+        let mut task_manager = task_manager_with_single_entry(now);
+        task_manager
+            .split_entry("Test1".to_string(), 0, now + Duration::minutes(4))
+            .unwrap();
+        assert_eq!(task_manager.tags("Test1"), ["tag"]);
+    }
+
+    #[test]
+    fn test_task_manager_move_entry_keeps_details() {
+        let now = Local::now();
+        let mut task_manager = task_manager_with_single_entry(now);
+        task_manager
+            .move_entry("Test1".to_string(), 0, "Test1".to_string())
+            .unwrap();
+        assert_eq!(task_manager.tags("Test1"), ["tag"]);
+        task_manager
+            .move_entry("Test1".to_string(), 0, "Test2".to_string())
+            .unwrap();
+        assert_eq!(task_manager.list_tasks(), vec!["Test2"]);
+        assert_eq!(task_manager.tags("Test2"), ["tag"]);
+        let record = task_manager.task_record("Test2").unwrap();
+        assert_eq!(record.notes, ["note"]);
+        assert_eq!(record.entry_notes, [Some("entry".to_string())]);
+    }
+
+    #[test]
+    fn test_task_manager_edit_entry_keeps_notes() {
+        let now = Local::now();
+        let mut task_manager = task_manager_with_single_entry(now);
+        task_manager
+            .edit_entry(
+                "Test1".to_string(),
+                0,
+                now + Duration::minutes(5),
+                now + Duration::minutes(20),
+            )
+            .unwrap();
+        let record = task_manager.task_record("Test1").unwrap();
+        assert_eq!(record.notes, ["note"]);
+        assert_eq!(record.entry_notes, [Some("entry".to_string())]);
+    }
+
+    #[test]
+    fn test_task_manager_split_entry_keeps_notes() {
+        let now = Local::now();
+        let mut task_manager = task_manager_with_single_entry(now);
+        task_manager
+            .split_entry("Test1".to_string(), 0, now + Duration::minutes(4))
+            .unwrap();
+        let record = task_manager.task_record("Test1").unwrap();
+        assert_eq!(record.notes, ["note"]);
+        assert_eq!(
+            record.entry_notes,
+            [Some("entry".to_string()), Some("entry".to_string())]
+        );
+    }
+
+    #[test]
+    fn test_task_manager_split_entry_when_outside() {
+        let now = Local::now();
+        let mut task_manager = task_manager_with_entries(now);
+        let before = task_manager.clone();
+        for at in [
+            now,
+            now + Duration::minutes(10),
+            now + Duration::minutes(15),
+        ] {
+            let error = task_manager
+                .split_entry("Test1".to_string(), 0, at)
+                .unwrap_err();
+            assert!(matches!(error, TaskError::InvalidSplitTime));
+        }
+        assert_eq!(task_manager, before);
+    }
+
+    #[test]
+    fn test_task_manager_move_entry() {
+        let now = Local::now();
+        let mut task_manager = task_manager_with_entries(now);
+        task_manager
+            .move_entry("Test1".to_string(), 1, "Test2".to_string())
+            .unwrap();
+        task_manager
+            .move_entry("Test1".to_string(), 0, "Test3".to_string())
+            .unwrap();
+        assert_eq!(task_manager.list_tasks(), vec!["Test3", "Test2"]);
+        let record = task_manager.task_record("Test2").unwrap();
+        assert_eq!(
+            record.entries,
+            vec![(now + Duration::minutes(20), now + Duration::minutes(30))]
+        );
+        let record = task_manager.task_record("Test3").unwrap();
+        assert_eq!(record.entries, vec![(now, now + Duration::minutes(10))]);
+    }
+
     #[test]
     fn test_task_manager_list_when_no_tasks() {
         let task_manager = TaskManager {
             stopped: vec![],
             running: None,
+            ..Default::default()
         };
         let result = task_manager.list_tasks();
         assert!(result.is_empty());
@@ -1040,11 +1651,15 @@ mod task_manager {
         let now = Local::now();
         let task1 = StoppedTask {
             name: "Test1".to_string(),
+            tags: vec![],
+            notes: vec![],
             entries: vec![],
             last_entry: CompletedTimeEntry::new(now, now + Duration::minutes(5)),
         };
         let task2 = StoppedTask {
             name: "Test2".to_string(),
+            tags: vec![],
+            notes: vec![],
             entries: vec![],
             last_entry: CompletedTimeEntry::new(
                 now + Duration::minutes(5),
@@ -1055,6 +1670,7 @@ mod task_manager {
         let task_manager = TaskManager {
             stopped: vec![task1.clone(), task2.clone()],
             running: Some(task3.clone()),
+            ..Default::default()
         };
         let result = task_manager.list_tasks();
         assert_eq!(result, vec!["Test1", "Test2", "Test3"]);
@@ -1065,6 +1681,8 @@ mod task_manager {
         let now = Local::now();
         let task1 = StoppedTask {
             name: "Test1".to_string(),
+            tags: vec![],
+            notes: vec![],
             entries: vec![],
             last_entry: CompletedTimeEntry::new(now, now + Duration::minutes(5)),
         };
@@ -1072,6 +1690,7 @@ mod task_manager {
         let mut task_manager = TaskManager {
             stopped: vec![task1.clone()],
             running: Some(task2.clone()),
+            ..Default::default()
         };
         let result = task_manager.rename_task("Abc".to_string(), "Test".to_string());
         assert_eq!(task_manager.running, Some(task2));
@@ -1086,6 +1705,8 @@ mod task_manager {
         let now = Local::now();
         let mut task1 = StoppedTask {
             name: "Test10".to_string(),
+            tags: vec![],
+            notes: vec![],
             entries: vec![],
             last_entry: CompletedTimeEntry::new(now, now + Duration::minutes(5)),
         };
@@ -1093,6 +1714,7 @@ mod task_manager {
         let mut task_manager = TaskManager {
             stopped: vec![task1.clone()],
             running: Some(task2.clone()),
+            ..Default::default()
         };
         let result = task_manager.rename_task("Test1".to_string(), "Test".to_string());
         task1.name = "Test".to_string();
@@ -1109,6 +1731,8 @@ mod task_manager {
         let now = Local::now();
         let task1 = StoppedTask {
             name: "Test1".to_string(),
+            tags: vec![],
+            notes: vec![],
             entries: vec![],
             last_entry: CompletedTimeEntry::new(now, now + Duration::minutes(5)),
         };
@@ -1116,6 +1740,7 @@ mod task_manager {
         let mut task_manager = TaskManager {
             stopped: vec![task1.clone()],
             running: Some(task2.clone()),
+            ..Default::default()
         };
         let result = task_manager.rename_task("Test2".to_string(), "Test".to_string());
         task2.name = "Test".to_string();
@@ -1132,11 +1757,15 @@ mod task_manager {
         let now = Local::now();
         let task1 = StoppedTask {
             name: "Test1".to_string(),
+            tags: vec![],
+            notes: vec![],
             entries: vec![],
             last_entry: CompletedTimeEntry::new(now, now + Duration::minutes(5)),
         };
         let task2 = StoppedTask {
             name: "Test2".to_string(),
+            tags: vec![],
+            notes: vec![],
             entries: vec![],
             last_entry: CompletedTimeEntry::new(
                 now + Duration::minutes(5),
@@ -1146,6 +1775,7 @@ mod task_manager {
         let mut task_manager = TaskManager {
             stopped: vec![task1.clone(), task2.clone()],
             running: None,
+            ..Default::default()
         };
         let result = task_manager.rename_task("Test".to_string(), "Abc".to_string());
         assert_eq!(task_manager.running, None);
@@ -1160,6 +1790,8 @@ mod task_manager {
         let now = Local::now();
         let task1 = StoppedTask {
             name: "Test1".to_string(),
+            tags: vec![],
+            notes: vec![],
             entries: vec![],
             last_entry: CompletedTimeEntry::new(now, now + Duration::minutes(5)),
         };
@@ -1167,6 +1799,7 @@ mod task_manager {
         let mut task_manager = TaskManager {
             stopped: vec![task1.clone()],
             running: Some(task2.clone()),
+            ..Default::default()
         };
         let result = task_manager.rename_task("Test".to_string(), "Abc".to_string());
         assert_eq!(task_manager.running, Some(task2));
@@ -1181,6 +1814,8 @@ mod task_manager {
         let now = Local::now();
         let task1 = StoppedTask {
             name: "Test1".to_string(),
+            tags: vec![],
+            notes: vec![],
             entries: vec![],
             last_entry: CompletedTimeEntry::new(now, now + Duration::minutes(5)),
         };
@@ -1188,6 +1823,7 @@ mod task_manager {
         let mut task_manager = TaskManager {
             stopped: vec![task1.clone()],
             running: Some(task2.clone()),
+            ..Default::default()
         };
         let result = task_manager.delete_task("Abc".to_string());
         assert_eq!(task_manager.running, Some(task2));
@@ -1202,6 +1838,8 @@ mod task_manager {
         let now = Local::now();
         let mut task1 = StoppedTask {
             name: "Test10".to_string(),
+            tags: vec![],
+            notes: vec![],
             entries: vec![],
             last_entry: CompletedTimeEntry::new(now, now + Duration::minutes(5)),
         };
@@ -1209,6 +1847,7 @@ mod task_manager {
         let mut task_manager = TaskManager {
             stopped: vec![task1.clone()],
             running: Some(task2.clone()),
+            ..Default::default()
         };
         let result = task_manager.delete_task("Test1".to_string());
         task1.name = "Test".to_string();
@@ -1224,6 +1863,8 @@ mod task_manager {
         let now = Local::now();
         let task1 = StoppedTask {
             name: "Test1".to_string(),
+            tags: vec![],
+            notes: vec![],
             entries: vec![],
             last_entry: CompletedTimeEntry::new(now, now + Duration::minutes(5)),
         };
@@ -1231,6 +1872,7 @@ mod task_manager {
         let mut task_manager = TaskManager {
             stopped: vec![task1.clone()],
             running: Some(task2.clone()),
+            ..Default::default()
         };
         let result = task_manager.delete_task("Test2".to_string());
         task2.name = "Test".to_string();
@@ -1246,11 +1888,15 @@ mod task_manager {
         let now = Local::now();
         let task1 = StoppedTask {
             name: "Test1".to_string(),
+            tags: vec![],
+            notes: vec![],
             entries: vec![],
             last_entry: CompletedTimeEntry::new(now, now + Duration::minutes(5)),
         };
         let task2 = StoppedTask {
             name: "Test2".to_string(),
+            tags: vec![],
+            notes: vec![],
             entries: vec![],
             last_entry: CompletedTimeEntry::new(
                 now + Duration::minutes(5),
@@ -1260,6 +1906,7 @@ mod task_manager {
         let mut task_manager = TaskManager {
             stopped: vec![task1.clone(), task2.clone()],
             running: None,
+            ..Default::default()
         };
         let result = task_manager.delete_task("Test".to_string());
         assert_eq!(task_manager.running, None);
@@ -1274,6 +1921,8 @@ mod task_manager {
         let now = Local::now();
         let task1 = StoppedTask {
             name: "Test1".to_string(),
+            tags: vec![],
+            notes: vec![],
             entries: vec![],
             last_entry: CompletedTimeEntry::new(now, now + Duration::minutes(5)),
         };
@@ -1281,6 +1930,7 @@ mod task_manager {
         let mut task_manager = TaskManager {
             stopped: vec![task1.clone()],
             running: Some(task2.clone()),
+            ..Default::default()
         };
         let result = task_manager.delete_task("Test".to_string());
         assert_eq!(task_manager.running, Some(task2));
@@ -1297,8 +1947,9 @@ mod task_manager {
         let task_manager = TaskManager {
             stopped: vec![],
             running: None,
+            ..Default::default()
         };
-        let report = task_manager.generate_report(today, now);
+        let report = task_manager.generate_report(today, now, &ReportOptions::default());
         assert!(report.contains("2024-07-16"));
         assert!(report.contains("Total | 00:00 | 100.0%"));
         assert_eq!(3, report.lines().count());
@@ -1310,11 +1961,15 @@ mod task_manager {
         let today = NaiveDate::from_ymd_opt(2024, 7, 16).unwrap();
         let task1 = StoppedTask {
             name: "Test1".to_string(),
+            tags: vec![],
+            notes: vec![],
             entries: vec![],
             last_entry: CompletedTimeEntry::new(now, now + Duration::minutes(5)),
         };
         let task2 = StoppedTask {
             name: "Test2".to_string(),
+            tags: vec![],
+            notes: vec![],
             entries: vec![],
             last_entry: CompletedTimeEntry::new(
                 now + Duration::minutes(5),
@@ -1324,8 +1979,13 @@ mod task_manager {
         let task_manager = TaskManager {
             stopped: vec![task1, task2],
             running: None,
+            ..Default::default()
         };
-        let report = task_manager.generate_report(today, now + Duration::minutes(20));
+        let report = task_manager.generate_report(
+            today,
+            now + Duration::minutes(20),
+            &ReportOptions::default(),
+        );
         assert!(report.contains("2024-07-16"));
         assert!(report.contains("  Test1 | 00:05 |  33.3%"));
         assert!(report.contains("  Test2 | 00:10 |  66.7%"));
@@ -1340,6 +2000,8 @@ mod task_manager {
         let today = NaiveDate::from_ymd_opt(2024, 7, 16).unwrap();
         let task1 = StoppedTask {
             name: "Test1".to_string(),
+            tags: vec![],
+            notes: vec![],
             entries: vec![],
             last_entry: CompletedTimeEntry::new(now, now + Duration::minutes(5)),
         };
@@ -1347,8 +2009,13 @@ mod task_manager {
         let task_manager = TaskManager {
             stopped: vec![task1],
             running: Some(task2),
+            ..Default::default()
         };
-        let report = task_manager.generate_report(today, now + Duration::minutes(20));
+        let report = task_manager.generate_report(
+            today,
+            now + Duration::minutes(20),
+            &ReportOptions::default(),
+        );
         assert!(report.contains("2024-07-16"));
         assert!(report.contains("  Test1 | 00:05 |  50.0%"));
         assert!(report.contains("  Test2 | 00:05 |  50.0%"));
@@ -1363,11 +2030,15 @@ mod task_manager {
         let today = NaiveDate::from_ymd_opt(2024, 7, 16).unwrap();
         let task1 = StoppedTask {
             name: "Test1".to_string(),
+            tags: vec![],
+            notes: vec![],
             entries: vec![],
             last_entry: CompletedTimeEntry::new(now, now + Duration::minutes(5)),
         };
         let task2 = StoppedTask {
             name: "Test2 is a very long name".to_string(),
+            tags: vec![],
+            notes: vec![],
             entries: vec![],
             last_entry: CompletedTimeEntry::new(
                 now + Duration::minutes(5),
@@ -1377,8 +2048,13 @@ mod task_manager {
         let task_manager = TaskManager {
             stopped: vec![task1, task2],
             running: None,
+            ..Default::default()
         };
-        let report = task_manager.generate_report(today, now + Duration::minutes(20));
+        let report = task_manager.generate_report(
+            today,
+            now + Duration::minutes(20),
+            &ReportOptions::default(),
+        );
         assert!(report.contains("2024-07-16"));
         assert!(report.contains("  Test1                     | 00:05 |  33.3%"));
         assert!(report.contains("  Test2 is a very long name | 00:10 |  66.7%"));
@@ -1386,6 +2062,658 @@ mod task_manager {
         assert!(report.contains("  Total                     | 00:15 | 100.0%"));
         assert_eq!(5, report.lines().count());
     }
+
+    #[test]
+    fn test_task_manager_generate_report_with_depth() {
+        let now = Local::now();
+        let today = NaiveDate::from_ymd_opt(2024, 7, 16).unwrap();
+        let entry = |from, to| {
+            CompletedTimeEntry::new(now + Duration::minutes(from), now + Duration::minutes(to))
+        };
+        let task = |name: &str, last_entry| StoppedTask {
+            name: name.to_string(),
+            tags: vec![],
+            notes: vec![],
+            entries: vec![],
+            last_entry,
+        };
+        let task_manager = TaskManager {
+            stopped: vec![
+                task("acme/website/login", entry(0, 5)),
+                task("acme/api", entry(5, 15)),
+                task("coding", entry(15, 20)),
+            ],
+            running: Some(RunningTask::new(
+                "acme/website/signup",
+                now + Duration::minutes(20),
+            )),
+            ..Default::default()
+        };
+        let time = now + Duration::minutes(40);
+        let depth = |depth| ReportOptions {
+            depth: Some(depth),
+            ..Default::default()
+        };
+        let report = task_manager.generate_report(today, time, &depth(1));
+        assert!(report.contains("  acme   | 00:35 |  87.5%"));
+        assert!(report.contains("  coding | 00:05 |  12.5%"));
+        assert_eq!(5, report.lines().count());
+        let report = task_manager.generate_report(today, time, &depth(2));
+        assert!(report.contains("  acme/website | 00:25 |  62.5%"));
+        assert!(report.contains("  acme/api     | 00:10 |  25.0%"));
+        assert!(report.contains("  coding       | 00:05 |  12.5%"));
+        assert_eq!(6, report.lines().count());
+    }
+
+    #[test]
+    fn test_task_manager_generate_report_with_tags() {
+        let now = Local::now();
+        let today = NaiveDate::from_ymd_opt(2024, 7, 16).unwrap();
+        let task = |name: &str, tags: &[&str], from, to| StoppedTask {
+            name: name.to_string(),
+            tags: tags.iter().map(|tag| tag.to_string()).collect(),
+            notes: vec![],
+            entries: vec![],
+            last_entry: CompletedTimeEntry::new(
+                now + Duration::minutes(from),
+                now + Duration::minutes(to),
+            ),
+        };
+        let task_manager = TaskManager {
+            stopped: vec![
+                task("coding", &["billable", "dev"], 0, 30),
+                task("review", &["dev"], 30, 40),
+                task("lunch", &[], 40, 50),
+            ],
+            ..Default::default()
+        };
+        let time = now + Duration::minutes(50);
+        let options = ReportOptions {
+            tags: vec!["dev".to_string()],
+            ..Default::default()
+        };
+        let report = task_manager.generate_report(today, time, &options);
+        assert!(report.contains("  coding | 00:30 |  75.0%"));
+        assert!(report.contains("  review | 00:10 |  25.0%"));
+        assert!(report.contains("  Total  | 00:40 | 100.0%"));
+        assert_eq!(5, report.lines().count());
+        let options = ReportOptions {
+            by_tag: true,
+            ..Default::default()
+        };
+        let report = task_manager.generate_report(today, time, &options);
+        assert!(report.contains("  billable   | 00:30 |  60.0%"));
+        assert!(report.contains("  dev        | 00:40 |  80.0%"));
+        assert!(report.contains("  (untagged) | 00:10 |  20.0%"));
+        assert!(report.contains("  Total      | 00:50 | 100.0%"));
+    }
+
+    #[test]
+    fn test_task_manager_report_timeline() {
+        let now = Local::now();
+        let today = NaiveDate::from_ymd_opt(2024, 7, 16).unwrap();
+        let mut task_manager = TaskManager::new();
+        task_manager
+            .add_entry(
+                "Test1".to_string(),
+                now + Duration::minutes(10),
+                now + Duration::minutes(20),
+            )
+            .unwrap();
+        task_manager
+            .add_entry("Test2".to_string(), now, now + Duration::minutes(5))
+            .unwrap();
+        task_manager
+            .resume_task("Test1".to_string(), now + Duration::minutes(30))
+            .unwrap();
+        let time = now + Duration::minutes(40);
+        let report = task_manager.report(today, time, &ReportOptions::default());
+        let timeline: Vec<_> = report
+            .timeline
+            .iter()
+            .map(|entry| (entry.task.as_str(), entry.start, entry.end, entry.running))
+            .collect();
+        assert_eq!(
+            timeline,
+            vec![
+                ("Test2", now, now + Duration::minutes(5), false),
+                (
+                    "Test1",
+                    now + Duration::minutes(10),
+                    now + Duration::minutes(20),
+                    false
+                ),
+                ("Test1", now + Duration::minutes(30), time, true),
+            ]
+        );
+        assert_eq!(report.total, Duration::minutes(25));
+        let row = &report.rows[1];
+        assert_eq!(
+            (row.name.as_str(), row.percent, row.running),
+            ("Test1", 80.0, true)
+        );
+    }
+}
+
+mod notes {
+    use chrono::TimeZone;
+
+    use super::*;
+
+    #[test]
+    fn test_task_manager_note_task() {
+        let now = Local::now();
+        let mut task_manager = TaskManager::new();
+        task_manager
+            .start_new_task("coding".to_string(), now)
+            .unwrap();
+        task_manager
+            .note_task("coding".to_string(), "Ticket #42".to_string())
+            .unwrap();
+        task_manager
+            .note_task("coding".to_string(), "Needs review".to_string())
+            .unwrap();
+        let record = task_manager.task_record("coding").unwrap();
+        assert_eq!(record.notes, vec!["Ticket #42", "Needs review"]);
+        let result = task_manager.note_task("other".to_string(), "Note".to_string());
+        assert!(matches!(result, Err(TaskError::TaskNotFound(_))));
+    }
+
+    #[test]
+    fn test_task_manager_note_entry() {
+        let now = Local::now();
+        let mut task_manager = TaskManager::new();
+        task_manager
+            .start_new_task("coding".to_string(), now)
+            .unwrap();
+        task_manager
+            .switch_new_task("review".to_string(), now + Duration::minutes(10))
+            .unwrap();
+        task_manager
+            .switch_task("coding".to_string(), now + Duration::minutes(20))
+            .unwrap();
+        task_manager
+            .note_entry("coding".to_string(), 0, "Fixed the bug".to_string())
+            .unwrap();
+        task_manager
+            .note_entry("review".to_string(), 0, "First".to_string())
+            .unwrap();
+        task_manager
+            .note_entry("review".to_string(), 0, "Second".to_string())
+            .unwrap();
+        let record = task_manager.task_record("coding").unwrap();
+        assert_eq!(record.entry_notes, vec![Some("Fixed the bug".to_string())]);
+        let record = task_manager.task_record("review").unwrap();
+        assert_eq!(record.entry_notes, vec![Some("Second".to_string())]);
+        let result = task_manager.note_entry("coding".to_string(), 1, "Note".to_string());
+        assert!(matches!(result, Err(TaskError::EntryRunning(2, _))));
+    }
+
+    #[test]
+    fn test_task_manager_entry_notes_survive_changes() {
+        let now = Local::now();
+        let mut task_manager = TaskManager::new();
+        task_manager
+            .add_entry("coding".to_string(), now, now + Duration::minutes(10))
+            .unwrap();
+        task_manager
+            .note_entry("coding".to_string(), 0, "Refactoring".to_string())
+            .unwrap();
+        task_manager
+            .split_entry("coding".to_string(), 0, now + Duration::minutes(5))
+            .unwrap();
+        task_manager
+            .edit_entry(
+                "coding".to_string(),
+                1,
+                now + Duration::minutes(6),
+                now + Duration::minutes(10),
+            )
+            .unwrap();
+        let record = task_manager.task_record("coding").unwrap();
+        let note = Some("Refactoring".to_string());
+        assert_eq!(record.entry_notes, vec![note.clone(), note]);
+        task_manager.take_events();
+        let records = task_manager.to_records();
+        assert_eq!(TaskManager::from_records(records).unwrap(), task_manager);
+    }
+
+    #[test]
+    fn test_task_manager_generate_report_with_notes() {
+        let now = Local.with_ymd_and_hms(2024, 7, 16, 9, 0, 0).unwrap();
+        let today = NaiveDate::from_ymd_opt(2024, 7, 16).unwrap();
+        let mut task_manager = TaskManager::new();
+        task_manager
+            .add_entry("coding".to_string(), now, now + Duration::minutes(30))
+            .unwrap();
+        task_manager
+            .note_task("coding".to_string(), "Ticket #42".to_string())
+            .unwrap();
+        task_manager
+            .note_entry("coding".to_string(), 0, "Fixed the bug".to_string())
+            .unwrap();
+        let time = now + Duration::minutes(30);
+        let report = task_manager.generate_report(today, time, &ReportOptions::default());
+        assert!(!report.contains("Ticket #42"));
+        let options = ReportOptions {
+            verbose: true,
+            ..Default::default()
+        };
+        let report = task_manager.generate_report(today, time, &options);
+        assert!(report.contains("  coding | 00:30 | 100.0%\n      - Ticket #42\n"));
+        assert!(report.contains("      - 09:00-09:30 Fixed the bug\n"));
+    }
+}
+
+mod tags {
+    use super::*;
+
+    #[test]
+    fn test_task_manager_tag_task() {
+        let now = Local::now();
+        let mut task_manager = TaskManager::new();
+        task_manager
+            .start_new_task("coding".to_string(), now)
+            .unwrap();
+        let tags = |tags: &[&str]| tags.iter().map(|tag| tag.to_string()).collect::<Vec<_>>();
+        task_manager
+            .tag_task("coding".to_string(), tags(&["review", "billable"]), vec![])
+            .unwrap();
+        assert_eq!(tags(&["billable", "review"]), task_manager.tags("coding"));
+        task_manager
+            .stop_running_task_with_time(now + Duration::minutes(5))
+            .unwrap();
+        task_manager
+            .tag_task(
+                "coding".to_string(),
+                tags(&["urgent", "billable"]),
+                tags(&["review", "urgent"]),
+            )
+            .unwrap();
+        assert_eq!(tags(&["billable"]), task_manager.tags("coding"));
+    }
+
+    #[test]
+    fn test_task_manager_tag_task_when_not_found() {
+        let mut task_manager = TaskManager::new();
+        let result = task_manager.tag_task("coding".to_string(), vec!["x".to_string()], vec![]);
+        assert!(matches!(result, Err(TaskError::TaskNotFound(_))));
+    }
+
+    #[test]
+    fn test_task_manager_tags_survive_serialization() {
+        let now = Local::now();
+        let mut task_manager = TaskManager::new();
+        task_manager
+            .start_new_task("coding".to_string(), now)
+            .unwrap();
+        task_manager
+            .tag_task("coding".to_string(), vec!["dev".to_string()], vec![])
+            .unwrap();
+        let json = serde_json::to_string(&task_manager).unwrap();
+        let deserialized: TaskManager = serde_json::from_str(&json).unwrap();
+        assert_eq!(vec!["dev".to_string()], deserialized.tags("coding"));
+    }
+}
+
+mod budgets {
+    use std::collections::HashMap;
+
+    use super::*;
+    use crate::report::Budget;
+
+    fn budget(spent: i64, estimate: i64) -> Budget {
+        Budget {
+            spent: Duration::minutes(spent),
+            estimate: Duration::minutes(estimate),
+        }
+    }
+
+    #[test]
+    fn test_task_manager_generate_report_with_budgets() {
+        colored::control::set_override(false);
+        let now = Local::now();
+        let today = NaiveDate::from_ymd_opt(2024, 7, 16).unwrap();
+        let mut task_manager = TaskManager::new();
+        task_manager
+            .add_entry("acme/api".to_string(), now, now + Duration::minutes(30))
+            .unwrap();
+        task_manager
+            .add_entry(
+                "coding".to_string(),
+                now + Duration::minutes(30),
+                now + Duration::minutes(60),
+            )
+            .unwrap();
+        let options = ReportOptions {
+            budgets: HashMap::from([
+                ("acme/api".to_string(), budget(150, 120)),
+                ("coding".to_string(), budget(30, 60)),
+            ]),
+            ..Default::default()
+        };
+        let time = now + Duration::minutes(60);
+        let report = task_manager.generate_report(today, time, &options);
+        assert!(report.contains("acme/api | 00:30 |  50.0% | 02:30 of 02:00, 00:30 over"));
+        assert!(report.contains("coding   | 00:30 |  50.0% | 00:30 of 01:00, 00:30 left"));
+        let options = ReportOptions {
+            depth: Some(1),
+            ..options
+        };
+        let report = task_manager.generate_report(today, time, &options);
+        assert!(report.contains("    acme   | 00:30 |  50.0%\n"));
+        assert!(report.contains("coding | 00:30 |  50.0% | 00:30 of 01:00, 00:30 left"));
+    }
+}
+
+mod events {
+    use super::*;
+
+    #[test]
+    fn test_task_manager_records_events() {
+        let now = Local::now();
+        let mut task_manager = TaskManager::new();
+        task_manager
+            .start_new_task("Test1".to_string(), now)
+            .unwrap();
+        task_manager
+            .switch_new_task("Test2".to_string(), now + Duration::minutes(5))
+            .unwrap();
+        task_manager
+            .switch_task("1".to_string(), now + Duration::minutes(10))
+            .unwrap();
+        task_manager
+            .rename_task("1".to_string(), "Test3".to_string())
+            .unwrap();
+        task_manager.delete_task("2".to_string()).unwrap();
+        let events = task_manager.take_events();
+        assert_eq!(
+            events,
+            vec![
+                Event::Started {
+                    task: "Test1".to_string(),
+                    at: now
+                },
+                Event::Stopped {
+                    at: now + Duration::minutes(5)
+                },
+                Event::Started {
+                    task: "Test2".to_string(),
+                    at: now + Duration::minutes(5)
+                },
+                Event::Stopped {
+                    at: now + Duration::minutes(10)
+                },
+                Event::Resumed {
+                    task: "Test1".to_string(),
+                    at: now + Duration::minutes(10)
+                },
+                Event::Renamed {
+                    from: "Test1".to_string(),
+                    to: "Test3".to_string()
+                },
+                Event::Deleted {
+                    task: "Test2".to_string()
+                },
+            ]
+        );
+        assert!(task_manager.take_events().is_empty());
+    }
+
+    #[test]
+    fn test_task_manager_records_no_events_on_error() {
+        let now = Local::now();
+        let mut task_manager = TaskManager::new();
+        assert!(task_manager.resume_last_task(now).is_err());
+        assert!(task_manager.stop_running_task_with_time(now).is_err());
+        assert!(task_manager.delete_task("Test".to_string()).is_err());
+        assert!(task_manager.take_events().is_empty());
+    }
+
+    #[test]
+    fn test_task_manager_replay() {
+        let now = Local::now();
+        let mut task_manager = TaskManager::new();
+        task_manager
+            .start_new_task("Test".to_string(), now)
+            .unwrap();
+        task_manager
+            .switch_new_task("Test review".to_string(), now + Duration::minutes(5))
+            .unwrap();
+        task_manager
+            .switch_last_task(now + Duration::minutes(10))
+            .unwrap();
+        task_manager
+            .stop_running_task_with_duration(Duration::minutes(5), now + Duration::minutes(20))
+            .unwrap();
+        task_manager
+            .rename_task("review".to_string(), "Review".to_string())
+            .unwrap();
+        task_manager
+            .add_entry(
+                "Added".to_string(),
+                now - Duration::minutes(10),
+                now - Duration::minutes(5),
+            )
+            .unwrap();
+        task_manager
+            .split_entry("Added".to_string(), 0, now - Duration::minutes(8))
+            .unwrap();
+        task_manager
+            .edit_entry(
+                "Added".to_string(),
+                1,
+                now - Duration::minutes(7),
+                now - Duration::minutes(6),
+            )
+            .unwrap();
+        task_manager
+            .move_entry("Added".to_string(), 0, "Moved".to_string())
+            .unwrap();
+        task_manager.delete_entry("Test".to_string(), 0).unwrap();
+        task_manager
+            .tag_task("Moved".to_string(), vec!["dev".to_string()], vec![])
+            .unwrap();
+        task_manager
+            .note_task("Moved".to_string(), "Task note".to_string())
+            .unwrap();
+        task_manager
+            .note_entry("Moved".to_string(), 0, "Entry note".to_string())
+            .unwrap();
+        let events = task_manager.take_events();
+        let replayed = TaskManager::replay(events).unwrap();
+        assert_eq!(replayed, task_manager);
+    }
+
+    #[test]
+    fn test_task_manager_replay_uses_exact_names() {
+        let now = Local::now();
+        let events = vec![
+            Event::Started {
+                task: "Test".to_string(),
+                at: now,
+            },
+            Event::Stopped {
+                at: now + Duration::minutes(5),
+            },
+            Event::Started {
+                task: "Test review".to_string(),
+                at: now + Duration::minutes(5),
+            },
+            Event::Deleted {
+                task: "Test".to_string(),
+            },
+        ];
+        let replayed = TaskManager::replay(events).unwrap();
+        assert_eq!(replayed.list_tasks(), vec!["Test review"]);
+    }
+
+    #[test]
+    fn test_task_manager_replay_snapshot() {
+        let now = Local::now();
+        let mut snapshot = TaskManager::new();
+        snapshot.start_new_task("Test1".to_string(), now).unwrap();
+        snapshot.take_events();
+        let events = vec![
+            Event::Snapshot {
+                tasks: snapshot.clone(),
+            },
+            Event::Stopped {
+                at: now + Duration::minutes(5),
+            },
+        ];
+        let replayed = TaskManager::replay(events).unwrap();
+        assert_eq!(replayed.running, None);
+        assert_eq!(replayed.list_tasks(), vec!["Test1"]);
+    }
+
+    #[test]
+    fn test_task_manager_replay_when_inconsistent() {
+        let now = Local::now();
+        let events = vec![Event::Resumed {
+            task: "Test".to_string(),
+            at: now,
+        }];
+        let error = TaskManager::replay(events).unwrap_err();
+        assert!(matches!(error, TaskError::TaskNotFound(_)));
+    }
+}
+
+mod task_record {
+    use super::*;
+
+    #[test]
+    fn test_task_manager_to_records() {
+        let now = Local::now();
+        let task1 = StoppedTask {
+            name: "Test1".to_string(),
+            tags: vec![],
+            notes: vec![],
+            entries: vec![CompletedTimeEntry::new(now, now + Duration::minutes(1))],
+            last_entry: CompletedTimeEntry::new(
+                now + Duration::minutes(2),
+                now + Duration::minutes(5),
+            ),
+        };
+        let task2 = RunningTask::new("Test2", now + Duration::minutes(15));
+        let task_manager = TaskManager {
+            stopped: vec![task1],
+            running: Some(task2),
+            ..Default::default()
+        };
+        let records = task_manager.to_records();
+        assert_eq!(
+            records,
+            vec![
+                TaskRecord {
+                    name: "Test1".to_string(),
+                    tags: vec![],
+                    notes: vec![],
+                    entries: vec![
+                        (now, now + Duration::minutes(1)),
+                        (now + Duration::minutes(2), now + Duration::minutes(5)),
+                    ],
+                    entry_notes: vec![None; 2],
+                    running_since: None,
+                },
+                TaskRecord {
+                    name: "Test2".to_string(),
+                    tags: vec![],
+                    notes: vec![],
+                    entries: vec![],
+                    entry_notes: vec![],
+                    running_since: Some(now + Duration::minutes(15)),
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_task_manager_from_records_round_trip() {
+        let now = Local::now();
+        let task1 = StoppedTask {
+            name: "Test1".to_string(),
+            tags: vec![],
+            notes: vec![],
+            entries: vec![CompletedTimeEntry::new(now, now + Duration::minutes(1))],
+            last_entry: CompletedTimeEntry::new(
+                now + Duration::minutes(2),
+                now + Duration::minutes(5),
+            ),
+        };
+        let task2 = RunningTask {
+            name: "Test2".to_string(),
+            tags: vec![],
+            notes: vec![],
+            entries: vec![CompletedTimeEntry::new(
+                now + Duration::minutes(5),
+                now + Duration::minutes(10),
+            )],
+            last_entry: OngoingTimeEntry::new(now + Duration::minutes(15)),
+        };
+        let task_manager = TaskManager {
+            stopped: vec![task1.clone()],
+            running: Some(task2.clone()),
+            ..Default::default()
+        };
+        let result = TaskManager::from_records(task_manager.to_records()).unwrap();
+        assert_eq!(result.stopped, vec![task1]);
+        assert_eq!(result.running, Some(task2));
+    }
+
+    #[test]
+    fn test_task_manager_from_records_when_stopped_without_entries() {
+        let records = vec![TaskRecord {
+            name: "Test".to_string(),
+            tags: vec![],
+            notes: vec![],
+            entries: vec![],
+            entry_notes: vec![],
+            running_since: None,
+        }];
+        assert!(TaskManager::from_records(records).is_err());
+    }
+
+    #[test]
+    fn test_task_manager_from_records_when_unordered_entries() {
+        let now = Local::now();
+        let records = vec![TaskRecord {
+            name: "Test".to_string(),
+            tags: vec![],
+            notes: vec![],
+            entries: vec![
+                (now + Duration::minutes(2), now + Duration::minutes(5)),
+                (now, now + Duration::minutes(1)),
+            ],
+            entry_notes: vec![None; 2],
+            running_since: None,
+        }];
+        assert!(TaskManager::from_records(records).is_err());
+    }
+
+    #[test]
+    fn test_task_manager_from_records_when_multiple_running() {
+        let now = Local::now();
+        let records = vec![
+            TaskRecord {
+                name: "Test1".to_string(),
+                tags: vec![],
+                notes: vec![],
+                entries: vec![],
+                entry_notes: vec![],
+                running_since: Some(now),
+            },
+            TaskRecord {
+                name: "Test2".to_string(),
+                tags: vec![],
+                notes: vec![],
+                entries: vec![],
+                entry_notes: vec![],
+                running_since: Some(now),
+            },
+        ];
+        assert!(TaskManager::from_records(records).is_err());
+    }
 }
 
 #[test]
@@ -1393,10 +2721,3 @@ fn test_format_duration() {
     assert_eq!(format_duration(Duration::minutes(10)), "00:10");
     assert_eq!(format_duration(Duration::minutes(140)), "02:20");
 }
-
-#[test]
-fn test_percent() {
-    assert_eq!(percent(0, 10), 0.0);
-    assert_eq!(percent(1, 10), 10.0);
-    assert_eq!(percent(10, 10), 100.0);
-}
@@ -0,0 +1,88 @@
+/*
+ * tasklog - A command-line task tracking tool.
+ *
+ * Copyright (C) 2024 Lucas M. de Jong Larrarte
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with this program. If not, see <https://www.gnu.org/licenses/>.
+ */
+#[cfg(test)]
+mod test;
+
+/// Separator between the levels of a task path, as in `acme/website/login-bug`.
+pub const SEPARATOR: char = '/';
+
+/// Returns the levels of a task path, leaving out empty ones.
+pub fn levels(name: &str) -> Vec<&str> {
+    name.split(SEPARATOR)
+        .filter(|level| !level.is_empty())
+        .collect()
+}
+
+/// Returns the path of the ancestor of a task at the given depth: `acme/website` for
+/// `acme/website/login-bug` at depth 2. Tasks with no more levels than `depth` are returned
+/// whole.
+pub fn ancestor(name: &str, depth: usize) -> String {
+    let levels = levels(name);
+    if levels.len() <= depth {
+        return name.to_string();
+    }
+    levels[..depth].join(&SEPARATOR.to_string())
+}
+
+/// Checks if a task is the task at `path`, or one of its descendants: `acme/website/login-bug`
+/// is within `acme` and `acme/website`, but not within `acme/web`.
+pub fn is_within(name: &str, path: &str) -> bool {
+    let path = levels(path);
+    levels(name).starts_with(&path)
+}
+
+/// A level of a tree of task paths.
+#[derive(Debug, Default)]
+struct Node<'a> {
+    level: &'a str,
+    children: Vec<Node<'a>>,
+}
+
+/// Renders task names as a tree of their levels, indenting each level under its parent.
+/// Levels are listed in the order they first appear in.
+pub fn tree<'a>(names: impl IntoIterator<Item = &'a str>) -> String {
+    let mut root = Node::default();
+    for name in names {
+        let mut node = &mut root;
+        for level in levels(name) {
+            let index = match node.children.iter().position(|child| child.level == level) {
+                Some(index) => index,
+                None => {
+                    node.children.push(Node {
+                        level,
+                        children: vec![],
+                    });
+                    node.children.len() - 1
+                }
+            };
+            node = &mut node.children[index];
+        }
+    }
+    let mut tree = String::new();
+    render(&root.children, 0, &mut tree);
+    tree
+}
+
+/// Renders the nodes and their descendants at the given depth.
+fn render(nodes: &[Node], depth: usize, tree: &mut String) {
+    for node in nodes {
+        *tree += &format!("{:indent$}{}\n", "", node.level, indent = depth * 2);
+        render(&node.children, depth + 1, tree);
+    }
+}
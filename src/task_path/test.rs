@@ -0,0 +1,66 @@
+/*
+ * tasklog - A command-line task tracking tool.
+ *
+ * Copyright (C) 2024 Lucas M. de Jong Larrarte
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with this program. If not, see <https://www.gnu.org/licenses/>.
+ */
+use super::*;
+
+#[test]
+fn test_levels() {
+    assert_eq!(
+        levels("acme/website/login-bug"),
+        vec!["acme", "website", "login-bug"]
+    );
+    assert_eq!(levels("/acme//website/"), vec!["acme", "website"]);
+    assert_eq!(levels("coding"), vec!["coding"]);
+}
+
+#[test]
+fn test_ancestor() {
+    assert_eq!(ancestor("acme/website/login-bug", 1), "acme");
+    assert_eq!(ancestor("acme/website/login-bug", 2), "acme/website");
+    assert_eq!(
+        ancestor("acme/website/login-bug", 3),
+        "acme/website/login-bug"
+    );
+    assert_eq!(ancestor("coding", 2), "coding");
+}
+
+#[test]
+fn test_is_within() {
+    assert!(is_within("acme/website/login-bug", "acme"));
+    assert!(is_within("acme/website/login-bug", "acme/website/"));
+    assert!(is_within(
+        "acme/website/login-bug",
+        "acme/website/login-bug"
+    ));
+    assert!(!is_within("acme/website/login-bug", "acme/web"));
+    assert!(!is_within("acme", "acme/website"));
+}
+
+#[test]
+fn test_tree() {
+    let names = [
+        "acme/website/login-bug",
+        "coding",
+        "acme/api",
+        "acme/website/signup",
+    ];
+    assert_eq!(
+        tree(names),
+        "acme\n  website\n    login-bug\n    signup\n  api\ncoding\n"
+    );
+}
@@ -0,0 +1,171 @@
+/*
+ * tasklog - A command-line task tracking tool.
+ *
+ * Copyright (C) 2024 Lucas M. de Jong Larrarte
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with this program. If not, see <https://www.gnu.org/licenses/>.
+ */
+use super::*;
+use crate::test_util::{date, time};
+use tempfile::TempDir;
+
+fn config() -> Config {
+    Config {
+        day_start: "04:30".to_string(),
+        ..Config::default()
+    }
+}
+
+/// Saves a day with a task "Test" worked on from 9:00, still running if `running` is set.
+fn save_day(storage: &dyn Storage, day: u32, running: bool) {
+    let mut tasks = TaskManager::new();
+    tasks
+        .start_new_task("Test".to_string(), time(day, 9, 0))
+        .unwrap();
+    if !running {
+        tasks.stop_running_task_with_time(time(day, 10, 0)).unwrap();
+    }
+    storage.save(&tasks, date(day)).unwrap();
+}
+
+#[test]
+fn test_carry_over_running_task() {
+    let storage = MemoryStorage::new();
+    save_day(&storage, 15, true);
+    carry_over_running_task(date(17), &storage, &config()).unwrap();
+    let first = storage.load(date(15)).unwrap();
+    assert_eq!(first.running_task(), None);
+    let record = first.task_record("Test").unwrap();
+    assert_eq!(record.entries, vec![(time(15, 9, 0), time(16, 4, 30))]);
+    let record = storage.load(date(16)).unwrap().task_record("Test").unwrap();
+    assert_eq!(record.entries, vec![(time(16, 4, 30), time(17, 4, 30))]);
+    let last = storage.load(date(17)).unwrap();
+    assert_eq!(last.running_task(), Some("Test"));
+    let record = last.task_record("Test").unwrap();
+    assert_eq!(record.running_since, Some(time(17, 4, 30)));
+}
+
+#[test]
+fn test_carry_over_running_task_when_none_running() {
+    let storage = MemoryStorage::new();
+    save_day(&storage, 15, false);
+    carry_over_running_task(date(17), &storage, &config()).unwrap();
+    assert_eq!(storage.dates().unwrap(), vec![date(15)]);
+    assert!(storage.load(date(17)).unwrap().list_tasks().is_empty());
+}
+
+#[test]
+fn test_carry_over_running_task_when_previous_day_is_damaged() {
+    let dir = TempDir::new().unwrap();
+    let storage = JsonDirStorage::new(dir.path(), StdDuration::from_millis(100)).unwrap();
+    save_day(&storage, 15, true);
+    fs::write(dir.path().join("2024-07-16.json"), r#"{"stopped":1}"#).unwrap();
+    carry_over_running_task(date(17), &storage, &config()).unwrap();
+    assert_eq!(storage.load(date(15)).unwrap().running_task(), Some("Test"));
+    assert!(storage.load(date(17)).unwrap().list_tasks().is_empty());
+}
+
+#[test]
+fn test_carry_over_running_task_when_previous_day_is_truncated() {
+    let dir = TempDir::new().unwrap();
+    let storage = JsonDirStorage::new(dir.path(), StdDuration::from_millis(100)).unwrap();
+    save_day(&storage, 15, true);
+    let file = dir.path().join("2024-07-16.json");
+    fs::write(&file, r#"{"schema_version":2,"tasks":{"stopp"#).unwrap();
+    let _lock = storage.lock().unwrap();
+    let error = carry_over_running_task(date(17), &storage, &config()).unwrap_err();
+    assert!(matches!(error, TaskError::TruncatedFile(..)));
+    assert_eq!(storage.load(date(15)).unwrap().running_task(), Some("Test"));
+    assert!(storage.load(date(17)).unwrap().list_tasks().is_empty());
+}
+
+#[test]
+fn test_restore_journals() {
+    let dir = TempDir::new().unwrap();
+    let storage = MemoryStorage::new();
+    mutate(date(15), "start Test", &storage, |tasks| {
+        tasks.start_new_task("Test".to_string(), time(15, 9, 0))
+    })
+    .unwrap();
+    let archive = dir.path().join("backup.tar.gz");
+    Backup::collect(&storage, None)
+        .unwrap()
+        .write(&archive)
+        .unwrap();
+    let config = Config {
+        backup_keep: 0,
+        ..config()
+    };
+    let restored = MemoryStorage::new();
+    restore(archive, true, &restored, &config).unwrap();
+    assert_eq!(
+        restored.load(date(15)).unwrap(),
+        storage.load(date(15)).unwrap()
+    );
+    let journal = restored.journal(date(15)).unwrap();
+    assert!(journal.starts_with(&storage.journal(date(15)).unwrap()));
+    assert_eq!(journal.len(), 2);
+}
+
+#[test]
+fn test_undo_rename_restores_catalogue() {
+    let storage = MemoryStorage::new();
+    mutate(date(16), "start Test", &storage, |tasks| {
+        tasks.start_new_task("Test".to_string(), time(16, 9, 0))
+    })
+    .unwrap();
+    mutate(date(16), "rename Test Renamed", &storage, |tasks| {
+        tasks.rename_task("Test".to_string(), "Renamed".to_string())
+    })
+    .unwrap();
+    assert_eq!(load_catalogue(&storage).unwrap().tasks()[0].name, "Renamed");
+    undo(1, false, &storage).unwrap();
+    let catalogue = load_catalogue(&storage).unwrap();
+    let names: Vec<_> = catalogue.tasks().iter().map(|task| &task.name).collect();
+    assert_eq!(names, ["Test"]);
+    assert!(catalogue.get("Renamed").is_none());
+    redo(1, &storage).unwrap();
+    let catalogue = load_catalogue(&storage).unwrap();
+    let names: Vec<_> = catalogue.tasks().iter().map(|task| &task.name).collect();
+    assert_eq!(names, ["Renamed"]);
+    assert_eq!(catalogue.get("Renamed").unwrap().aliases, ["Test"]);
+}
+
+#[test]
+fn test_add_when_end_outside_day() {
+    let storage = MemoryStorage::new();
+    let time_expr = |value: &str| value.parse::<TimeExpr>().unwrap();
+    for end in ["2024-07-17T04:30", "2024-07-17T10:00", "2024-07-15T10:00"] {
+        let result = add(
+            "Test".to_string(),
+            time_expr("2024-07-16T09:00"),
+            time_expr(end),
+            None,
+            &storage,
+            &config(),
+        );
+        assert!(matches!(result, Err(TaskError::TimeOutsideDate(_, day)) if day == date(16)));
+    }
+    add(
+        "Test".to_string(),
+        time_expr("2024-07-16T09:00"),
+        time_expr("2024-07-16T04:00"),
+        None,
+        &storage,
+        &config(),
+    )
+    .unwrap();
+    let record = storage.load(date(16)).unwrap().task_record("Test").unwrap();
+    assert_eq!(record.entries, vec![(time(16, 9, 0), time(17, 4, 0))]);
+}
@@ -0,0 +1,36 @@
+/*
+ * tasklog - A command-line task tracking tool.
+ *
+ * Copyright (C) 2024 Lucas M. de Jong Larrarte
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with this program. If not, see <https://www.gnu.org/licenses/>.
+ */
+use chrono::{DateTime, Local, NaiveDate, NaiveTime, TimeZone};
+
+/// Returns the given day of July 2024, the month the tests take place in.
+pub fn date(day: u32) -> NaiveDate {
+    NaiveDate::from_ymd_opt(2024, 7, day).unwrap()
+}
+
+/// Returns the given time of the given day of July 2024.
+pub fn time(day: u32, hour: u32, minute: u32) -> DateTime<Local> {
+    Local
+        .with_ymd_and_hms(2024, 7, day, hour, minute, 0)
+        .unwrap()
+}
+
+/// Returns the time days start at in the tests.
+pub fn day_start() -> NaiveTime {
+    NaiveTime::from_hms_opt(4, 30, 0).unwrap()
+}
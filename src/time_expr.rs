@@ -0,0 +1,309 @@
+/*
+ * tasklog - A command-line task tracking tool.
+ *
+ * Copyright (C) 2024 Lucas M. de Jong Larrarte
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with this program. If not, see <https://www.gnu.org/licenses/>.
+ */
+#[cfg(test)]
+mod test;
+
+use std::str::FromStr;
+
+use chrono::{
+    DateTime, Datelike, Days, Duration, Local, NaiveDate, NaiveDateTime, NaiveTime, Weekday,
+};
+
+use crate::{TaskError, TaskResult};
+
+/// An expression of a point in time, relative to the moment it is resolved at, such as `9:15`,
+/// `yesterday 17:30`, `-20m`, `noon` or `last friday 14:00`.
+///
+/// Days are logical days, starting at a configurable time rather than at midnight, so that a
+/// time after midnight but before the start of the day belongs to the day before.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum TimeExpr {
+    /// The moment the expression is resolved at: `now`.
+    Now,
+    /// A duration before now: `-20m`, `1h30m ago` or `2 days ago`.
+    Ago(Duration),
+    /// A day, with a time of day if given: `9:15`, `yesterday`, `2024-07-16T17:30`.
+    At(DayExpr, Option<NaiveTime>),
+}
+
+/// An expression of a logical day.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum DayExpr {
+    /// The day it is now: `today`, or nothing when only a time of day is given.
+    Today,
+    /// A number of days before today: `yesterday`.
+    DaysAgo(u64),
+    /// A given date: `2024-07-16`.
+    Date(NaiveDate),
+    /// The last given weekday: `friday` is today on a Friday, `last friday` is a week ago.
+    Weekday { weekday: Weekday, last: bool },
+}
+
+impl TimeExpr {
+    /// Checks if the expression names a moment, rather than a whole day.
+    pub fn has_time(&self) -> bool {
+        !matches!(self, TimeExpr::At(_, None))
+    }
+
+    /// Resolves the expression to a time, relative to `now` and with days starting at
+    /// `day_start`. A day without a time of day stands for its start.
+    pub fn resolve(
+        &self,
+        now: DateTime<Local>,
+        day_start: NaiveTime,
+    ) -> TaskResult<DateTime<Local>> {
+        self.resolve_on_day(logical_date(now, day_start), now, day_start)
+    }
+
+    /// Resolves the expression to a time like [TimeExpr::resolve], but with `today` as the day
+    /// that days and times of day are relative to.
+    pub fn resolve_on_day(
+        &self,
+        today: NaiveDate,
+        now: DateTime<Local>,
+        day_start: NaiveTime,
+    ) -> TaskResult<DateTime<Local>> {
+        match *self {
+            TimeExpr::Now => Ok(now),
+            TimeExpr::Ago(duration) => before(now, duration),
+            TimeExpr::At(day, time) => {
+                let date = day.resolve(today);
+                let time = time.unwrap_or(day_start);
+                let date = if time >= day_start {
+                    date
+                } else {
+                    date.succ_opt()
+                        .expect("date should not be the last representable date")
+                };
+                local_time(date.and_time(time))
+            }
+        }
+    }
+
+    /// Resolves the expression to the logical day it falls on, relative to `now` and with days
+    /// starting at `day_start`.
+    pub fn resolve_date(
+        &self,
+        now: DateTime<Local>,
+        day_start: NaiveTime,
+    ) -> TaskResult<NaiveDate> {
+        match *self {
+            TimeExpr::At(day, None) => Ok(day.resolve(logical_date(now, day_start))),
+            _ => Ok(logical_date(self.resolve(now, day_start)?, day_start)),
+        }
+    }
+}
+
+impl DayExpr {
+    /// Resolves the expression to a date, `today` being the current logical day.
+    fn resolve(&self, today: NaiveDate) -> NaiveDate {
+        let days_ago = match *self {
+            DayExpr::Today => 0,
+            DayExpr::DaysAgo(days) => days,
+            DayExpr::Date(date) => return date,
+            DayExpr::Weekday { weekday, last } => {
+                let today = today.weekday().num_days_from_monday();
+                let days = (today + 7 - weekday.num_days_from_monday()) % 7;
+                if last && days == 0 {
+                    7
+                } else {
+                    days as u64
+                }
+            }
+        };
+        today
+            .checked_sub_days(Days::new(days_ago))
+            .expect("days ago should be a representable date")
+    }
+}
+
+impl FromStr for TimeExpr {
+    type Err = String;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        let invalid = || {
+            format!(
+                "'{value}' is not a time, expected e.g. 9:15, yesterday 17:30, -20m, noon or last friday 14:00"
+            )
+        };
+        let value = value.to_lowercase();
+        let tokens: Vec<_> = value.split_whitespace().collect();
+        match tokens.as_slice() {
+            [] => return Err(invalid()),
+            ["now"] => return Ok(TimeExpr::Now),
+            [duration] if duration.starts_with('-') => {
+                return parse_duration(&duration[1..]).map(TimeExpr::Ago)
+            }
+            [duration @ .., "ago"] => return parse_duration(&duration.concat()).map(TimeExpr::Ago),
+            _ => {}
+        }
+        let mut day = None;
+        let mut time = None;
+        let mut tokens = tokens.into_iter().peekable();
+        while let Some(token) = tokens.next() {
+            let (day_part, time_part) = if token == "last" {
+                let weekday = tokens.next().and_then(|token| token.parse().ok());
+                let weekday = weekday.ok_or_else(invalid)?;
+                (
+                    Some(DayExpr::Weekday {
+                        weekday,
+                        last: true,
+                    }),
+                    None,
+                )
+            } else if let Some(day) = parse_day(token) {
+                (Some(day), None)
+            } else if let Ok(date_time) = NaiveDateTime::parse_from_str(token, "%Y-%m-%dt%H:%M") {
+                (
+                    Some(DayExpr::Date(date_time.date())),
+                    Some(date_time.time()),
+                )
+            } else if let Some(meridiem) = tokens.next_if(|next| *next == "am" || *next == "pm") {
+                (
+                    None,
+                    Some(parse_time_of_day(&format!("{token}{meridiem}")).ok_or_else(invalid)?),
+                )
+            } else {
+                (None, Some(parse_time_of_day(token).ok_or_else(invalid)?))
+            };
+            let repeated_day = day_part.is_some_and(|_| day.is_some());
+            let repeated_time = time_part.is_some_and(|_| time.is_some());
+            if repeated_day || repeated_time {
+                return Err(invalid());
+            }
+            day = day.or(day_part);
+            time = time.or(time_part);
+        }
+        Ok(TimeExpr::At(day.unwrap_or(DayExpr::Today), time))
+    }
+}
+
+/// Parses a word naming a day, such as `today`, `yesterday`, `friday` or `2024-07-16`.
+fn parse_day(token: &str) -> Option<DayExpr> {
+    match token {
+        "today" => Some(DayExpr::Today),
+        "yesterday" => Some(DayExpr::DaysAgo(1)),
+        _ => token
+            .parse()
+            .ok()
+            .map(|weekday| DayExpr::Weekday {
+                weekday,
+                last: false,
+            })
+            .or_else(|| {
+                NaiveDate::parse_from_str(token, "%F")
+                    .ok()
+                    .map(DayExpr::Date)
+            }),
+    }
+}
+
+/// Parses a time of day, such as `9:15`, `17:30:05`, `noon`, `midnight`, `9am` or `5:30pm`.
+fn parse_time_of_day(token: &str) -> Option<NaiveTime> {
+    match token {
+        "noon" => return NaiveTime::from_hms_opt(12, 0, 0),
+        "midnight" => return NaiveTime::from_hms_opt(0, 0, 0),
+        _ => {}
+    }
+    let parse = |token: &str| {
+        NaiveTime::parse_from_str(token, "%H:%M")
+            .or_else(|_| NaiveTime::parse_from_str(token, "%H:%M:%S"))
+            .ok()
+    };
+    let (clock, offset) = match (token.strip_suffix("am"), token.strip_suffix("pm")) {
+        (Some(clock), _) => (clock, 0),
+        (_, Some(clock)) => (clock, 12),
+        _ => return parse(token),
+    };
+    let (hour, minutes) = clock.split_once(':').unwrap_or((clock, "00"));
+    let hour: u32 = hour.parse().ok().filter(|hour| (1..=12).contains(hour))?;
+    parse(&format!("{}:{minutes}", hour % 12 + offset))
+}
+
+/// Parses a duration made of days, hours and minutes, such as `15m`, `2h`, `1h30m`, `2 days` or
+/// `90 minutes`. A number without a unit is taken as minutes.
+pub fn parse_duration(value: &str) -> Result<Duration, String> {
+    let invalid = || format!("'{value}' is not a duration, expected e.g. 15m, 2h or 1h30m");
+    let value: String = value.split_whitespace().collect();
+    if value.is_empty() {
+        return Err(invalid());
+    }
+    let mut total = Duration::zero();
+    let mut rest = value.as_str();
+    while !rest.is_empty() {
+        let digits = rest
+            .find(|c: char| !c.is_ascii_digit())
+            .unwrap_or(rest.len());
+        let (number, after) = rest.split_at(digits);
+        let letters = after
+            .find(|c: char| !c.is_ascii_alphabetic())
+            .unwrap_or(after.len());
+        let (unit, after) = after.split_at(letters);
+        let amount: i64 = number.parse().map_err(|_| invalid())?;
+        let duration = match unit {
+            "d" | "day" | "days" => Duration::try_days(amount),
+            "h" | "hr" | "hrs" | "hour" | "hours" => Duration::try_hours(amount),
+            "m" | "min" | "mins" | "minute" | "minutes" => Duration::try_minutes(amount),
+            "" if digits == value.len() => Duration::try_minutes(amount),
+            _ => None,
+        };
+        total = duration
+            .and_then(|duration| total.checked_add(&duration))
+            .ok_or_else(invalid)?;
+        rest = after;
+    }
+    Ok(total)
+}
+
+/// Parses an expression that names a moment rather than a whole day, for options taking a time.
+pub fn parse_time(value: &str) -> Result<TimeExpr, String> {
+    let expr: TimeExpr = value.parse()?;
+    if expr.has_time() {
+        Ok(expr)
+    } else {
+        Err(format!(
+            "'{value}' is a day, expected a time such as {value} 9:15"
+        ))
+    }
+}
+
+/// Returns the time `duration` before `now`, failing if that is beyond the representable times.
+pub fn before(now: DateTime<Local>, duration: Duration) -> TaskResult<DateTime<Local>> {
+    now.checked_sub_signed(duration)
+        .ok_or(TaskError::TimeOutOfRange)
+}
+
+/// Returns the logical day a time falls on, days starting at `day_start`.
+pub fn logical_date(time: DateTime<Local>, day_start: NaiveTime) -> NaiveDate {
+    let date = time.date_naive();
+    if time.time() >= day_start {
+        date
+    } else {
+        date.pred_opt()
+            .expect("date should not be the first representable date")
+    }
+}
+
+/// Converts a local date and time to [Local], failing for times skipped by a change to daylight
+/// saving time.
+fn local_time(time: NaiveDateTime) -> TaskResult<DateTime<Local>> {
+    time.and_local_timezone(Local)
+        .earliest()
+        .ok_or_else(|| TaskError::NonexistentTime(time.format("%F %H:%M").to_string()))
+}
@@ -0,0 +1,179 @@
+/*
+ * tasklog - A command-line task tracking tool.
+ *
+ * Copyright (C) 2024 Lucas M. de Jong Larrarte
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with this program. If not, see <https://www.gnu.org/licenses/>.
+ */
+use super::*;
+use crate::test_util::{date, day_start, time};
+
+/// Friday 2024-07-19 at 10:00.
+fn now() -> DateTime<Local> {
+    time(19, 10, 0)
+}
+
+fn resolve(expr: &str) -> DateTime<Local> {
+    expr.parse::<TimeExpr>()
+        .unwrap()
+        .resolve(now(), day_start())
+        .unwrap()
+}
+
+fn resolve_date(expr: &str) -> NaiveDate {
+    expr.parse::<TimeExpr>()
+        .unwrap()
+        .resolve_date(now(), day_start())
+        .unwrap()
+}
+
+#[test]
+fn test_resolve_time_of_day() {
+    assert_eq!(resolve("9:15"), time(19, 9, 15));
+    assert_eq!(resolve("09:15"), time(19, 9, 15));
+    assert_eq!(resolve("noon"), time(19, 12, 0));
+    assert_eq!(resolve("9am"), time(19, 9, 0));
+    assert_eq!(resolve("5:30 PM"), time(19, 17, 30));
+    assert_eq!(resolve("12am"), time(20, 0, 0));
+}
+
+#[test]
+fn test_resolve_time_before_day_start() {
+    assert_eq!(resolve("1:00"), time(20, 1, 0));
+    assert_eq!(resolve("midnight"), time(20, 0, 0));
+    assert_eq!(resolve("yesterday 2:00"), time(19, 2, 0));
+    let after_midnight = time(20, 1, 0);
+    let expr: TimeExpr = "23:00".parse().unwrap();
+    assert_eq!(
+        expr.resolve(after_midnight, day_start()).unwrap(),
+        time(19, 23, 0)
+    );
+}
+
+#[test]
+fn test_resolve_day_and_time() {
+    assert_eq!(resolve("yesterday 17:30"), time(18, 17, 30));
+    assert_eq!(resolve("17:30 yesterday"), time(18, 17, 30));
+    assert_eq!(resolve("today 8:00"), time(19, 8, 0));
+    assert_eq!(resolve("2024-07-16 8:00"), time(16, 8, 0));
+    assert_eq!(resolve("2024-07-16T08:00"), time(16, 8, 0));
+    assert_eq!(resolve("yesterday"), time(18, 4, 30));
+}
+
+#[test]
+fn test_resolve_weekdays() {
+    assert_eq!(resolve("friday 14:00"), time(19, 14, 0));
+    assert_eq!(resolve("last friday 14:00"), time(12, 14, 0));
+    assert_eq!(resolve("last Mon 9:00"), time(15, 9, 0));
+    assert_eq!(resolve("wednesday noon"), time(17, 12, 0));
+}
+
+#[test]
+fn test_resolve_relative() {
+    assert_eq!(resolve("now"), now());
+    assert_eq!(resolve("-20m"), time(19, 9, 40));
+    assert_eq!(resolve("-1h30m"), time(19, 8, 30));
+    assert_eq!(resolve("2 hours ago"), time(19, 8, 0));
+    assert_eq!(resolve("1d ago"), time(18, 10, 0));
+}
+
+#[test]
+fn test_resolve_on_day() {
+    let resolve_on_day = |expr: &str| {
+        expr.parse::<TimeExpr>()
+            .unwrap()
+            .resolve_on_day(date(16), now(), day_start())
+            .unwrap()
+    };
+    assert_eq!(resolve_on_day("9:15"), time(16, 9, 15));
+    assert_eq!(resolve_on_day("1:00"), time(17, 1, 0));
+    assert_eq!(resolve_on_day("yesterday 9:15"), time(15, 9, 15));
+    assert_eq!(resolve_on_day("-20m"), time(19, 9, 40));
+}
+
+#[test]
+fn test_resolve_date() {
+    assert_eq!(resolve_date("today"), date(19));
+    assert_eq!(resolve_date("yesterday"), date(18));
+    assert_eq!(resolve_date("last friday"), date(12));
+    assert_eq!(resolve_date("2024-07-01"), date(1));
+    assert_eq!(resolve_date("1:00"), date(19));
+    assert_eq!(resolve_date("-10h"), date(18));
+}
+
+#[test]
+fn test_parse_when_invalid() {
+    for expr in [
+        "",
+        "soon",
+        "25:00",
+        "13pm",
+        "9",
+        "yesterday today",
+        "9:00 10:00",
+        "last",
+        "last week",
+        "-",
+        "-20x",
+        "ago",
+    ] {
+        assert!(expr.parse::<TimeExpr>().is_err(), "{expr}");
+    }
+}
+
+#[test]
+fn test_parse_time_when_only_a_day() {
+    assert!(parse_time("yesterday").is_err());
+    assert!(parse_time("yesterday 9:00").is_ok());
+}
+
+#[test]
+fn test_parse_duration() {
+    assert_eq!(parse_duration("15m"), Ok(Duration::minutes(15)));
+    assert_eq!(parse_duration("90"), Ok(Duration::minutes(90)));
+    assert_eq!(parse_duration("1h30m"), Ok(Duration::minutes(90)));
+    assert_eq!(parse_duration("2 days"), Ok(Duration::days(2)));
+    assert!(parse_duration("").is_err());
+    assert!(parse_duration("h").is_err());
+    assert!(parse_duration("15s").is_err());
+    assert!(parse_duration("10m5").is_err());
+    assert!(parse_duration("99999999999999999h").is_err());
+}
+
+#[test]
+fn test_logical_date() {
+    assert_eq!(logical_date(time(19, 4, 30), day_start()), date(19));
+    assert_eq!(logical_date(time(19, 4, 29), day_start()), date(18));
+}
+
+#[test]
+fn test_before() {
+    assert_eq!(
+        before(now(), Duration::minutes(90)).unwrap(),
+        time(19, 8, 30)
+    );
+    let duration = parse_duration("10000000000d").unwrap();
+    let error = before(now(), duration).unwrap_err();
+    assert!(matches!(error, TaskError::TimeOutOfRange));
+}
+
+#[test]
+fn test_resolve_when_too_far_ago() {
+    let error = "-10000000000d"
+        .parse::<TimeExpr>()
+        .unwrap()
+        .resolve(now(), day_start())
+        .unwrap_err();
+    assert!(matches!(error, TaskError::TimeOutOfRange));
+}